@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TABLE users
+ADD COLUMN anonymized_at TIMESTAMPTZ;
+
+CREATE INDEX idx_users_purge ON users (deleted_at)
+WHERE
+    status = 'DELETED'
+    AND anonymized_at IS NULL;
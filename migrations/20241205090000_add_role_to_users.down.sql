@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE users
+DROP COLUMN IF EXISTS role;
+
+DROP TYPE IF EXISTS user_role;
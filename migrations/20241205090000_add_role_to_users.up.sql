@@ -0,0 +1,5 @@
+-- Add up migration script here
+CREATE TYPE user_role AS ENUM ('USER', 'ADMIN');
+
+ALTER TABLE users
+ADD COLUMN role user_role DEFAULT 'USER' NOT NULL;
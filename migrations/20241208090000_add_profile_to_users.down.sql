@@ -0,0 +1,8 @@
+-- Add down migration script here
+ALTER TABLE users
+DROP COLUMN IF EXISTS avatar_updated_at,
+DROP COLUMN IF EXISTS bio,
+DROP COLUMN IF EXISTS timezone,
+DROP COLUMN IF EXISTS locale,
+DROP COLUMN IF EXISTS phone,
+DROP COLUMN IF EXISTS display_name;
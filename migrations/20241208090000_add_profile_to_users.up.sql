@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TABLE users
+ADD COLUMN display_name VARCHAR(100),
+ADD COLUMN phone VARCHAR(16),
+ADD COLUMN locale VARCHAR(35),
+ADD COLUMN timezone VARCHAR(64),
+ADD COLUMN bio VARCHAR(500),
+ADD COLUMN avatar_updated_at TIMESTAMPTZ;
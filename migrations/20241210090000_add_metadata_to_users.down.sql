@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_users_metadata;
+
+ALTER TABLE users
+DROP COLUMN IF EXISTS metadata;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TABLE users
+ADD COLUMN metadata JSONB DEFAULT '{}'::jsonb NOT NULL;
+
+-- jsonb_path_ops only supports containment (@>) but is smaller and faster for it
+CREATE INDEX idx_users_metadata ON users USING GIN (metadata jsonb_path_ops);
@@ -0,0 +1,19 @@
+-- Add up migration script here
+CREATE TABLE
+    email_change_requests (
+        id UUID DEFAULT gen_random_uuid () PRIMARY KEY,
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        new_email VARCHAR(255) NOT NULL,
+        confirm_token_hash VARCHAR(64) UNIQUE NOT NULL,
+        cancel_token_hash VARCHAR(64) UNIQUE NOT NULL,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        expires_at TIMESTAMPTZ NOT NULL,
+        confirmed_at TIMESTAMPTZ,
+        cancelled_at TIMESTAMPTZ
+    );
+
+-- At most one pending change per user
+CREATE UNIQUE INDEX idx_email_change_requests_pending ON email_change_requests (user_id)
+WHERE
+    confirmed_at IS NULL
+    AND cancelled_at IS NULL;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS user_exports;
+
+DROP TYPE IF EXISTS user_export_format;
+
+DROP TYPE IF EXISTS user_export_status;
@@ -0,0 +1,29 @@
+-- Add up migration script here
+CREATE TYPE user_export_status AS ENUM ('PENDING', 'PROCESSING', 'COMPLETED', 'FAILED', 'EXPIRED');
+
+CREATE TYPE user_export_format AS ENUM ('json', 'zip');
+
+CREATE TABLE
+    user_exports (
+        id UUID DEFAULT gen_random_uuid () PRIMARY KEY,
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        status user_export_status DEFAULT 'PENDING' NOT NULL,
+        format user_export_format NOT NULL,
+        storage_key VARCHAR(255),
+        error TEXT,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        started_at TIMESTAMPTZ,
+        completed_at TIMESTAMPTZ,
+        expires_at TIMESTAMPTZ
+    );
+
+CREATE INDEX idx_user_exports_user_id ON user_exports (user_id);
+
+-- At most one export per user is queued or running at a time.
+CREATE UNIQUE INDEX idx_user_exports_active ON user_exports (user_id)
+WHERE
+    status IN ('PENDING', 'PROCESSING');
+
+CREATE INDEX idx_user_exports_queue ON user_exports (created_at)
+WHERE
+    status IN ('PENDING', 'PROCESSING');
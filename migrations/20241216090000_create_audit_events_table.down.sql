@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS trg_audit_events_append_only ON audit_events;
+
+DROP FUNCTION IF EXISTS audit_events_append_only ();
+
+DROP TABLE IF EXISTS audit_events;
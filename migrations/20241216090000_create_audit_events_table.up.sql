@@ -0,0 +1,44 @@
+-- Add up migration script here
+CREATE TABLE
+    audit_events (
+        id UUID DEFAULT gen_random_uuid () PRIMARY KEY,
+        occurred_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        actor_id UUID,
+        action VARCHAR(64) NOT NULL,
+        target_type VARCHAR(32) NOT NULL,
+        target_id UUID,
+        changes JSONB DEFAULT '{}' NOT NULL,
+        ip VARCHAR(45),
+        user_agent VARCHAR(512),
+        request_id VARCHAR(64)
+    );
+
+CREATE INDEX idx_audit_events_occurred_at ON audit_events (occurred_at, id);
+
+CREATE INDEX idx_audit_events_target ON audit_events (target_id, occurred_at);
+
+CREATE INDEX idx_audit_events_actor ON audit_events (actor_id, occurred_at);
+
+CREATE INDEX idx_audit_events_action ON audit_events (action, occurred_at);
+
+-- Events are append-only. The only exception is erasing personal data from the
+-- changes of an anonymized user, which must opt in with `app.audit_redaction`.
+CREATE FUNCTION audit_events_append_only () RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND current_setting('app.audit_redaction', true) = 'on'
+        AND NEW.id = OLD.id
+        AND NEW.occurred_at = OLD.occurred_at
+        AND NEW.action = OLD.action
+        AND NEW.target_id IS NOT DISTINCT FROM OLD.target_id THEN
+        RETURN NEW;
+    END IF;
+
+    RAISE EXCEPTION 'audit_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_audit_events_append_only BEFORE
+UPDATE
+OR DELETE ON audit_events FOR EACH ROW
+EXECUTE FUNCTION audit_events_append_only ();
@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS trg_users_history_delete ON users;
+
+DROP TRIGGER IF EXISTS trg_users_history_update ON users;
+
+DROP FUNCTION IF EXISTS users_history_capture ();
+
+DROP TABLE IF EXISTS users_history;
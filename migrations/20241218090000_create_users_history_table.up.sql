@@ -0,0 +1,41 @@
+-- Add up migration script here
+CREATE TABLE
+    users_history (
+        history_id BIGSERIAL PRIMARY KEY,
+        id UUID NOT NULL,
+        operation VARCHAR(6) NOT NULL,
+        valid_from TIMESTAMPTZ NOT NULL,
+        valid_to TIMESTAMPTZ NOT NULL,
+        record JSONB NOT NULL
+    );
+
+CREATE INDEX idx_users_history_id ON users_history (id, valid_to);
+
+-- Every version of a user row that gets replaced or removed is kept with the period
+-- it was current, `[valid_from, valid_to)`. The password hash is never copied.
+CREATE FUNCTION users_history_capture () RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO users_history (id, operation, valid_from, valid_to, record)
+    VALUES (
+        OLD.id,
+        TG_OP,
+        COALESCE(
+            (SELECT MAX(valid_to) FROM users_history WHERE id = OLD.id),
+            OLD.created_at
+        ),
+        CURRENT_TIMESTAMP,
+        to_jsonb(OLD) - 'password' - 'search_vector'
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER trg_users_history_update
+AFTER
+UPDATE ON users FOR EACH ROW WHEN (OLD IS DISTINCT FROM NEW)
+EXECUTE FUNCTION users_history_capture ();
+
+CREATE TRIGGER trg_users_history_delete
+AFTER DELETE ON users FOR EACH ROW
+EXECUTE FUNCTION users_history_capture ();
@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP FUNCTION IF EXISTS in_tenant (UUID, UUID);
+
+DROP TABLE IF EXISTS memberships;
+
+DROP TABLE IF EXISTS organizations;
+
+DROP TYPE IF EXISTS membership_role;
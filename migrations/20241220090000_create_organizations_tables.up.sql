@@ -0,0 +1,63 @@
+-- Add up migration script here
+CREATE TYPE membership_role AS ENUM ('OWNER', 'ADMIN', 'MEMBER');
+
+CREATE TABLE
+    organizations (
+        id UUID DEFAULT gen_random_uuid () PRIMARY KEY,
+        name VARCHAR(255) NOT NULL,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL
+    );
+
+CREATE TABLE
+    memberships (
+        organization_id UUID NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        role membership_role DEFAULT 'MEMBER' NOT NULL,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        PRIMARY KEY (organization_id, user_id)
+    );
+
+CREATE INDEX idx_memberships_user_id ON memberships (user_id, created_at);
+
+-- Tenant check shared by the user queries, a NULL organization means the query is
+-- not scoped (background jobs and account flows acting on one known user).
+CREATE FUNCTION in_tenant (target_user_id UUID, tenant_id UUID) RETURNS BOOLEAN AS $$
+    SELECT
+        tenant_id IS NULL
+        OR EXISTS (
+            SELECT
+                1
+            FROM
+                memberships
+            WHERE
+                memberships.user_id = target_user_id
+                AND memberships.organization_id = tenant_id
+        );
+$$ LANGUAGE sql STABLE;
+
+-- Users that existed before organizations share one, admins become its owners.
+INSERT INTO
+    organizations (name)
+SELECT
+    'Default'
+WHERE
+    EXISTS (
+        SELECT
+            1
+        FROM
+            users
+    );
+
+INSERT INTO
+    memberships (organization_id, user_id, role)
+SELECT
+    organizations.id,
+    users.id,
+    CASE
+        WHEN users.role = 'ADMIN' THEN 'OWNER'::membership_role
+        ELSE 'MEMBER'::membership_role
+    END
+FROM
+    users
+    CROSS JOIN organizations;
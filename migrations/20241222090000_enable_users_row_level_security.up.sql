@@ -0,0 +1,16 @@
+-- Add up migration script here
+-- Defense in depth for tenant isolation: requests acting in an organization set
+-- `app.current_tenant` for their transaction and only see its members, even where a
+-- query misses its tenant condition. Without the setting (jobs, account flows) every
+-- row is visible. Superusers and BYPASSRLS roles are never subject to policies, the
+-- application has to connect as a regular role for this to apply.
+ALTER TABLE users ENABLE ROW LEVEL SECURITY;
+
+ALTER TABLE users FORCE ROW LEVEL SECURITY;
+
+CREATE POLICY users_tenant_isolation ON users USING (
+    in_tenant (
+        id,
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID
+    )
+);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS invitations;
+
+DROP TYPE IF EXISTS invitation_status;
@@ -0,0 +1,25 @@
+-- Add up migration script here
+CREATE TYPE invitation_status AS ENUM ('PENDING', 'ACCEPTED', 'REVOKED');
+
+CREATE TABLE
+    invitations (
+        id UUID DEFAULT gen_random_uuid () PRIMARY KEY,
+        organization_id UUID NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+        email VARCHAR(255) NOT NULL,
+        role membership_role DEFAULT 'MEMBER' NOT NULL,
+        status invitation_status DEFAULT 'PENDING' NOT NULL,
+        invited_by UUID REFERENCES users (id) ON DELETE SET NULL,
+        accepted_by UUID REFERENCES users (id) ON DELETE SET NULL,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        sent_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        expires_at TIMESTAMPTZ NOT NULL,
+        accepted_at TIMESTAMPTZ,
+        revoked_at TIMESTAMPTZ
+    );
+
+-- At most one pending invitation per address and organization, expired ones are resent
+CREATE UNIQUE INDEX idx_invitations_pending ON invitations (organization_id, email)
+WHERE
+    status = 'PENDING';
+
+CREATE INDEX idx_invitations_organization_id ON invitations (organization_id, created_at);
@@ -0,0 +1,28 @@
+-- Add down migration script here
+-- Enum values cannot be dropped, the type is rebuilt without them.
+UPDATE users
+SET
+    status = 'ACTIVE'
+WHERE
+    status IN ('SUSPENDED', 'LOCKED', 'PENDING');
+
+DROP INDEX IF EXISTS idx_users_purge;
+
+ALTER TYPE user_status
+RENAME TO user_status_old;
+
+CREATE TYPE user_status AS ENUM ('ACTIVE', 'DELETED');
+
+ALTER TABLE users
+ALTER COLUMN status
+DROP DEFAULT,
+ALTER COLUMN status TYPE user_status USING status::TEXT::user_status,
+ALTER COLUMN status
+SET DEFAULT 'ACTIVE';
+
+DROP TYPE user_status_old;
+
+CREATE INDEX idx_users_purge ON users (deleted_at)
+WHERE
+    status = 'DELETED'
+    AND anonymized_at IS NULL;
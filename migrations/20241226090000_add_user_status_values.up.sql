@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TYPE user_status ADD VALUE IF NOT EXISTS 'SUSPENDED';
+
+ALTER TYPE user_status ADD VALUE IF NOT EXISTS 'LOCKED';
+
+ALTER TYPE user_status ADD VALUE IF NOT EXISTS 'PENDING';
@@ -0,0 +1,8 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS user_status_transitions;
+
+UPDATE users
+SET
+    status = 'ACTIVE'
+WHERE
+    status = 'PENDING';
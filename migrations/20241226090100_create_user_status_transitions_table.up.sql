@@ -0,0 +1,21 @@
+-- Add up migration script here
+CREATE TABLE
+    user_status_transitions (
+        id BIGSERIAL PRIMARY KEY,
+        user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+        from_status user_status NOT NULL,
+        to_status user_status NOT NULL,
+        reason VARCHAR(500),
+        actor_id UUID,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL
+    );
+
+CREATE INDEX idx_user_status_transitions_user_id ON user_status_transitions (user_id, created_at);
+
+-- Imported users that have not accepted their invitation yet
+UPDATE users
+SET
+    status = 'PENDING'
+WHERE
+    status = 'ACTIVE'
+    AND password = '!';
@@ -0,0 +1,8 @@
+-- Add up migration script here
+CREATE TABLE
+    user_preferences (
+        user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+        schema_version INTEGER NOT NULL,
+        document JSONB DEFAULT '{}'::jsonb NOT NULL,
+        updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL
+    );
@@ -0,0 +1,23 @@
+-- Add up migration script here
+CREATE TABLE
+    outbox (
+        id BIGSERIAL PRIMARY KEY,
+        aggregate_type VARCHAR(50) NOT NULL,
+        aggregate_id UUID NOT NULL,
+        event_type VARCHAR(100) NOT NULL,
+        payload JSONB NOT NULL,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        attempts INTEGER DEFAULT 0 NOT NULL,
+        next_attempt_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        last_error TEXT,
+        published_at TIMESTAMPTZ
+    );
+
+-- Unpublished events in the order of their aggregate, the relay reads the head of each
+CREATE INDEX idx_outbox_unpublished ON outbox (aggregate_type, aggregate_id, id)
+WHERE
+    published_at IS NULL;
+
+CREATE INDEX idx_outbox_published_at ON outbox (published_at)
+WHERE
+    published_at IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS webhook_deliveries;
+
+DROP TABLE IF EXISTS webhook_endpoints;
+
+DROP TYPE IF EXISTS webhook_delivery_status;
@@ -0,0 +1,47 @@
+-- Add up migration script here
+CREATE TYPE webhook_delivery_status AS ENUM ('PENDING', 'SUCCEEDED', 'FAILED');
+
+CREATE TABLE
+    webhook_endpoints (
+        id UUID DEFAULT gen_random_uuid () PRIMARY KEY,
+        url VARCHAR(2048) NOT NULL,
+        description VARCHAR(255),
+        secret VARCHAR(255) NOT NULL,
+        event_types TEXT[] NOT NULL,
+        enabled BOOLEAN DEFAULT TRUE NOT NULL,
+        consecutive_failures INTEGER DEFAULT 0 NOT NULL,
+        disabled_at TIMESTAMPTZ,
+        created_by UUID REFERENCES users (id) ON DELETE SET NULL,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL
+    );
+
+CREATE TABLE
+    webhook_deliveries (
+        id BIGSERIAL PRIMARY KEY,
+        endpoint_id UUID NOT NULL REFERENCES webhook_endpoints (id) ON DELETE CASCADE,
+        outbox_id BIGINT NOT NULL,
+        redelivery_of BIGINT,
+        event_type VARCHAR(100) NOT NULL,
+        payload JSONB NOT NULL,
+        status webhook_delivery_status DEFAULT 'PENDING' NOT NULL,
+        attempts INTEGER DEFAULT 0 NOT NULL,
+        next_attempt_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        last_attempt_at TIMESTAMPTZ,
+        response_status INTEGER,
+        response_body TEXT,
+        last_error TEXT,
+        created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP NOT NULL,
+        delivered_at TIMESTAMPTZ
+    );
+
+-- The relay publishes at least once, an event is queued once per endpoint
+CREATE UNIQUE INDEX idx_webhook_deliveries_event ON webhook_deliveries (endpoint_id, outbox_id)
+WHERE
+    redelivery_of IS NULL;
+
+CREATE INDEX idx_webhook_deliveries_endpoint_id ON webhook_deliveries (endpoint_id, created_at);
+
+CREATE INDEX idx_webhook_deliveries_pending ON webhook_deliveries (next_attempt_at)
+WHERE
+    status = 'PENDING';
@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP POLICY IF EXISTS users_tenant_isolation ON users;
+
+CREATE POLICY users_tenant_isolation ON users USING (
+    in_tenant (
+        id,
+        NULLIF(current_setting('app.current_tenant', true), '')::UUID
+    )
+);
@@ -0,0 +1,17 @@
+-- Add up migration script here
+-- The tenant isolation policy failed open: without `app.current_tenant` every row
+-- was visible. Now a transaction sees no users unless it either acts in an
+-- organization or opts out explicitly with `app.rls_bypass` (jobs, account flows
+-- and the caller's own record).
+DROP POLICY IF EXISTS users_tenant_isolation ON users;
+
+CREATE POLICY users_tenant_isolation ON users USING (
+    current_setting('app.rls_bypass', true) = 'on'
+    OR (
+        NULLIF(current_setting('app.current_tenant', true), '') IS NOT NULL
+        AND in_tenant (
+            id,
+            NULLIF(current_setting('app.current_tenant', true), '')::UUID
+        )
+    )
+);
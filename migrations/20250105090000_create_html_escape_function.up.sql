@@ -0,0 +1,19 @@
+-- Add up migration script here
+-- Search highlights are rendered as HTML, so user supplied text has to be escaped
+-- before `ts_headline` wraps the matches in `<mark>` tags.
+CREATE FUNCTION html_escape (value TEXT) RETURNS TEXT AS $$
+    SELECT
+        replace(
+            replace(
+                replace(
+                    replace(replace(value, '&', '&amp;'), '<', '&lt;'),
+                    '>',
+                    '&gt;'
+                ),
+                '"',
+                '&quot;'
+            ),
+            '''',
+            '&#39;'
+        );
+$$ LANGUAGE sql IMMUTABLE;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE webhook_deliveries
+ADD COLUMN IF NOT EXISTS response_body TEXT;
@@ -0,0 +1,5 @@
+-- Add up migration script here
+-- Responses of endpoints are not stored anymore, an endpoint pointing into the internal
+-- network would otherwise expose what it answers through the deliveries API.
+ALTER TABLE webhook_deliveries
+DROP COLUMN IF EXISTS response_body;
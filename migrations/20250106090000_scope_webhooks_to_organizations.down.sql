@@ -0,0 +1,8 @@
+-- Add down migration script here
+ALTER TABLE outbox
+DROP COLUMN IF EXISTS organization_ids;
+
+DROP INDEX IF EXISTS idx_webhook_endpoints_organization_id;
+
+ALTER TABLE webhook_endpoints
+DROP COLUMN IF EXISTS organization_id;
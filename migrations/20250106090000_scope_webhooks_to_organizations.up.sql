@@ -0,0 +1,37 @@
+-- Add up migration script here
+-- Endpoints belong to an organization and receive the events of its members only.
+ALTER TABLE webhook_endpoints
+ADD COLUMN organization_id UUID REFERENCES organizations (id) ON DELETE CASCADE;
+
+-- Existing endpoints go to the organization their creator joined first, endpoints
+-- that cannot be attributed would otherwise keep receiving every organization's events.
+UPDATE webhook_endpoints
+SET
+    organization_id = (
+        SELECT
+            organization_id
+        FROM
+            memberships
+        WHERE
+            memberships.user_id = webhook_endpoints.created_by
+        ORDER BY
+            created_at,
+            organization_id
+        LIMIT
+            1
+    );
+
+DELETE FROM webhook_endpoints
+WHERE
+    organization_id IS NULL;
+
+ALTER TABLE webhook_endpoints
+ALTER COLUMN organization_id
+SET NOT NULL;
+
+CREATE INDEX idx_webhook_endpoints_organization_id ON webhook_endpoints (organization_id, created_at);
+
+-- Organizations of the user when the event was written, a deleted user has no
+-- memberships left by the time the relay publishes its last event.
+ALTER TABLE outbox
+ADD COLUMN organization_ids UUID[] DEFAULT '{}' NOT NULL;
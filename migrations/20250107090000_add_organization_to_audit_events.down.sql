@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS idx_audit_events_organization_id;
+
+ALTER TABLE audit_events
+DROP COLUMN IF EXISTS organization_id;
@@ -0,0 +1,40 @@
+-- Add up migration script here
+-- Organization the event was produced in, admins only read the events of theirs.
+ALTER TABLE audit_events
+ADD COLUMN organization_id UUID;
+
+-- Past events are attributed where that is unambiguous: invitations by their
+-- organization, users with a single membership by that one.
+ALTER TABLE audit_events
+DISABLE TRIGGER trg_audit_events_append_only;
+
+UPDATE audit_events
+SET
+    organization_id = invitations.organization_id
+FROM
+    invitations
+WHERE
+    audit_events.target_type = 'invitation'
+    AND audit_events.target_id = invitations.id;
+
+UPDATE audit_events
+SET
+    organization_id = memberships.organization_id
+FROM
+    memberships
+WHERE
+    audit_events.target_type = 'user'
+    AND audit_events.target_id = memberships.user_id
+    AND (
+        SELECT
+            COUNT(*)
+        FROM
+            memberships other
+        WHERE
+            other.user_id = memberships.user_id
+    ) = 1;
+
+ALTER TABLE audit_events
+ENABLE TRIGGER trg_audit_events_append_only;
+
+CREATE INDEX idx_audit_events_organization_id ON audit_events (organization_id, occurred_at);
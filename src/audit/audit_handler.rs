@@ -0,0 +1,36 @@
+use crate::{
+    audit::{audit_service, dto::AuditEventFilterQuery},
+    middlewares::middleware_auth::JwtAuthMiddleware,
+    server::AppState,
+    utils::{errors::AppError, query_paginaton::QueryPagination},
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_qs::actix::QsQuery;
+use sqlx::PgPool;
+
+pub fn configure(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/admin/audit-events")
+            .wrap(JwtAuthMiddleware::new(app_state))
+            .service(web::resource("").route(web::get().to(find_all))),
+    );
+}
+
+async fn find_all(
+    pool: web::Data<PgPool>,
+    query_pagination: QsQuery<QueryPagination>,
+    query_filter: QsQuery<AuditEventFilterQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match audit_service::find_all(
+        &pool,
+        query_pagination.into_inner(),
+        query_filter.into_inner().filter,
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    audit::{
+        dto::AuditEventFilter,
+        entity::{AuditAction, AuditEvent, TARGET_USER},
+    },
+    utils::{
+        audit::{AuditContext, REDACTED},
+        errors::AppError,
+        query_cursor::split_page,
+        query_paginaton::{QueryPagination, ResultWithPagination},
+        query_sort::push_order_by,
+        tenant::Tenant,
+    },
+};
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+/// Records a mutation of the action's target, `conn` must be the transaction of the
+/// mutation so the event is kept exactly when the change is.
+pub async fn insert_audit_event(
+    conn: &mut PgConnection,
+    context: &AuditContext,
+    action: AuditAction,
+    target_id: Uuid,
+    changes: Value,
+) -> Result<(), AppError> {
+    insert_audit_events(conn, context, action, vec![(target_id, changes)]).await
+}
+
+/// Records the same action for several targets in one statement.
+pub async fn insert_audit_events(
+    conn: &mut PgConnection,
+    context: &AuditContext,
+    action: AuditAction,
+    events: Vec<(Uuid, Value)>,
+) -> Result<(), AppError> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = QueryBuilder::new(
+        "INSERT INTO audit_events (actor_id, organization_id, action, target_type, target_id, changes, ip, user_agent, request_id) ",
+    );
+
+    query_builder.push_values(events, |mut row, (target_id, changes)| {
+        row.push_bind(context.actor_id)
+            .push_bind(context.organization_id)
+            .push_bind(action.as_str())
+            .push_bind(action.target_type())
+            .push_bind(target_id)
+            .push_bind(changes)
+            .push_bind(context.ip.clone())
+            .push_bind(context.user_agent.clone())
+            .push_bind(context.request_id.clone());
+    });
+
+    query_builder
+        .build()
+        .execute(conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn find_all_audit_events(
+    pool: &PgPool,
+    tenant: Tenant,
+    query_pagination: QueryPagination,
+    filter: AuditEventFilter,
+) -> Result<ResultWithPagination<Vec<AuditEvent>>, AppError> {
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let sort_keys = query_pagination.sort_keys::<AuditEvent>()?;
+    let cursor = query_pagination.cursor(&sort_keys)?;
+
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM audit_events");
+    filter.push_where(&mut count_query);
+    push_tenant_condition(&mut count_query, tenant);
+
+    let count: i64 = count_query
+        .build_query_scalar::<i64>()
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    let mut query_builder = QueryBuilder::new("SELECT * FROM audit_events");
+    filter.push_where(&mut query_builder);
+    push_tenant_condition(&mut query_builder, tenant);
+
+    match &cursor {
+        Some(cursor) => {
+            cursor.push_condition(&mut query_builder, &sort_keys);
+            push_order_by(&mut query_builder, &cursor.query_keys(&sort_keys));
+        }
+        None => push_order_by(&mut query_builder, &sort_keys),
+    }
+
+    query_builder.push(" LIMIT ").push_bind(limit + 1);
+    query_builder.push(" OFFSET ").push_bind(offset);
+
+    let rows: Vec<AuditEvent> = query_builder
+        .build_query_as::<AuditEvent>()
+        .fetch_all(pool)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    let (result, next_cursor, prev_cursor) =
+        split_page(rows, limit, &sort_keys, cursor.as_ref(), offset > 0);
+
+    Ok(
+        ResultWithPagination::new(limit, page, count, result.len(), result)
+            .with_cursors(next_cursor, prev_cursor),
+    )
+}
+
+/// Keeps the events produced in the tenant's organization. What members did in their
+/// other organizations stays with those.
+fn push_tenant_condition(query_builder: &mut QueryBuilder<'_, Postgres>, tenant: Tenant) {
+    let Some(organization_id) = tenant.organization_id() else {
+        return;
+    };
+
+    query_builder
+        .push(" AND organization_id = ")
+        .push_bind(organization_id);
+}
+
+/// Events about the user or caused by the user, oldest first. The values of changes
+/// the user made to others belong to them, only the changed field names are kept.
+pub async fn find_user_audit_events(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<AuditEvent>, AppError> {
+    let result = sqlx::query_as::<_, AuditEvent>(
+        r#"--sql
+        SELECT
+            id,
+            occurred_at,
+            actor_id,
+            organization_id,
+            action,
+            target_type,
+            target_id,
+            CASE
+                WHEN target_type = $1 AND target_id = $2 THEN changes
+                ELSE COALESCE(
+                    (
+                        SELECT
+                            jsonb_object_agg(key, jsonb_build_object('before', $3::text, 'after', $3::text))
+                        FROM
+                            jsonb_each(changes)
+                    ),
+                    '{}'
+                )
+            END AS changes,
+            ip,
+            user_agent,
+            request_id
+        FROM
+            audit_events
+        WHERE
+            (target_type = $1 AND target_id = $2) OR actor_id = $2
+        ORDER BY
+            occurred_at, id
+        "#,
+    )
+    .bind(TARGET_USER)
+    .bind(user_id)
+    .bind(REDACTED)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Erases the personal data erased users left in the audit log: the values of
+/// changes made to them and the network details of requests they made. Which
+/// fields changed, when and by whom is kept.
+pub async fn redact_user_audit_events(
+    conn: &mut PgConnection,
+    user_ids: &[Uuid],
+) -> Result<(), AppError> {
+    // Lifts the append-only trigger for this transaction only.
+    sqlx::query("SELECT set_config('app.audit_redaction', 'on', true)")
+        .execute(&mut *conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            audit_events
+        SET
+            changes = COALESCE(
+                (
+                    SELECT
+                        jsonb_object_agg(key, jsonb_build_object('before', $1::text, 'after', $1::text))
+                    FROM
+                        jsonb_each(changes)
+                ),
+                '{}'
+            )
+        WHERE
+            target_type = $2 AND target_id = ANY($3)
+        "#,
+    )
+    .bind(REDACTED)
+    .bind(TARGET_USER)
+    .bind(user_ids)
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            audit_events
+        SET
+            ip = NULL,
+            user_agent = NULL
+        WHERE
+            actor_id = ANY($1)
+        "#,
+    )
+    .bind(user_ids)
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    sqlx::query("SELECT set_config('app.audit_redaction', 'off', true)")
+        .execute(conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
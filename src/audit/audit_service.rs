@@ -0,0 +1,30 @@
+use crate::{
+    audit::{audit_query, dto::AuditEventFilter, entity::AuditEvent},
+    utils::{
+        auth::validate_org_admin_in_token, errors::AppError, query_paginaton::QueryPagination,
+        response_data::ResponseDatas, tenant::Tenant,
+    },
+};
+use actix_web::HttpRequest;
+use sqlx::PgPool;
+
+pub async fn find_all(
+    pool: &PgPool,
+    query_pagination: QueryPagination,
+    filter: AuditEventFilter,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<AuditEvent>>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let tenant = Tenant::from_request(req)?;
+
+    let result = audit_query::find_all_audit_events(pool, tenant, query_pagination, filter).await?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        result.data,
+    )
+    .with_cursors(result.next_cursor, result.prev_cursor))
+}
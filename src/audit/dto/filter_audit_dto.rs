@@ -0,0 +1,63 @@
+use crate::utils::query_filter::DateRangeFilter;
+use serde::Deserialize;
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct AuditEventFilter {
+    pub actor_id: Option<Uuid>,
+    pub target_type: Option<String>,
+    pub target_id: Option<Uuid>,
+    pub action: Option<String>,
+    pub request_id: Option<String>,
+    pub occurred_at: Option<DateRangeFilter>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AuditEventFilterQuery {
+    #[serde(default)]
+    pub filter: AuditEventFilter,
+}
+
+impl AuditEventFilter {
+    pub fn push_where(&self, query_builder: &mut QueryBuilder<'_, Postgres>) {
+        query_builder.push(" WHERE TRUE");
+
+        if let Some(actor_id) = self.actor_id {
+            query_builder.push(" AND actor_id = ").push_bind(actor_id);
+        }
+
+        if let Some(target_type) = &self.target_type {
+            query_builder
+                .push(" AND target_type = ")
+                .push_bind(target_type.clone());
+        }
+
+        if let Some(target_id) = self.target_id {
+            query_builder.push(" AND target_id = ").push_bind(target_id);
+        }
+
+        // `user.` matches every user action, anything else must match exactly.
+        if let Some(action) = &self.action {
+            match action.strip_suffix('.') {
+                Some(prefix) => query_builder.push(" AND action LIKE ").push_bind(format!(
+                    "{}.%",
+                    prefix.replace('%', "\\%").replace('_', "\\_")
+                )),
+                None => query_builder
+                    .push(" AND action = ")
+                    .push_bind(action.clone()),
+            };
+        }
+
+        if let Some(request_id) = &self.request_id {
+            query_builder
+                .push(" AND request_id = ")
+                .push_bind(request_id.clone());
+        }
+
+        if let Some(occurred_at) = &self.occurred_at {
+            occurred_at.push_conditions(query_builder, "occurred_at");
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::utils::{
+    query_cursor::{CursorValue, Cursorable},
+    query_sort::Sortable,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+pub const TARGET_USER: &str = "user";
+pub const TARGET_INVITATION: &str = "invitation";
+pub const TARGET_WEBHOOK: &str = "webhook";
+
+/// What happened to the target, stored as the dotted string of `as_str`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    UserRegistered,
+    UserImported,
+    UserInviteAccepted,
+    UserLoggedIn,
+    UserUpdated,
+    UserAvatarUpdated,
+    UserMetadataUpdated,
+    UserMetadataDeleted,
+    UserPreferencesUpdated,
+    UserStatusChanged,
+    UserRoleChanged,
+    UserSoftDeleted,
+    UserDeleted,
+    UserAnonymized,
+    UserPurged,
+    UserEmailChangeRequested,
+    UserEmailChangeCancelled,
+    UserEmailChanged,
+    UserPasswordChanged,
+    UserExportRequested,
+    UserMembershipAdded,
+    UserMembershipRoleChanged,
+    UserMembershipRemoved,
+    InvitationCreated,
+    InvitationResent,
+    InvitationRevoked,
+    InvitationAccepted,
+    WebhookCreated,
+    WebhookUpdated,
+    WebhookDeleted,
+    WebhookRedelivered,
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub occurred_at: DateTime<Utc>,
+    pub actor_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<Uuid>,
+    pub changes: Value,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::UserRegistered => "user.registered",
+            AuditAction::UserImported => "user.imported",
+            AuditAction::UserInviteAccepted => "user.invite_accepted",
+            AuditAction::UserLoggedIn => "user.logged_in",
+            AuditAction::UserUpdated => "user.updated",
+            AuditAction::UserAvatarUpdated => "user.avatar_updated",
+            AuditAction::UserMetadataUpdated => "user.metadata_updated",
+            AuditAction::UserMetadataDeleted => "user.metadata_deleted",
+            AuditAction::UserPreferencesUpdated => "user.preferences_updated",
+            AuditAction::UserStatusChanged => "user.status_changed",
+            AuditAction::UserRoleChanged => "user.role_changed",
+            AuditAction::UserSoftDeleted => "user.soft_deleted",
+            AuditAction::UserDeleted => "user.deleted",
+            AuditAction::UserAnonymized => "user.anonymized",
+            AuditAction::UserPurged => "user.purged",
+            AuditAction::UserEmailChangeRequested => "user.email_change_requested",
+            AuditAction::UserEmailChangeCancelled => "user.email_change_cancelled",
+            AuditAction::UserEmailChanged => "user.email_changed",
+            AuditAction::UserPasswordChanged => "user.password_changed",
+            AuditAction::UserExportRequested => "user.export_requested",
+            AuditAction::UserMembershipAdded => "user.membership_added",
+            AuditAction::UserMembershipRoleChanged => "user.membership_role_changed",
+            AuditAction::UserMembershipRemoved => "user.membership_removed",
+            AuditAction::InvitationCreated => "invitation.created",
+            AuditAction::InvitationResent => "invitation.resent",
+            AuditAction::InvitationRevoked => "invitation.revoked",
+            AuditAction::InvitationAccepted => "invitation.accepted",
+            AuditAction::WebhookCreated => "webhook.created",
+            AuditAction::WebhookUpdated => "webhook.updated",
+            AuditAction::WebhookDeleted => "webhook.deleted",
+            AuditAction::WebhookRedelivered => "webhook.redelivered",
+        }
+    }
+
+    pub fn target_type(&self) -> &'static str {
+        match self {
+            AuditAction::InvitationCreated
+            | AuditAction::InvitationResent
+            | AuditAction::InvitationRevoked
+            | AuditAction::InvitationAccepted => TARGET_INVITATION,
+            AuditAction::WebhookCreated
+            | AuditAction::WebhookUpdated
+            | AuditAction::WebhookDeleted
+            | AuditAction::WebhookRedelivered => TARGET_WEBHOOK,
+            _ => TARGET_USER,
+        }
+    }
+}
+
+impl Sortable for AuditEvent {
+    const SORTABLE_COLUMNS: &'static [&'static str] = &["id", "occurred_at"];
+    const DEFAULT_SORT: &'static str = "-occurred_at";
+}
+
+impl Cursorable for AuditEvent {
+    fn cursor_value(&self, column: &str) -> Option<CursorValue> {
+        match column {
+            "id" => Some(CursorValue::Uuid(self.id)),
+            "occurred_at" => Some(CursorValue::Timestamp(self.occurred_at)),
+            _ => None,
+        }
+    }
+}
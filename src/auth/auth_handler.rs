@@ -1,10 +1,12 @@
 use crate::{server::AppState, users::dto::CreateUserDTO, utils::errors::AppError};
-use actix_web::{guard, web, HttpResponse};
+use actix_web::{guard, web, HttpRequest, HttpResponse};
 use sqlx::PgPool;
 
 use super::{
     auth_service,
-    dto::{jwt_dto::RefreshJwtDto, LoginDto},
+    dto::{
+        jwt_dto::RefreshJwtDto, AcceptInviteDto, EmailChangeTokenDto, InviteTokenQuery, LoginDto,
+    },
 };
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -24,6 +26,21 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
                 web::resource("/refresh")
                     .guard(guard::Post())
                     .route(web::post().to(refresh)),
+            )
+            .service(
+                web::resource("/accept-invite")
+                    .route(web::get().to(preview_invite))
+                    .route(web::post().to(accept_invite)),
+            )
+            .service(
+                web::resource("/confirm-email-change")
+                    .route(web::get().to(preview_email_change_confirmation))
+                    .route(web::post().to(confirm_email_change)),
+            )
+            .service(
+                web::resource("/cancel-email-change")
+                    .route(web::get().to(preview_email_change_cancellation))
+                    .route(web::post().to(cancel_email_change)),
             ),
     );
 }
@@ -32,8 +49,9 @@ async fn register(
     pool: web::Data<PgPool>,
     app_state: web::Data<AppState>,
     payload: web::Json<CreateUserDTO>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, AppError> {
-    match auth_service::register(&pool, &app_state, payload.into_inner()).await {
+    match auth_service::register(&pool, &app_state, payload.into_inner(), &req).await {
         Ok(response) => Ok(HttpResponse::Created().json(response)),
         Err(err) => Err(err),
     }
@@ -43,18 +61,85 @@ async fn login(
     pool: web::Data<PgPool>,
     app_state: web::Data<AppState>,
     payload: web::Json<LoginDto>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, AppError> {
-    match auth_service::login(&pool, &app_state, payload.into_inner()).await {
+    match auth_service::login(&pool, &app_state, payload.into_inner(), &req).await {
         Ok(response) => Ok(HttpResponse::Ok().json(response)),
         Err(err) => Err(err),
     }
 }
 
 async fn refresh(
+    pool: web::Data<PgPool>,
     payload: web::Json<RefreshJwtDto>,
     app_state: web::Data<AppState>,
 ) -> Result<HttpResponse, AppError> {
-    match auth_service::refresh(payload.into_inner(), &app_state).await {
+    match auth_service::refresh(&pool, payload.into_inner(), &app_state).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn preview_invite(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    query: web::Query<InviteTokenQuery>,
+) -> Result<HttpResponse, AppError> {
+    match auth_service::preview_invite(&pool, &app_state, &query.token).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn accept_invite(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    payload: web::Json<AcceptInviteDto>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match auth_service::accept_invite(&pool, &app_state, payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn preview_email_change_confirmation(
+    pool: web::Data<PgPool>,
+    query: web::Query<EmailChangeTokenDto>,
+) -> Result<HttpResponse, AppError> {
+    match auth_service::preview_email_change_confirmation(&pool, &query.token).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn confirm_email_change(
+    pool: web::Data<PgPool>,
+    payload: web::Json<EmailChangeTokenDto>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match auth_service::confirm_email_change(&pool, payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn preview_email_change_cancellation(
+    pool: web::Data<PgPool>,
+    query: web::Query<EmailChangeTokenDto>,
+) -> Result<HttpResponse, AppError> {
+    match auth_service::preview_email_change_cancellation(&pool, &query.token).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn cancel_email_change(
+    pool: web::Data<PgPool>,
+    payload: web::Json<EmailChangeTokenDto>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match auth_service::cancel_email_change(&pool, payload.into_inner(), &req).await {
         Ok(response) => Ok(HttpResponse::Ok().json(response)),
         Err(err) => Err(err),
     }
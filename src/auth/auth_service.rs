@@ -1,37 +1,93 @@
-use actix_web::web;
+use actix_web::{web, HttpRequest};
+use chrono::{DateTime, Utc};
 use jsonwebtoken::{encode, EncodingKey, Header};
-use sqlx::PgPool;
+use serde_json::{json, Value};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
+    audit::{audit_query, entity::AuditAction},
     auth::dto::{
         jwt_dto::{JwtDto, RefreshJwtDto},
-        Claims, LoginDto,
+        login_dto::GetLoginDto,
+        AcceptInviteDto, Claims, EmailChangeTokenDto, InviteClaims, InvitePreviewDTO,
+        InvitePurpose, LoginDto,
+    },
+    events::{entity::DomainEvent, events_query},
+    invitations::{entity::Invitation, invitations_query, invitations_service},
+    organizations::{
+        entity::{Membership, MembershipRole},
+        organizations_query, organizations_service,
     },
     server::AppState,
-    users::{dto::CreateUserDTO, users_query},
+    users::{
+        dto::{CreateUserDTO, GetUserDTO},
+        entity::{EmailChangeRequest, UserRole, UserStatus},
+        users_query,
+    },
     utils::{
+        audit::{change, diff, AuditContext},
         errors::AppError,
-        jwt::verify_refresh_jwt,
-        password::{hash_password, verify_password},
+        jwt::{verify_invite_jwt, verify_refresh_jwt},
+        password::{hash_password, validate_new_password, verify_password, UNUSABLE_PASSWORD},
+        query_fields::Fieldset,
         response_data::ResponseData,
+        tenant::Tenant,
+        token::hash_token,
     },
 };
 
+/// Where invite links point, `GET` previews the invite and `POST` accepts it.
+pub const ACCEPT_INVITE_PATH: &str = "/api/V1/auth/accept-invite";
+/// Where the link mailed to a new address points, `GET` previews the change and `POST`
+/// confirms it.
+pub const CONFIRM_EMAIL_CHANGE_PATH: &str = "/api/V1/auth/confirm-email-change";
+/// Where the link mailed to the current address points, `GET` previews the change and
+/// `POST` cancels it.
+pub const CANCEL_EMAIL_CHANGE_PATH: &str = "/api/V1/auth/cancel-email-change";
+
 pub async fn register(
     pool: &PgPool,
     app_state: &web::Data<AppState>,
     mut payload: CreateUserDTO,
+    req: &HttpRequest,
 ) -> Result<ResponseData<JwtDto>, AppError> {
     payload.validate().map_err(AppError::ValidationError)?;
 
     payload.password = hash_password(&payload.password)?;
 
-    let user_id = users_query::create_user(pool, payload).await?;
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let organization_name = payload.name.clone();
+    let user_id = create_account(&mut tx, payload, req).await?;
 
-    let access_token = generate_token(user_id, app_state)?;
-    let refresh_token = generate_refresh_token(user_id, app_state)?;
+    // Self sign-ups start in an organization of their own.
+    let organization =
+        organizations_query::create_organization(&mut tx, &organization_name).await?;
+    let membership = organizations_query::add_membership(
+        &mut tx,
+        organization.id,
+        user_id,
+        MembershipRole::OWNER,
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req).with_actor(user_id),
+        AuditAction::UserMembershipAdded,
+        user_id,
+        organizations_service::membership_change(
+            organization.id,
+            Value::Null,
+            json!(membership.role),
+        ),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    let access_token = generate_token(user_id, UserRole::USER, Some(&membership), app_state)?;
+    let refresh_token =
+        generate_refresh_token(user_id, UserRole::USER, Some(&membership), app_state)?;
 
     Ok(ResponseData::new(
         JwtDto {
@@ -46,17 +102,53 @@ pub async fn login(
     pool: &PgPool,
     app_state: &web::Data<AppState>,
     payload: LoginDto,
+    req: &HttpRequest,
 ) -> Result<ResponseData<JwtDto>, AppError> {
     payload.validate().map_err(AppError::ValidationError)?;
 
-    let LoginDto { email, password } = payload;
+    let LoginDto {
+        email,
+        password,
+        organization_id,
+    } = payload;
 
-    let result = users_query::login_users_query(pool, &email).await?;
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let result = users_query::login_users_query(&mut tx, &email).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+    verify_account(&result, &password)?;
 
-    verify_password(&password, &result.password)?;
+    let membership = select_membership(pool, result.id, organization_id, None).await?;
 
-    let access_token = generate_token(result.id, app_state)?;
-    let refresh_token = generate_refresh_token(result.id, app_state)?;
+    let organization_id = membership
+        .as_ref()
+        .map(|membership| membership.organization_id);
+    let mut context = AuditContext::from_request(req).with_actor(result.id);
+    if let Some(organization_id) = organization_id {
+        context = context.with_organization(organization_id);
+    }
+
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+    events_query::insert_event(
+        &mut tx,
+        DomainEvent::LoggedIn {
+            user_id: result.id,
+            organization_id,
+        },
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &context,
+        AuditAction::UserLoggedIn,
+        result.id,
+        json!({}),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    let access_token = generate_token(result.id, result.role, membership.as_ref(), app_state)?;
+    let refresh_token =
+        generate_refresh_token(result.id, result.role, membership.as_ref(), app_state)?;
 
     Ok(ResponseData::new(
         JwtDto {
@@ -68,15 +160,75 @@ pub async fn login(
 }
 
 pub async fn refresh(
+    pool: &PgPool,
     payload: RefreshJwtDto,
     app_state: &web::Data<AppState>,
 ) -> Result<ResponseData<JwtDto>, AppError> {
     payload.validate().map_err(AppError::ValidationError)?;
 
-    let user_id = verify_refresh_jwt(payload.refresh_token, app_state)?;
+    let claims = verify_refresh_jwt(payload.refresh_token, app_state)?;
+
+    // Re-read the user so deleted accounts cannot refresh and role changes are picked up.
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let user = users_query::find_user(&mut tx, Tenant::Unscoped, claims.sub, &Fieldset::all())
+        .await
+        .map_err(|err| match err {
+            AppError::NotFound(_) => AppError::Unauthorized("User is no longer active".to_string()),
+            err => err,
+        })?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+    validate_login_status(&user.status)?;
+
+    let membership = select_membership(pool, user.id, payload.organization_id, claims.org).await?;
+
+    let access_token = generate_token(user.id, user.role, membership.as_ref(), app_state)?;
+    let refresh_token = generate_refresh_token(user.id, user.role, membership.as_ref(), app_state)?;
+
+    Ok(ResponseData::new(
+        JwtDto {
+            access_token,
+            refresh_token,
+        },
+        "Token has been successfuly retrieved.",
+    ))
+}
+
+pub async fn accept_invite(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    payload: AcceptInviteDto,
+    req: &HttpRequest,
+) -> Result<ResponseData<JwtDto>, AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let claims = verify_invite_jwt(&payload.token, app_state)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let (user_id, role, organization_id) = match claims.purpose {
+        InvitePurpose::Invitation => {
+            let invitation = invitations_query::lock_invitation(&mut tx, claims.sub)
+                .await?
+                .ok_or(AppError::NotFound(format!(
+                    "Invitation with ID {} not found",
+                    claims.sub
+                )))?;
+            let organization_id = invitation.organization_id;
+            let (user_id, role) = accept_invitation(&mut tx, invitation, payload, req).await?;
+            (user_id, role, Some(organization_id))
+        }
+        InvitePurpose::AccountSetup => {
+            validate_new_password(&payload.password)?;
+            let password = hash_password(&payload.password)?;
+            let user = set_initial_password(&mut tx, claims.sub, password, req).await?;
+            (user.id, user.role, None)
+        }
+    };
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    let membership = select_membership(pool, user_id, organization_id, None).await?;
 
-    let access_token = generate_token(user_id, app_state)?;
-    let refresh_token = generate_refresh_token(user_id, app_state)?;
+    let access_token = generate_token(user_id, role, membership.as_ref(), app_state)?;
+    let refresh_token = generate_refresh_token(user_id, role, membership.as_ref(), app_state)?;
 
     Ok(ResponseData::new(
         JwtDto {
@@ -87,7 +239,327 @@ pub async fn refresh(
     ))
 }
 
-fn generate_token(user_id: Uuid, app_state: &web::Data<AppState>) -> Result<String, AppError> {
+/// Details of an invite link for the form that accepts it, fails like accepting it
+/// would once the invitation is no longer pending.
+pub async fn preview_invite(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    token: &str,
+) -> Result<ResponseData<InvitePreviewDTO>, AppError> {
+    let claims = verify_invite_jwt(token, app_state)?;
+    let expires_at = DateTime::from_timestamp(claims.exp as i64, 0).unwrap_or_default();
+
+    let result = match claims.purpose {
+        InvitePurpose::Invitation => {
+            let invitation = invitations_query::find_invitation(pool, claims.sub)
+                .await?
+                .ok_or(AppError::NotFound(format!(
+                    "Invitation with ID {} not found",
+                    claims.sub
+                )))?;
+            invitations_service::ensure_pending(&invitation)?;
+            let organization =
+                organizations_query::find_organization(pool, invitation.organization_id).await?;
+
+            InvitePreviewDTO {
+                purpose: claims.purpose,
+                email: invitation.email,
+                organization_id: Some(organization.id),
+                organization_name: Some(organization.name),
+                role: Some(invitation.role),
+                expires_at: invitation.expires_at,
+            }
+        }
+        InvitePurpose::AccountSetup => {
+            let mut tx = Tenant::Unscoped.begin(pool).await?;
+            let user =
+                users_query::find_user(&mut tx, Tenant::Unscoped, claims.sub, &Fieldset::all())
+                    .await?;
+            tx.commit().await.map_err(AppError::DatabaseError)?;
+
+            if user.status != UserStatus::PENDING {
+                return Err(AppError::Conflict(
+                    "Invitation has already been accepted.".to_string(),
+                ));
+            }
+
+            InvitePreviewDTO {
+                purpose: claims.purpose,
+                email: user.email,
+                organization_id: None,
+                organization_name: None,
+                role: None,
+                expires_at,
+            }
+        }
+    };
+
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+/// Joins the invited organization with the account of the invited address. Existing
+/// accounts confirm with their password, otherwise the account is registered.
+async fn accept_invitation(
+    conn: &mut PgConnection,
+    invitation: Invitation,
+    payload: AcceptInviteDto,
+    req: &HttpRequest,
+) -> Result<(Uuid, UserRole), AppError> {
+    invitations_service::ensure_pending(&invitation)?;
+
+    if invitation.is_expired() {
+        return Err(AppError::Unauthorized(
+            "Invitation has expired, ask for it to be resent".to_string(),
+        ));
+    }
+
+    let (user_id, role) = match users_query::login_users_query(conn, &invitation.email).await {
+        Ok(account) if account.status == UserStatus::PENDING => {
+            validate_new_password(&payload.password)?;
+            let password = hash_password(&payload.password)?;
+            let user = set_initial_password(conn, account.id, password, req).await?;
+            (user.id, user.role)
+        }
+        Ok(account) => {
+            verify_account(&account, &payload.password)?;
+            (account.id, account.role)
+        }
+        Err(AppError::NotFound(_)) => {
+            validate_new_password(&payload.password)?;
+            let account = CreateUserDTO {
+                name: payload.name.ok_or(AppError::BadRequest(
+                    "Name is required to create an account.".to_string(),
+                ))?,
+                email: invitation.email.clone(),
+                password: hash_password(&payload.password)?,
+            };
+            (create_account(conn, account, req).await?, UserRole::USER)
+        }
+        Err(err) => return Err(err),
+    };
+
+    if organizations_query::find_membership(conn, invitation.organization_id, user_id)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Conflict(
+            "Already a member of the organization.".to_string(),
+        ));
+    }
+
+    let membership = organizations_query::add_membership(
+        conn,
+        invitation.organization_id,
+        user_id,
+        invitation.role,
+    )
+    .await?;
+    let accepted = invitations_query::accept_invitation(conn, invitation.id, user_id).await?;
+
+    let context = AuditContext::from_request(req)
+        .with_actor(user_id)
+        .with_organization(invitation.organization_id);
+    audit_query::insert_audit_event(
+        conn,
+        &context,
+        AuditAction::UserMembershipAdded,
+        user_id,
+        organizations_service::membership_change(
+            invitation.organization_id,
+            Value::Null,
+            json!(membership.role),
+        ),
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        conn,
+        &context,
+        AuditAction::InvitationAccepted,
+        invitation.id,
+        diff(Some(&invitation), Some(&accepted)),
+    )
+    .await?;
+
+    Ok((user_id, role))
+}
+
+/// Inserts an account whose password is already hashed and records the registration.
+async fn create_account(
+    conn: &mut PgConnection,
+    payload: CreateUserDTO,
+    req: &HttpRequest,
+) -> Result<Uuid, AppError> {
+    let user_id = users_query::create_user(conn, payload).await?;
+    let user = users_query::find_user_snapshot(conn, user_id).await?;
+    events_query::insert_event(conn, DomainEvent::UserRegistered { user_id }).await?;
+    audit_query::insert_audit_event(
+        conn,
+        &AuditContext::from_request(req).with_actor(user_id),
+        AuditAction::UserRegistered,
+        user_id,
+        diff(None, user.as_ref()),
+    )
+    .await?;
+
+    Ok(user_id)
+}
+
+/// First password of an imported account, set when its invitation is accepted.
+async fn set_initial_password(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    password: String,
+    req: &HttpRequest,
+) -> Result<GetUserDTO, AppError> {
+    let context = AuditContext::from_request(req).with_actor(user_id);
+    let user = users_query::set_initial_password(conn, user_id, password).await?;
+    events_query::insert_event(conn, DomainEvent::PasswordChanged { user_id }).await?;
+    users_query::insert_status_transition(
+        conn,
+        user_id,
+        UserStatus::PENDING,
+        UserStatus::ACTIVE,
+        None,
+        context.actor_id,
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        conn,
+        &context,
+        AuditAction::UserInviteAccepted,
+        user_id,
+        json!({
+            "password": change("password", Value::Null, Value::Null),
+            "status": change("status", json!(UserStatus::PENDING), json!(user.status)),
+        }),
+    )
+    .await?;
+
+    Ok(user)
+}
+
+/// Checks the password before the status, so only someone knowing the password learns
+/// that an account is pending, suspended or locked. Accounts without a password yet
+/// fail like a wrong password.
+fn verify_account(account: &GetLoginDto, password: &str) -> Result<(), AppError> {
+    if account.password == UNUSABLE_PASSWORD {
+        return Err(AppError::InvalidCredentials("invalid password".to_string()));
+    }
+
+    verify_password(password, &account.password)?;
+    validate_login_status(&account.status)
+}
+
+/// Only active accounts get tokens, the others fail with an error of their own so
+/// clients can tell the user what to do.
+pub fn validate_login_status(status: &UserStatus) -> Result<(), AppError> {
+    match status {
+        UserStatus::ACTIVE => Ok(()),
+        UserStatus::PENDING => Err(AppError::AccountPending(
+            "Account has not been activated, accept the invitation first".to_string(),
+        )),
+        UserStatus::SUSPENDED => Err(AppError::AccountSuspended(
+            "Account has been suspended".to_string(),
+        )),
+        UserStatus::LOCKED => Err(AppError::AccountLocked(
+            "Account has been locked, contact an administrator".to_string(),
+        )),
+        UserStatus::DELETED => Err(AppError::Unauthorized(
+            "User is no longer active".to_string(),
+        )),
+    }
+}
+
+pub fn generate_invite_link(
+    user_id: Uuid,
+    app_state: &web::Data<AppState>,
+) -> Result<String, AppError> {
+    let expires_at = chrono::Utc::now()
+        .checked_add_signed(*app_state.invite_expiration_time)
+        .expect("Valid timestamp");
+
+    invite_link(user_id, InvitePurpose::AccountSetup, expires_at, app_state)
+}
+
+/// Accept link of an organization invitation, valid as long as the invitation is.
+pub fn generate_invitation_link(
+    invitation: &Invitation,
+    app_state: &web::Data<AppState>,
+) -> Result<String, AppError> {
+    invite_link(
+        invitation.id,
+        InvitePurpose::Invitation,
+        invitation.expires_at,
+        app_state,
+    )
+}
+
+fn invite_link(
+    subject: Uuid,
+    purpose: InvitePurpose,
+    expires_at: DateTime<Utc>,
+    app_state: &web::Data<AppState>,
+) -> Result<String, AppError> {
+    let invite_claims = InviteClaims {
+        sub: subject,
+        exp: expires_at.timestamp() as usize,
+        purpose,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &invite_claims,
+        &EncodingKey::from_secret(app_state.invite_key.as_bytes()),
+    )
+    .map_err(|err| AppError::InternalServerError(err.to_string()))?;
+
+    Ok(format!(
+        "{}{}?token={}",
+        app_state.app_base_url, ACCEPT_INVITE_PATH, token
+    ))
+}
+
+/// Organization the issued tokens act in. An explicitly requested one must be a
+/// membership of the user, a previous one is kept while the membership lasts and the
+/// oldest membership is used otherwise.
+async fn select_membership(
+    pool: &PgPool,
+    user_id: Uuid,
+    requested: Option<Uuid>,
+    previous: Option<Uuid>,
+) -> Result<Option<Membership>, AppError> {
+    let mut conn = pool.acquire().await.map_err(AppError::DatabaseError)?;
+
+    if let Some(organization_id) = requested {
+        return organizations_query::find_membership(&mut conn, organization_id, user_id)
+            .await?
+            .ok_or(AppError::Unauthorized(format!(
+                "Not a member of organization {}",
+                organization_id
+            )))
+            .map(Some);
+    }
+
+    if let Some(organization_id) = previous {
+        let membership =
+            organizations_query::find_membership(&mut conn, organization_id, user_id).await?;
+        if membership.is_some() {
+            return Ok(membership);
+        }
+    }
+
+    organizations_query::find_default_membership(pool, user_id).await
+}
+
+fn generate_token(
+    user_id: Uuid,
+    role: UserRole,
+    membership: Option<&Membership>,
+    app_state: &web::Data<AppState>,
+) -> Result<String, AppError> {
     let expiration = chrono::Utc::now()
         .checked_add_signed(*app_state.jwt_expiration_time)
         .expect("Valid timestamp")
@@ -96,6 +568,9 @@ fn generate_token(user_id: Uuid, app_state: &web::Data<AppState>) -> Result<Stri
     let claims = Claims {
         sub: user_id,
         exp: expiration,
+        role,
+        org: membership.map(|membership| membership.organization_id),
+        membership_role: None,
     };
 
     encode(
@@ -108,6 +583,8 @@ fn generate_token(user_id: Uuid, app_state: &web::Data<AppState>) -> Result<Stri
 
 fn generate_refresh_token(
     user_id: Uuid,
+    role: UserRole,
+    membership: Option<&Membership>,
     app_state: &web::Data<AppState>,
 ) -> Result<String, AppError> {
     let refresh_expiration = chrono::Utc::now()
@@ -118,6 +595,9 @@ fn generate_refresh_token(
     let refresh_claims = Claims {
         sub: user_id,
         exp: refresh_expiration,
+        role,
+        org: membership.map(|membership| membership.organization_id),
+        membership_role: None,
     };
 
     encode(
@@ -127,3 +607,92 @@ fn generate_refresh_token(
     )
     .map_err(|err| AppError::InternalServerError(err.to_string()))
 }
+
+/// Pending change behind a confirmation link, opening the link changes nothing. Mail
+/// clients fetch links ahead of the user, only the `POST` confirms.
+pub async fn preview_email_change_confirmation(
+    pool: &PgPool,
+    token: &str,
+) -> Result<ResponseData<EmailChangeRequest>, AppError> {
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let request = users_query::find_email_change_to_confirm(&mut tx, &hash_token(token)).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        request,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+/// Pending change behind a cancellation link, only the `POST` cancels it.
+pub async fn preview_email_change_cancellation(
+    pool: &PgPool,
+    token: &str,
+) -> Result<ResponseData<EmailChangeRequest>, AppError> {
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let request = users_query::find_email_change_to_cancel(&mut tx, &hash_token(token)).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        request,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+pub async fn confirm_email_change(
+    pool: &PgPool,
+    payload: EmailChangeTokenDto,
+    req: &HttpRequest,
+) -> Result<ResponseData<GetUserDTO>, AppError> {
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+
+    let request =
+        users_query::confirm_email_change_request(&mut tx, &hash_token(&payload.token)).await?;
+    let before = users_query::find_user_snapshot(&mut tx, request.user_id).await?;
+    let user = users_query::update_user_email(&mut tx, request.user_id, &request.new_email).await?;
+    let changes = diff(before.as_ref(), Some(&user));
+    events_query::insert_event(
+        &mut tx,
+        DomainEvent::user_updated(request.user_id, &changes),
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req).with_actor(request.user_id),
+        AuditAction::UserEmailChanged,
+        request.user_id,
+        changes,
+    )
+    .await?;
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        user,
+        "Data has been successfuly updated.",
+    ))
+}
+
+pub async fn cancel_email_change(
+    pool: &PgPool,
+    payload: EmailChangeTokenDto,
+    req: &HttpRequest,
+) -> Result<ResponseData<EmailChangeRequest>, AppError> {
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let request =
+        users_query::cancel_email_change_request(&mut tx, &hash_token(&payload.token)).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req).with_actor(request.user_id),
+        AuditAction::UserEmailChangeCancelled,
+        request.user_id,
+        json!({ "pending_email": change("pending_email", json!(request.new_email), Value::Null) }),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        request,
+        "Data has been successfuly cancelled.",
+    ))
+}
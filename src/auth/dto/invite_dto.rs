@@ -0,0 +1,37 @@
+use crate::{auth::dto::InvitePurpose, organizations::entity::MembershipRole};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize)]
+pub struct InviteTokenQuery {
+    pub token: String,
+}
+
+/// What an invite link is for, so a client opening it can show the matching form
+/// before it posts the token back with a password.
+#[derive(Debug, Serialize)]
+pub struct InvitePreviewDTO {
+    pub purpose: InvitePurpose,
+    pub email: String,
+    /// Only set for organization invitations.
+    pub organization_id: Option<Uuid>,
+    pub organization_name: Option<String>,
+    pub role: Option<MembershipRole>,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct AcceptInviteDto {
+    pub token: String,
+
+    /// Password of the existing account when an organization invitation goes to an
+    /// address that already has one, the new password otherwise. Only a new password
+    /// is held to the password policy, an existing one is just verified.
+    pub password: String,
+
+    /// Name of the account created for an organization invitation.
+    #[validate(length(min = 3, max = 255))]
+    pub name: Option<String>,
+}
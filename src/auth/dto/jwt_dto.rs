@@ -1,3 +1,4 @@
+use crate::{organizations::entity::MembershipRole, users::entity::UserRole};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use validator::Validate;
@@ -12,9 +13,39 @@ pub struct JwtDto {
 pub struct Claims {
     pub sub: Uuid,
     pub exp: usize,
+    #[serde(default)]
+    pub role: UserRole,
+    /// Active organization, queries of other users are scoped to its members. The
+    /// auth middleware checks on every request that the membership still exists.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<Uuid>,
+    /// Role in the active organization, read from the database by the auth middleware
+    /// on every request and never part of the token.
+    #[serde(skip)]
+    pub membership_role: Option<MembershipRole>,
+}
+
+/// What an invite token is for. Required, so a token of one kind is never accepted
+/// as the other.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InvitePurpose {
+    /// Joining an organization, the subject is the invitation.
+    Invitation,
+    /// First password of an imported account, the subject is the user.
+    AccountSetup,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct InviteClaims {
+    pub sub: Uuid,
+    pub exp: usize,
+    pub purpose: InvitePurpose,
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct RefreshJwtDto {
     pub refresh_token: String,
+    /// Switches the active organization, defaults to the one of the refresh token.
+    pub organization_id: Option<Uuid>,
 }
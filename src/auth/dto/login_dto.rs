@@ -1,3 +1,4 @@
+use crate::users::entity::{UserRole, UserStatus};
 use serde::Deserialize;
 use sqlx::prelude::FromRow;
 use uuid::Uuid;
@@ -7,6 +8,8 @@ use validator::Validate;
 pub struct LoginDto {
     pub email: String,
     pub password: String,
+    /// Organization to log in to, defaults to the oldest membership.
+    pub organization_id: Option<Uuid>,
 }
 
 #[derive(FromRow)]
@@ -14,4 +17,6 @@ pub struct GetLoginDto {
     pub id: Uuid,
     pub email: String,
     pub password: String,
+    pub role: UserRole,
+    pub status: UserStatus,
 }
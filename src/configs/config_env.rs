@@ -1,6 +1,12 @@
+use crate::{
+    jobs::{outbox_relay_job::OutboxSink, purge_users_job::PurgeMode},
+    users::entity::Preferences,
+};
 use chrono::Duration;
+use serde_json::json;
 use std::env;
 use thiserror::Error;
+use validator::Validate;
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -17,10 +23,42 @@ pub struct Config {
     pub app_env: String,
     pub app_host: String,
     pub app_port: u16,
+    pub app_base_url: String,
     pub jwt_secret_key: String,
     pub jwt_refresh_key: String,
     pub jwt_expiration_time: Duration,
     pub jwt_refresh_expiration_time: Duration,
+    pub jwt_invite_key: String,
+    pub invite_expiration_time: Duration,
+    pub email_change_expiration_time: Duration,
+    pub jwt_download_key: String,
+    pub download_expiration_time: Duration,
+    pub purge_enabled: bool,
+    pub purge_interval_time: Duration,
+    pub purge_retention_time: Duration,
+    pub purge_batch_size: i64,
+    pub purge_dry_run: bool,
+    pub purge_mode: PurgeMode,
+    pub storage_path: String,
+    pub avatar_max_size: usize,
+    pub metadata_schema_path: Option<String>,
+    pub export_enabled: bool,
+    pub export_interval_time: Duration,
+    pub export_retention_time: Duration,
+    pub preferences_defaults: Preferences,
+    pub outbox_enabled: bool,
+    pub outbox_interval_time: Duration,
+    pub outbox_batch_size: i64,
+    pub outbox_retention_time: Duration,
+    pub outbox_sinks: Vec<OutboxSink>,
+    pub webhook_enabled: bool,
+    pub webhook_interval_time: Duration,
+    pub webhook_batch_size: i64,
+    pub webhook_timeout: Duration,
+    pub webhook_max_attempts: i32,
+    pub webhook_disable_after: i32,
+    pub webhook_retention_time: Duration,
+    pub webhook_allow_private_networks: bool,
 }
 
 impl Config {
@@ -38,6 +76,12 @@ impl Config {
         };
         let app_host = env_var("APP_HOST", Some("localhost"))?;
         let app_port = env_var_u16("APP_PORT", 8080)?;
+        let app_base_url = env_var(
+            "APP_BASE_URL",
+            Some(&format!("http://{}:{}", app_host, app_port)),
+        )?
+        .trim_end_matches('/')
+        .to_string();
         let jwt_secret_key = env_var("JWT_SECRET_KEY", Some("jwt-secret-key"))?;
         let jwt_refresh_key = env_var("JWT_REFRESH_KEY", Some("jwt-refresh-key"))?;
         let jwt_expiration_seconds = env_var_u64("JWT_EXPIRATION_TIME", 86400)?;
@@ -46,6 +90,124 @@ impl Config {
         let jwt_expiration_time = Duration::seconds(jwt_expiration_seconds as i64);
         let jwt_refresh_expiration_time = Duration::seconds(jwt_refresh_expiration_seconds as i64);
 
+        let jwt_invite_key = env_var("JWT_INVITE_KEY", Some("jwt-invite-key"))?;
+        let invite_expiration_seconds = env_var_u64("INVITE_EXPIRATION_TIME", 604800)?;
+        let invite_expiration_time = Duration::seconds(invite_expiration_seconds as i64);
+
+        let email_change_expiration_seconds = env_var_u64("EMAIL_CHANGE_EXPIRATION_TIME", 86400)?;
+        let email_change_expiration_time =
+            Duration::seconds(email_change_expiration_seconds as i64);
+
+        let jwt_download_key = env_var("JWT_DOWNLOAD_KEY", Some("jwt-download-key"))?;
+        let download_expiration_seconds = env_var_u64("DOWNLOAD_EXPIRATION_TIME", 3600)?;
+        let download_expiration_time = Duration::seconds(download_expiration_seconds as i64);
+
+        let purge_enabled = env_var_bool("PURGE_ENABLED", false)?;
+        let purge_interval_seconds = env_var_u64("PURGE_INTERVAL_TIME", 3600)?;
+        let purge_retention_seconds = env_var_u64("PURGE_RETENTION_TIME", 2592000)?;
+        let purge_batch_size = env_var_u64("PURGE_BATCH_SIZE", 500)?;
+        let purge_dry_run = env_var_bool("PURGE_DRY_RUN", true)?;
+        let purge_mode = env_var("PURGE_MODE", Some("anonymize"))?
+            .parse::<PurgeMode>()
+            .map_err(ConfigError::InvalidValue)?;
+
+        if purge_interval_seconds == 0 || purge_batch_size == 0 {
+            return Err(ConfigError::InvalidValue(
+                "PURGE_INTERVAL_TIME and PURGE_BATCH_SIZE must be greater than 0".to_string(),
+            ));
+        }
+
+        let purge_interval_time = Duration::seconds(purge_interval_seconds as i64);
+        let purge_retention_time = Duration::seconds(purge_retention_seconds as i64);
+
+        let storage_path = env_var("STORAGE_PATH", Some("storage"))?;
+        let avatar_max_size = env_var_u64("AVATAR_MAX_SIZE", 5242880)?;
+        let metadata_schema_path =
+            Some(env_var("METADATA_SCHEMA_PATH", Some(""))?).filter(|path| !path.is_empty());
+
+        let export_enabled = env_var_bool("EXPORT_ENABLED", true)?;
+        let export_interval_seconds = env_var_u64("EXPORT_INTERVAL_TIME", 5)?;
+        let export_retention_seconds = env_var_u64("EXPORT_RETENTION_TIME", 604800)?;
+
+        if export_interval_seconds == 0 {
+            return Err(ConfigError::InvalidValue(
+                "EXPORT_INTERVAL_TIME must be greater than 0".to_string(),
+            ));
+        }
+
+        let export_interval_time = Duration::seconds(export_interval_seconds as i64);
+        let export_retention_time = Duration::seconds(export_retention_seconds as i64);
+
+        // Built through serde so the variables use the same names as the API.
+        let preferences_defaults: Preferences = serde_json::from_value(json!({
+            "language": env_var("PREFERENCES_DEFAULT_LANGUAGE", Some("en"))?,
+            "timezone": env_var("PREFERENCES_DEFAULT_TIMEZONE", Some("UTC"))?,
+            "date_format": env_var("PREFERENCES_DEFAULT_DATE_FORMAT", Some("YYYY-MM-DD"))?,
+            "notifications": {
+                "channels": env_var_list("PREFERENCES_DEFAULT_CHANNELS", "email")?,
+                "opt_ins": env_var_list("PREFERENCES_DEFAULT_OPT_INS", "security_alerts")?,
+            },
+        }))
+        .map_err(|e| ConfigError::InvalidValue(format!("PREFERENCES_DEFAULT_*: {}", e)))?;
+        preferences_defaults
+            .validate()
+            .map_err(|e| ConfigError::InvalidValue(format!("PREFERENCES_DEFAULT_*: {}", e)))?;
+
+        let outbox_enabled = env_var_bool("OUTBOX_ENABLED", true)?;
+        let outbox_interval_seconds = env_var_u64("OUTBOX_INTERVAL_TIME", 1)?;
+        let outbox_batch_size = env_var_u64("OUTBOX_BATCH_SIZE", 100)?;
+        let outbox_retention_seconds = env_var_u64("OUTBOX_RETENTION_TIME", 604800)?;
+        let outbox_sinks = env_var_list("OUTBOX_SINK", "log,endpoints")?
+            .iter()
+            .map(|sink| match sink.to_lowercase().as_str() {
+                "log" => Ok(OutboxSink::Log),
+                "webhook" => Ok(OutboxSink::Webhook(env_var("OUTBOX_WEBHOOK_URL", None)?)),
+                "endpoints" => Ok(OutboxSink::Endpoints),
+                _ => Err(ConfigError::InvalidValue(
+                    "OUTBOX_SINK must list 'log', 'webhook' or 'endpoints'".to_string(),
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if outbox_sinks.is_empty() {
+            return Err(ConfigError::InvalidValue(
+                "OUTBOX_SINK must list at least one sink".to_string(),
+            ));
+        }
+
+        if outbox_interval_seconds == 0 || outbox_batch_size == 0 {
+            return Err(ConfigError::InvalidValue(
+                "OUTBOX_INTERVAL_TIME and OUTBOX_BATCH_SIZE must be greater than 0".to_string(),
+            ));
+        }
+
+        let outbox_interval_time = Duration::seconds(outbox_interval_seconds as i64);
+        let outbox_retention_time = Duration::seconds(outbox_retention_seconds as i64);
+
+        let webhook_enabled = env_var_bool("WEBHOOK_ENABLED", true)?;
+        let webhook_interval_seconds = env_var_u64("WEBHOOK_INTERVAL_TIME", 1)?;
+        let webhook_batch_size = env_var_u64("WEBHOOK_BATCH_SIZE", 50)?;
+        let webhook_timeout_seconds = env_var_u64("WEBHOOK_TIMEOUT", 10)?;
+        let webhook_max_attempts = env_var_u16("WEBHOOK_MAX_ATTEMPTS", 10)?;
+        let webhook_disable_after = env_var_u16("WEBHOOK_DISABLE_AFTER", 50)?;
+        let webhook_retention_seconds = env_var_u64("WEBHOOK_RETENTION_TIME", 2592000)?;
+        let webhook_allow_private_networks = env_var_bool("WEBHOOK_ALLOW_PRIVATE_NETWORKS", false)?;
+
+        if webhook_interval_seconds == 0
+            || webhook_batch_size == 0
+            || webhook_timeout_seconds == 0
+            || webhook_max_attempts == 0
+            || webhook_disable_after == 0
+        {
+            return Err(ConfigError::InvalidValue(
+                "WEBHOOK_INTERVAL_TIME, WEBHOOK_BATCH_SIZE, WEBHOOK_TIMEOUT, WEBHOOK_MAX_ATTEMPTS and WEBHOOK_DISABLE_AFTER must be greater than 0".to_string(),
+            ));
+        }
+
+        let webhook_interval_time = Duration::seconds(webhook_interval_seconds as i64);
+        let webhook_timeout = Duration::seconds(webhook_timeout_seconds as i64);
+        let webhook_retention_time = Duration::seconds(webhook_retention_seconds as i64);
+
         log::info!("Successfully loaded environment");
 
         Ok(Self {
@@ -53,10 +215,42 @@ impl Config {
             app_env,
             app_host,
             app_port,
+            app_base_url,
             jwt_secret_key,
             jwt_refresh_key,
             jwt_expiration_time,
             jwt_refresh_expiration_time,
+            jwt_invite_key,
+            invite_expiration_time,
+            email_change_expiration_time,
+            jwt_download_key,
+            download_expiration_time,
+            purge_enabled,
+            purge_interval_time,
+            purge_retention_time,
+            purge_batch_size: purge_batch_size as i64,
+            purge_dry_run,
+            purge_mode,
+            storage_path,
+            avatar_max_size: avatar_max_size as usize,
+            metadata_schema_path,
+            export_enabled,
+            export_interval_time,
+            export_retention_time,
+            preferences_defaults,
+            outbox_enabled,
+            outbox_interval_time,
+            outbox_batch_size: outbox_batch_size as i64,
+            outbox_retention_time,
+            outbox_sinks,
+            webhook_enabled,
+            webhook_interval_time,
+            webhook_batch_size: webhook_batch_size as i64,
+            webhook_timeout,
+            webhook_max_attempts: webhook_max_attempts as i32,
+            webhook_disable_after: webhook_disable_after as i32,
+            webhook_retention_time,
+            webhook_allow_private_networks,
         })
     }
 }
@@ -82,3 +276,21 @@ fn env_var_u64(key: &str, default: u64) -> Result<u64, ConfigError> {
             .map_err(|_| ConfigError::InvalidValue(format!("invalid u64: {}", key)))
     })
 }
+
+fn env_var_bool(key: &str, default: bool) -> Result<bool, ConfigError> {
+    env_var(key, Some(&default.to_string())).and_then(|v| match v.to_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(ConfigError::InvalidValue(format!("invalid bool: {}", key))),
+    })
+}
+
+/// Comma separated list, an empty value is an empty list.
+fn env_var_list(key: &str, default: &str) -> Result<Vec<String>, ConfigError> {
+    env_var(key, Some(default)).map(|v| {
+        v.split(',')
+            .map(|item| item.trim().to_string())
+            .filter(|item| !item.is_empty())
+            .collect()
+    })
+}
@@ -1,5 +1,6 @@
-use crate::configs::{
-    config_conn::establish_connection, config_env::Config, config_tls::certs_config,
+use crate::{
+    configs::{config_conn::establish_connection, config_env::Config, config_tls::certs_config},
+    utils::metadata::MetadataSchema,
 };
 use rustls::ServerConfig;
 use sqlx::PgPool;
@@ -24,3 +25,12 @@ pub async fn load_connection(db_url: &str) -> PgPool {
         std::process::exit(1);
     })
 }
+
+pub fn load_metadata_schema(path: Option<&str>) -> Option<MetadataSchema> {
+    path.map(|path| {
+        MetadataSchema::load(path).unwrap_or_else(|e| {
+            log::error!("Failed to load metadata schema: {}", e);
+            std::process::exit(1);
+        })
+    })
+}
@@ -0,0 +1,104 @@
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+pub const AGGREGATE_USER: &str = "user";
+
+/// Every `event_type` written to the outbox, webhooks subscribe to these.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventType {
+    UserRegistered,
+    UserUpdated,
+    UserDeleted,
+    PasswordChanged,
+    LoggedIn,
+}
+
+impl EventType {
+    pub const ALL: [EventType; 5] = [
+        EventType::UserRegistered,
+        EventType::UserUpdated,
+        EventType::UserDeleted,
+        EventType::PasswordChanged,
+        EventType::LoggedIn,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::UserRegistered => "user.registered",
+            EventType::UserUpdated => "user.updated",
+            EventType::UserDeleted => "user.deleted",
+            EventType::PasswordChanged => "user.password_changed",
+            EventType::LoggedIn => "user.logged_in",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<EventType> {
+        EventType::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str() == value)
+    }
+}
+
+/// Change to a user that other systems may react to. Payloads carry ids and field
+/// names only, the current values are read from the API so erasure stays simple.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum DomainEvent {
+    UserRegistered {
+        user_id: Uuid,
+    },
+    UserUpdated {
+        user_id: Uuid,
+        fields: Vec<String>,
+    },
+    UserDeleted {
+        user_id: Uuid,
+        /// `false` for soft deletes, which can still be restored.
+        permanent: bool,
+    },
+    PasswordChanged {
+        user_id: Uuid,
+    },
+    LoggedIn {
+        user_id: Uuid,
+        organization_id: Option<Uuid>,
+    },
+}
+
+impl DomainEvent {
+    /// Update event listing the fields of an audit diff.
+    pub fn user_updated(user_id: Uuid, changes: &Value) -> Self {
+        let fields = changes
+            .as_object()
+            .map(|changes| changes.keys().cloned().collect())
+            .unwrap_or_default();
+
+        DomainEvent::UserUpdated { user_id, fields }
+    }
+
+    pub fn event_type(&self) -> EventType {
+        match self {
+            DomainEvent::UserRegistered { .. } => EventType::UserRegistered,
+            DomainEvent::UserUpdated { .. } => EventType::UserUpdated,
+            DomainEvent::UserDeleted { .. } => EventType::UserDeleted,
+            DomainEvent::PasswordChanged { .. } => EventType::PasswordChanged,
+            DomainEvent::LoggedIn { .. } => EventType::LoggedIn,
+        }
+    }
+
+    pub fn aggregate_type(&self) -> &'static str {
+        AGGREGATE_USER
+    }
+
+    /// Events of one aggregate are published in the order they were written.
+    pub fn aggregate_id(&self) -> Uuid {
+        match self {
+            DomainEvent::UserRegistered { user_id }
+            | DomainEvent::UserUpdated { user_id, .. }
+            | DomainEvent::UserDeleted { user_id, .. }
+            | DomainEvent::PasswordChanged { user_id }
+            | DomainEvent::LoggedIn { user_id, .. } => *user_id,
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+/// Domain event as stored in the outbox and handed to sinks. Delivery is at least
+/// once, consumers deduplicate on `id`.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct OutboxEvent {
+    pub id: i64,
+    pub aggregate_type: String,
+    pub aggregate_id: Uuid,
+    pub event_type: String,
+    pub payload: Value,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    pub attempts: i32,
+    /// Organizations of the user when the event was written, webhooks of other
+    /// organizations never see it.
+    #[serde(skip)]
+    pub organization_ids: Vec<Uuid>,
+}
@@ -0,0 +1,137 @@
+use crate::{events::entity::OutboxEvent, utils::errors::AppError, webhooks::webhooks_query};
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::{fmt::Debug, sync::Arc};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Destination the outbox relay publishes events to. An error leaves the event in the
+/// outbox and it is offered again later, together with every later event of its
+/// aggregate.
+#[async_trait]
+pub trait EventSink: Send + Sync + Debug {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), AppError>;
+}
+
+/// Writes events to the application log.
+#[derive(Debug, Default)]
+pub struct LogSink;
+
+#[async_trait]
+impl EventSink for LogSink {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), AppError> {
+        log::info!(
+            "domain_event id={} type={} aggregate={}:{} payload={}",
+            event.id,
+            event.event_type,
+            event.aggregate_type,
+            event.aggregate_id,
+            event.payload
+        );
+
+        Ok(())
+    }
+}
+
+/// POSTs every event as JSON to one URL, any response other than 2xx is a failure.
+#[derive(Debug)]
+pub struct WebhookSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    pub fn new(url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .expect("Valid HTTP client configuration");
+
+        WebhookSink { url, client }
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), AppError> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(event)
+            .send()
+            .await
+            .map_err(|err| AppError::InternalServerError(err.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::InternalServerError(format!(
+                "Webhook responded with {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Queues a delivery for every webhook endpoint subscribed to the event, the webhook
+/// delivery job sends them.
+#[derive(Debug)]
+pub struct EndpointsSink {
+    pool: PgPool,
+}
+
+impl EndpointsSink {
+    pub fn new(pool: PgPool) -> Self {
+        EndpointsSink { pool }
+    }
+}
+
+#[async_trait]
+impl EventSink for EndpointsSink {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), AppError> {
+        webhooks_query::enqueue_deliveries(&self.pool, event).await?;
+
+        Ok(())
+    }
+}
+
+/// Publishes to several sinks in order. A failure retries the event on all of them, so
+/// each sink must tolerate receiving an event twice.
+#[derive(Debug)]
+pub struct FanoutSink(Vec<Arc<dyn EventSink>>);
+
+impl FanoutSink {
+    pub fn new(sinks: Vec<Arc<dyn EventSink>>) -> Self {
+        FanoutSink(sinks)
+    }
+}
+
+#[async_trait]
+impl EventSink for FanoutSink {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), AppError> {
+        for sink in &self.0 {
+            sink.publish(event).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Hands events to an in-process receiver, used by tests and embedded consumers.
+#[derive(Debug)]
+pub struct ChannelSink(UnboundedSender<OutboxEvent>);
+
+impl ChannelSink {
+    pub fn new() -> (Self, UnboundedReceiver<OutboxEvent>) {
+        let (sender, receiver) = unbounded_channel();
+        (ChannelSink(sender), receiver)
+    }
+}
+
+#[async_trait]
+impl EventSink for ChannelSink {
+    async fn publish(&self, event: &OutboxEvent) -> Result<(), AppError> {
+        self.0
+            .send(event.clone())
+            .map_err(|_| AppError::InternalServerError("Event receiver was dropped".to_string()))
+    }
+}
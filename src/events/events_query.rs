@@ -0,0 +1,160 @@
+use crate::{
+    events::entity::{DomainEvent, OutboxEvent},
+    utils::errors::AppError,
+};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool, QueryBuilder};
+
+/// Writes a domain event to the outbox, `conn` must be the transaction of the change
+/// so the event is published exactly when the change is committed.
+pub async fn insert_event(conn: &mut PgConnection, event: DomainEvent) -> Result<(), AppError> {
+    insert_events(conn, vec![event]).await
+}
+
+/// Writes several events in one statement, in the order given. Each event records the
+/// organizations its user belongs to at this point, webhooks are only sent to those, so
+/// events of a user being deleted are written before the delete.
+pub async fn insert_events(
+    conn: &mut PgConnection,
+    events: Vec<DomainEvent>,
+) -> Result<(), AppError> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = QueryBuilder::new(
+        "INSERT INTO outbox (aggregate_type, aggregate_id, event_type, payload, organization_ids) ",
+    );
+
+    query_builder.push_values(events, |mut row, event| {
+        row.push_bind(event.aggregate_type())
+            .push_bind(event.aggregate_id())
+            .push_bind(event.event_type().as_str())
+            .push_bind(serde_json::to_value(&event).unwrap_or_default())
+            .push("ARRAY(SELECT organization_id FROM memberships WHERE user_id = ")
+            .push_bind_unseparated(event.aggregate_id())
+            .push_unseparated(")");
+    });
+
+    query_builder
+        .build()
+        .execute(conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Locks the oldest unpublished event of up to `limit` aggregates whose retry is due.
+/// Later events of an aggregate wait until the earlier ones are published, so a failing
+/// event holds back its aggregate only. Locked rows are skipped, replicas share the work.
+pub async fn claim_events(
+    conn: &mut PgConnection,
+    limit: i64,
+) -> Result<Vec<OutboxEvent>, AppError> {
+    let result = sqlx::query_as::<_, OutboxEvent>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            outbox
+        WHERE
+            published_at IS NULL
+            AND next_attempt_at <= $1
+            AND NOT EXISTS (
+                SELECT
+                    1
+                FROM
+                    outbox earlier
+                WHERE
+                    earlier.aggregate_type = outbox.aggregate_type
+                    AND earlier.aggregate_id = outbox.aggregate_id
+                    AND earlier.published_at IS NULL
+                    AND earlier.id < outbox.id
+            )
+        ORDER BY
+            id
+        LIMIT
+            $2
+        FOR UPDATE
+            SKIP LOCKED
+        "#,
+    )
+    .bind(Utc::now())
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn mark_event_published(conn: &mut PgConnection, id: i64) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            outbox
+        SET
+            published_at = $1,
+            attempts = attempts + 1,
+            last_error = NULL
+        WHERE
+            id = $2
+        "#,
+    )
+    .bind(Utc::now())
+    .bind(id)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn record_event_failure(
+    conn: &mut PgConnection,
+    id: i64,
+    error: &str,
+    next_attempt_at: DateTime<Utc>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            outbox
+        SET
+            attempts = attempts + 1,
+            last_error = $1,
+            next_attempt_at = $2
+        WHERE
+            id = $3
+        "#,
+    )
+    .bind(error)
+    .bind(next_attempt_at)
+    .bind(id)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Removes events published before `cutoff`, returns how many were removed.
+pub async fn delete_published_events(
+    pool: &PgPool,
+    cutoff: DateTime<Utc>,
+) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        r#"--sql
+        DELETE FROM outbox
+        WHERE
+            published_at < $1
+        "#,
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result.rows_affected())
+}
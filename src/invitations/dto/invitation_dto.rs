@@ -0,0 +1,16 @@
+use crate::organizations::entity::MembershipRole;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateInvitationDTO {
+    #[validate(email, length(max = 255))]
+    pub email: String,
+
+    #[serde(default)]
+    pub role: MembershipRole,
+
+    /// Defaults to `INVITE_EXPIRATION_TIME` from now.
+    pub expires_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,36 @@
+use crate::organizations::entity::MembershipRole;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Type};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Type, PartialEq)]
+#[sqlx(type_name = "invitation_status")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum InvitationStatus {
+    PENDING,
+    ACCEPTED,
+    REVOKED,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Invitation {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub email: String,
+    pub role: MembershipRole,
+    pub status: InvitationStatus,
+    pub invited_by: Option<Uuid>,
+    pub accepted_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub sent_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Invitation {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
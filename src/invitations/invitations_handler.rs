@@ -0,0 +1,70 @@
+use crate::{
+    invitations::{dto::CreateInvitationDTO, invitations_service},
+    middlewares::middleware_auth::JwtAuthMiddleware,
+    server::AppState,
+    utils::{errors::AppError, query_paginaton::QueryPagination},
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_qs::actix::QsQuery;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub fn configure(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/invitations")
+            .wrap(JwtAuthMiddleware::new(app_state))
+            .service(web::resource("/{id}/resend").route(web::post().to(resend)))
+            .service(web::resource("/{id}").route(web::delete().to(revoke)))
+            .service(
+                web::resource("")
+                    .route(web::get().to(find_all))
+                    .route(web::post().to(create)),
+            ),
+    );
+}
+
+async fn create(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    payload: web::Json<CreateInvitationDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match invitations_service::create(&pool, &app_state, payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_all(
+    pool: web::Data<PgPool>,
+    query_pagination: QsQuery<QueryPagination>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match invitations_service::find_all(&pool, query_pagination.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn resend(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match invitations_service::resend(&pool, &app_state, id.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn revoke(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match invitations_service::revoke(&pool, id.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,251 @@
+use crate::{
+    invitations::entity::{Invitation, InvitationStatus},
+    organizations::entity::MembershipRole,
+    utils::{errors::AppError, query_paginaton::ResultWithPagination},
+};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+pub async fn create_invitation(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    email: &str,
+    role: MembershipRole,
+    invited_by: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<Invitation, AppError> {
+    let result = sqlx::query_as::<_, Invitation>(
+        r#"--sql
+        INSERT INTO
+            invitations (organization_id, email, role, invited_by, expires_at)
+        VALUES
+            ($1, $2, $3, $4, $5)
+        RETURNING
+            *
+        "#,
+    )
+    .bind(organization_id)
+    .bind(email)
+    .bind(role)
+    .bind(invited_by)
+    .bind(expires_at)
+    .fetch_one(conn)
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::Database(err) if err.is_unique_violation() => AppError::Conflict(format!(
+            "A pending invitation for {} already exists, resend it instead.",
+            email
+        )),
+        err => AppError::DatabaseError(err),
+    })?;
+
+    Ok(result)
+}
+
+pub async fn find_invitations(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    limit: i64,
+    offset: i64,
+    page: i64,
+) -> Result<ResultWithPagination<Vec<Invitation>>, AppError> {
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(*)
+        FROM
+            invitations
+        WHERE
+            organization_id = $1
+        "#,
+    )
+    .bind(organization_id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    let result = sqlx::query_as::<_, Invitation>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            invitations
+        WHERE
+            organization_id = $1
+        ORDER BY
+            created_at DESC, id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(organization_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(ResultWithPagination::new(
+        limit,
+        page,
+        count,
+        result.len(),
+        result,
+    ))
+}
+
+pub async fn find_invitation(pool: &PgPool, id: Uuid) -> Result<Option<Invitation>, AppError> {
+    let result = sqlx::query_as::<_, Invitation>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            invitations
+        WHERE
+            id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Locks the invitation for the rest of the transaction so it is resent, revoked or
+/// accepted only once.
+pub async fn lock_invitation(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<Invitation>, AppError> {
+    let result = sqlx::query_as::<_, Invitation>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            invitations
+        WHERE
+            id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn renew_invitation(
+    conn: &mut PgConnection,
+    id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<Invitation, AppError> {
+    let result = sqlx::query_as::<_, Invitation>(
+        r#"--sql
+        UPDATE
+            invitations
+        SET
+            sent_at = $1,
+            expires_at = $2
+        WHERE
+            id = $3
+        RETURNING
+            *
+        "#,
+    )
+    .bind(Utc::now())
+    .bind(expires_at)
+    .bind(id)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn revoke_invitation(conn: &mut PgConnection, id: Uuid) -> Result<Invitation, AppError> {
+    let result = sqlx::query_as::<_, Invitation>(
+        r#"--sql
+        UPDATE
+            invitations
+        SET
+            status = $1,
+            revoked_at = $2
+        WHERE
+            id = $3
+        RETURNING
+            *
+        "#,
+    )
+    .bind(InvitationStatus::REVOKED)
+    .bind(Utc::now())
+    .bind(id)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn accept_invitation(
+    conn: &mut PgConnection,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<Invitation, AppError> {
+    let result = sqlx::query_as::<_, Invitation>(
+        r#"--sql
+        UPDATE
+            invitations
+        SET
+            status = $1,
+            accepted_by = $2,
+            accepted_at = $3
+        WHERE
+            id = $4
+        RETURNING
+            *
+        "#,
+    )
+    .bind(InvitationStatus::ACCEPTED)
+    .bind(user_id)
+    .bind(Utc::now())
+    .bind(id)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Whether an account with the email already belongs to the organization.
+pub async fn is_member_email(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    email: &str,
+) -> Result<bool, AppError> {
+    let result: bool = sqlx::query_scalar(
+        r#"--sql
+        SELECT
+            EXISTS (
+                SELECT
+                    1
+                FROM
+                    memberships
+                    JOIN users ON users.id = memberships.user_id
+                WHERE
+                    memberships.organization_id = $1
+                    AND LOWER(users.email) = LOWER($2)
+            )
+        "#,
+    )
+    .bind(organization_id)
+    .bind(email)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
@@ -0,0 +1,272 @@
+use crate::{
+    audit::{audit_query, entity::AuditAction},
+    auth::auth_service::generate_invitation_link,
+    invitations::{
+        dto::CreateInvitationDTO,
+        entity::{Invitation, InvitationStatus},
+        invitations_query,
+    },
+    organizations::{entity::MembershipRole, organizations_query},
+    server::AppState,
+    utils::{
+        audit::{diff, AuditContext},
+        auth::{organization_id_in_token, user_id_in_token, validate_admin_in_token},
+        errors::AppError,
+        mailer::Mail,
+        query_paginaton::QueryPagination,
+        response_data::{ResponseData, ResponseDatas},
+        tenant::Tenant,
+    },
+};
+use actix_web::{web, HttpRequest};
+use chrono::{Duration, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+/// Upper bound for an explicit `expires_at`, links should not stay usable for months.
+const INVITATION_MAX_LIFETIME_DAYS: i64 = 30;
+
+pub async fn create(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    payload: CreateInvitationDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<Invitation>, AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let organization_id = organization_id_in_token(req)?;
+    let now = Utc::now();
+    let expires_at = match payload.expires_at {
+        Some(expires_at) if expires_at <= now => {
+            return Err(AppError::BadRequest(
+                "Invitation expiry must be in the future.".to_string(),
+            ))
+        }
+        Some(expires_at) if expires_at > now + Duration::days(INVITATION_MAX_LIFETIME_DAYS) => {
+            return Err(AppError::BadRequest(format!(
+                "Invitation expiry must be within {} days.",
+                INVITATION_MAX_LIFETIME_DAYS
+            )))
+        }
+        Some(expires_at) => expires_at,
+        None => now + *app_state.invite_expiration_time,
+    };
+
+    let mut tx = Tenant::Organization(organization_id).begin(pool).await?;
+    validate_inviter(&mut tx, organization_id, Some(payload.role), req).await?;
+
+    if invitations_query::is_member_email(&mut tx, organization_id, &payload.email).await? {
+        return Err(AppError::Conflict(format!(
+            "{} is already a member of the organization.",
+            payload.email
+        )));
+    }
+
+    let invitation = invitations_query::create_invitation(
+        &mut tx,
+        organization_id,
+        &payload.email,
+        payload.role,
+        user_id_in_token(req)?,
+        expires_at,
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::InvitationCreated,
+        invitation.id,
+        diff(None, Some(&invitation)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    send_invitation(pool, app_state, &invitation).await?;
+
+    Ok(ResponseData::new(
+        invitation,
+        "Data has been successfuly created.",
+    ))
+}
+
+pub async fn find_all(
+    pool: &PgPool,
+    query_pagination: QueryPagination,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<Invitation>>, AppError> {
+    let organization_id = organization_id_in_token(req)?;
+
+    let mut conn = pool.acquire().await.map_err(AppError::DatabaseError)?;
+    validate_inviter(&mut conn, organization_id, None, req).await?;
+
+    if query_pagination.after.is_some() || query_pagination.before.is_some() {
+        return Err(AppError::BadRequest(
+            "Cursor pagination is not supported for invitations.".to_string(),
+        ));
+    }
+
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let result =
+        invitations_query::find_invitations(&mut conn, organization_id, limit, offset, page)
+            .await?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        result.data,
+    ))
+}
+
+/// Sends the invitation again with a fresh expiry, also for invitations that expired.
+pub async fn resend(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<Invitation>, AppError> {
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+
+    let invitation = lock_pending_invitation(&mut tx, id, req).await?;
+    let renewed = invitations_query::renew_invitation(
+        &mut tx,
+        id,
+        Utc::now() + *app_state.invite_expiration_time,
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::InvitationResent,
+        id,
+        diff(Some(&invitation), Some(&renewed)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    send_invitation(pool, app_state, &renewed).await?;
+
+    Ok(ResponseData::new(
+        renewed,
+        "Data has been successfuly updated.",
+    ))
+}
+
+pub async fn revoke(
+    pool: &PgPool,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<Invitation>, AppError> {
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+
+    let invitation = lock_pending_invitation(&mut tx, id, req).await?;
+    let revoked = invitations_query::revoke_invitation(&mut tx, id).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::InvitationRevoked,
+        id,
+        diff(Some(&invitation), Some(&revoked)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        revoked,
+        "Data has been successfuly revoked.",
+    ))
+}
+
+/// Accepted and revoked invitations are final.
+pub fn ensure_pending(invitation: &Invitation) -> Result<(), AppError> {
+    match invitation.status {
+        InvitationStatus::PENDING => Ok(()),
+        InvitationStatus::ACCEPTED => Err(AppError::Conflict(
+            "Invitation has already been accepted.".to_string(),
+        )),
+        InvitationStatus::REVOKED => Err(AppError::Conflict(
+            "Invitation has been revoked.".to_string(),
+        )),
+    }
+}
+
+/// Pending invitation of the active organization, invitations of other organizations
+/// are reported as missing.
+async fn lock_pending_invitation(
+    conn: &mut PgConnection,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<Invitation, AppError> {
+    let organization_id = organization_id_in_token(req)?;
+
+    let invitation = invitations_query::lock_invitation(conn, id)
+        .await?
+        .filter(|invitation| invitation.organization_id == organization_id)
+        .ok_or(AppError::NotFound(format!(
+            "Invitation with ID {} not found",
+            id
+        )))?;
+
+    validate_inviter(conn, organization_id, Some(invitation.role), req).await?;
+    ensure_pending(&invitation)?;
+
+    Ok(invitation)
+}
+
+/// Invitations are managed by owners and admins of the organization or by platform
+/// admins, only owners invite further owners.
+async fn validate_inviter(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    invited_role: Option<MembershipRole>,
+    req: &HttpRequest,
+) -> Result<(), AppError> {
+    if validate_admin_in_token(req).is_ok() {
+        return Ok(());
+    }
+
+    let user_id = user_id_in_token(req)?;
+    let role = organizations_query::find_membership(conn, organization_id, user_id)
+        .await?
+        .map(|membership| membership.role);
+
+    if !role.is_some_and(|role| role.can_manage_members()) {
+        return Err(AppError::Forbidden(
+            "Organization admin role is required to manage invitations".to_string(),
+        ));
+    }
+
+    if invited_role == Some(MembershipRole::OWNER) && role != Some(MembershipRole::OWNER) {
+        return Err(AppError::Forbidden(
+            "Organization owner role is required to invite an owner".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn send_invitation(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    invitation: &Invitation,
+) -> Result<(), AppError> {
+    let organization =
+        organizations_query::find_organization(pool, invitation.organization_id).await?;
+    let link = generate_invitation_link(invitation, app_state)?;
+
+    app_state
+        .mailer
+        .send(Mail {
+            to: invitation.email.clone(),
+            subject: format!("You have been invited to {}", organization.name),
+            body: format!(
+                "You have been invited to join {} as {}. Accept the invitation: {}\nThe link expires at {}.",
+                organization.name,
+                invitation.role.as_str(),
+                link, invitation.expires_at
+            ),
+        })
+        .await
+}
@@ -0,0 +1,156 @@
+use crate::{
+    configs::config_env::Config,
+    events::{
+        event_sink::{EndpointsSink, EventSink, FanoutSink, LogSink, WebhookSink},
+        events_query,
+    },
+    utils::{backoff::Backoff, errors::AppError},
+};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+// Failed events are retried after 5s, 10s, 20s, ... up to an hour between attempts.
+const RETRY_BACKOFF: Backoff = Backoff::new(5, 3600);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutboxSink {
+    Log,
+    Webhook(String),
+    /// Webhook endpoints registered through the API.
+    Endpoints,
+}
+
+impl OutboxSink {
+    pub fn build(&self, pool: &PgPool) -> Arc<dyn EventSink> {
+        match self {
+            OutboxSink::Log => Arc::new(LogSink),
+            OutboxSink::Webhook(url) => Arc::new(WebhookSink::new(url.clone())),
+            OutboxSink::Endpoints => Arc::new(EndpointsSink::new(pool.clone())),
+        }
+    }
+}
+
+/// Sink publishing to every configured sink in order.
+pub fn build_sink(sinks: &[OutboxSink], pool: &PgPool) -> Arc<dyn EventSink> {
+    match sinks {
+        [sink] => sink.build(pool),
+        sinks => Arc::new(FanoutSink::new(
+            sinks.iter().map(|sink| sink.build(pool)).collect(),
+        )),
+    }
+}
+
+impl std::fmt::Display for OutboxSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutboxSink::Log => write!(f, "log"),
+            OutboxSink::Webhook(_) => write!(f, "webhook"),
+            OutboxSink::Endpoints => write!(f, "endpoints"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct OutboxRelayConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+    pub retention: Duration,
+}
+
+impl From<&Config> for OutboxRelayConfig {
+    fn from(config: &Config) -> Self {
+        OutboxRelayConfig {
+            interval: config.outbox_interval_time,
+            batch_size: config.outbox_batch_size,
+            retention: config.outbox_retention_time,
+        }
+    }
+}
+
+pub fn spawn(pool: PgPool, sink: Arc<dyn EventSink>, config: OutboxRelayConfig) -> JoinHandle<()> {
+    log::info!(
+        "outbox_relay event=scheduled interval_seconds={} batch_size={} retention_seconds={} sink={:?}",
+        config.interval.num_seconds(),
+        config.batch_size,
+        config.retention.num_seconds(),
+        sink,
+    );
+
+    tokio::spawn(async move {
+        let period = config
+            .interval
+            .to_std()
+            .unwrap_or(std::time::Duration::from_secs(1));
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = run(&pool, sink.as_ref(), &config).await {
+                log::error!("outbox_relay event=failed error=\"{:?}\"", err);
+            }
+        }
+    })
+}
+
+/// Publishes every due event and removes published events past the retention, returns
+/// the number of events published. An event is marked published in the transaction
+/// that locked it, a crash in between publishes it again (at-least-once).
+pub async fn run(
+    pool: &PgPool,
+    sink: &dyn EventSink,
+    config: &OutboxRelayConfig,
+) -> Result<u64, AppError> {
+    let mut published: u64 = 0;
+
+    loop {
+        let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+        let events = events_query::claim_events(&mut tx, config.batch_size).await?;
+        if events.is_empty() {
+            break;
+        }
+
+        for event in events {
+            match sink.publish(&event).await {
+                Ok(()) => {
+                    events_query::mark_event_published(&mut tx, event.id).await?;
+                    published += 1;
+                }
+                Err(err) => {
+                    let next_attempt_at = Utc::now() + RETRY_BACKOFF.delay(event.attempts + 1);
+                    log::warn!(
+                        "outbox_relay event=publish_failed outbox_id={} type={} attempts={} next_attempt_at={} error=\"{}\"",
+                        event.id,
+                        event.event_type,
+                        event.attempts + 1,
+                        next_attempt_at.to_rfc3339(),
+                        err.message(),
+                    );
+                    events_query::record_event_failure(
+                        &mut tx,
+                        event.id,
+                        &err.message(),
+                        next_attempt_at,
+                    )
+                    .await?;
+                }
+            }
+        }
+
+        tx.commit().await.map_err(AppError::DatabaseError)?;
+    }
+
+    let removed =
+        events_query::delete_published_events(pool, Utc::now() - config.retention).await?;
+    if published > 0 || removed > 0 {
+        log::info!(
+            "outbox_relay event=completed published={} removed={}",
+            published,
+            removed
+        );
+    }
+
+    Ok(published)
+}
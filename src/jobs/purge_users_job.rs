@@ -0,0 +1,226 @@
+use crate::{
+    audit::{audit_query, entity::AuditAction},
+    configs::config_env::Config,
+    events::{entity::DomainEvent, events_query},
+    users::{users_query, users_service},
+    utils::{audit::AuditContext, errors::AppError, storage::Storage, tenant::Tenant},
+};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::{pool::PoolConnection, Acquire, PgPool, Postgres};
+use std::{str::FromStr, sync::Arc};
+use tokio::task::JoinHandle;
+
+// Advisory lock key shared by every replica, only the holder runs the purge.
+const PURGE_LOCK_KEY: i64 = 0x7075_7267_655f_7573;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PurgeMode {
+    Delete,
+    Anonymize,
+}
+
+impl FromStr for PurgeMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "delete" => Ok(PurgeMode::Delete),
+            "anonymize" => Ok(PurgeMode::Anonymize),
+            _ => Err("PURGE_MODE must be 'delete' or 'anonymize'".to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for PurgeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurgeMode::Delete => write!(f, "delete"),
+            PurgeMode::Anonymize => write!(f, "anonymize"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PurgeUsersConfig {
+    pub interval: Duration,
+    pub retention: Duration,
+    pub batch_size: i64,
+    pub dry_run: bool,
+    pub mode: PurgeMode,
+}
+
+impl From<&Config> for PurgeUsersConfig {
+    fn from(config: &Config) -> Self {
+        PurgeUsersConfig {
+            interval: config.purge_interval_time,
+            retention: config.purge_retention_time,
+            batch_size: config.purge_batch_size,
+            dry_run: config.purge_dry_run,
+            mode: config.purge_mode,
+        }
+    }
+}
+
+pub fn spawn(pool: PgPool, storage: Arc<dyn Storage>, config: PurgeUsersConfig) -> JoinHandle<()> {
+    log::info!(
+        "purge_users event=scheduled interval_seconds={} retention_seconds={} batch_size={} mode={} dry_run={}",
+        config.interval.num_seconds(),
+        config.retention.num_seconds(),
+        config.batch_size,
+        config.mode,
+        config.dry_run,
+    );
+
+    tokio::spawn(async move {
+        let period = config
+            .interval
+            .to_std()
+            .unwrap_or(std::time::Duration::from_secs(3600));
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = run(&pool, storage.as_ref(), &config).await {
+                log::error!("purge_users event=failed error=\"{:?}\"", err);
+            }
+        }
+    })
+}
+
+/// Runs a single purge pass, returns the number of affected users.
+pub async fn run(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    config: &PurgeUsersConfig,
+) -> Result<u64, AppError> {
+    let mut conn = pool.acquire().await.map_err(AppError::DatabaseError)?;
+
+    let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_lock($1)")
+        .bind(PURGE_LOCK_KEY)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    if !locked {
+        log::info!("purge_users event=skipped reason=lock_held");
+        return Ok(0);
+    }
+
+    let result = purge(&mut conn, storage, config).await;
+
+    let unlocked = sqlx::query_scalar::<_, bool>("SELECT pg_advisory_unlock($1)")
+        .bind(PURGE_LOCK_KEY)
+        .fetch_one(&mut *conn)
+        .await;
+
+    if !matches!(unlocked, Ok(true)) {
+        // Closing the session is the only other way to release the lock.
+        log::warn!("purge_users event=unlock_failed action=close_connection");
+        drop(conn.detach());
+    }
+
+    result
+}
+
+async fn purge(
+    conn: &mut PoolConnection<Postgres>,
+    storage: &dyn Storage,
+    config: &PurgeUsersConfig,
+) -> Result<u64, AppError> {
+    let cutoff = Utc::now() - config.retention;
+
+    if config.dry_run {
+        let mut tx = conn.begin().await.map_err(AppError::DatabaseError)?;
+        Tenant::Unscoped.set_local(&mut tx).await?;
+        let candidates = users_query::count_purgeable_users(&mut tx, cutoff).await?;
+        tx.commit().await.map_err(AppError::DatabaseError)?;
+        log::info!(
+            "purge_users event=dry_run mode={} cutoff={} candidates={}",
+            config.mode,
+            cutoff.to_rfc3339(),
+            candidates,
+        );
+        return Ok(0);
+    }
+
+    let mut total: u64 = 0;
+    let mut batch: u64 = 0;
+
+    loop {
+        let mut tx = conn.begin().await.map_err(AppError::DatabaseError)?;
+        Tenant::Unscoped.set_local(&mut tx).await?;
+
+        let ids = users_query::lock_purgeable_users(&mut tx, cutoff, config.batch_size).await?;
+
+        // Written first, the events go to the organizations a delete removes the users from.
+        events_query::insert_events(
+            &mut tx,
+            ids.iter()
+                .map(|id| DomainEvent::UserDeleted {
+                    user_id: *id,
+                    permanent: true,
+                })
+                .collect(),
+        )
+        .await?;
+
+        let (keys, action) = match config.mode {
+            PurgeMode::Delete => {
+                // Erased before the delete, the cascade would drop the export rows and
+                // with them the storage keys of their archives. The history trigger
+                // copies the deleted rows, so that copy goes afterwards.
+                let keys = users_service::erase_user_data(&mut tx, &ids).await?;
+                users_query::purge_users(&mut tx, &ids).await?;
+                users_query::delete_users_history(&mut tx, &ids).await?;
+                (keys, AuditAction::UserPurged)
+            }
+            PurgeMode::Anonymize => {
+                users_query::anonymize_users(&mut tx, &ids).await?;
+                let keys = users_service::erase_user_data(&mut tx, &ids).await?;
+                (keys, AuditAction::UserAnonymized)
+            }
+        };
+
+        audit_query::insert_audit_events(
+            &mut tx,
+            &AuditContext::system(),
+            action,
+            ids.iter().map(|id| (*id, json!({}))).collect(),
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::DatabaseError)?;
+
+        users_service::delete_user_files(storage, keys).await;
+
+        if ids.is_empty() {
+            break;
+        }
+
+        batch += 1;
+        total += ids.len() as u64;
+
+        log::info!(
+            "purge_users event=batch mode={} batch={} affected={}",
+            config.mode,
+            batch,
+            ids.len(),
+        );
+
+        if (ids.len() as i64) < config.batch_size {
+            break;
+        }
+    }
+
+    log::info!(
+        "purge_users event=completed mode={} cutoff={} batches={} affected={}",
+        config.mode,
+        cutoff.to_rfc3339(),
+        batch,
+        total,
+    );
+
+    Ok(total)
+}
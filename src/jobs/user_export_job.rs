@@ -0,0 +1,225 @@
+use crate::{
+    audit::audit_query,
+    configs::config_env::Config,
+    users::{
+        dto::data_export_users_dto::DataExportDocument,
+        entity::{UserExport, UserExportFormat},
+        users_query,
+    },
+    utils::{
+        avatar::{avatar_key, AvatarSize},
+        errors::AppError,
+        query_fields::Fieldset,
+        storage::Storage,
+        tenant::Tenant,
+    },
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use std::{
+    io::{Cursor, Write},
+    sync::Arc,
+};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+// An export still processing after this long is assumed abandoned and claimed again.
+const STALE_AFTER_SECONDS: i64 = 900;
+
+#[derive(Debug, Clone)]
+pub struct UserExportConfig {
+    pub interval: Duration,
+    pub retention: Duration,
+}
+
+impl From<&Config> for UserExportConfig {
+    fn from(config: &Config) -> Self {
+        UserExportConfig {
+            interval: config.export_interval_time,
+            retention: config.export_retention_time,
+        }
+    }
+}
+
+pub fn export_key(export: &UserExport) -> String {
+    format!(
+        "exports/{}/{}.{}",
+        export.user_id,
+        export.id,
+        export.format.extension()
+    )
+}
+
+pub fn spawn(pool: PgPool, storage: Arc<dyn Storage>, config: UserExportConfig) -> JoinHandle<()> {
+    log::info!(
+        "user_export event=scheduled interval_seconds={} retention_seconds={}",
+        config.interval.num_seconds(),
+        config.retention.num_seconds(),
+    );
+
+    tokio::spawn(async move {
+        let period = config
+            .interval
+            .to_std()
+            .unwrap_or(std::time::Duration::from_secs(5));
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = run(&pool, storage.as_ref(), &config).await {
+                log::error!("user_export event=failed error=\"{:?}\"", err);
+            }
+        }
+    })
+}
+
+/// Builds every queued export and removes expired archives, returns the number of
+/// exports built. Claims use `SKIP LOCKED` so several replicas can run this at once.
+pub async fn run(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    config: &UserExportConfig,
+) -> Result<u64, AppError> {
+    let mut built: u64 = 0;
+
+    loop {
+        let stale_before = Utc::now() - Duration::seconds(STALE_AFTER_SECONDS);
+        let Some(export) = users_query::claim_user_export(pool, stale_before).await? else {
+            break;
+        };
+
+        match build(pool, storage, &export).await {
+            Ok(size) => {
+                let key = export_key(&export);
+                let expires_at = Utc::now() + config.retention;
+                if !users_query::complete_user_export(pool, export.id, &key, expires_at).await? {
+                    storage.delete(&key).await?;
+                    log::info!("user_export event=discarded export_id={}", export.id);
+                    continue;
+                }
+                built += 1;
+
+                log::info!(
+                    "user_export event=completed export_id={} user_id={} format={} bytes={}",
+                    export.id,
+                    export.user_id,
+                    export.format.extension(),
+                    size,
+                );
+            }
+            Err(err) => {
+                log::error!(
+                    "user_export event=build_failed export_id={} user_id={} error=\"{:?}\"",
+                    export.id,
+                    export.user_id,
+                    err,
+                );
+                users_query::fail_user_export(pool, export.id, &err.message()).await?;
+            }
+        }
+    }
+
+    for export in users_query::expire_user_exports(pool).await? {
+        if let Some(key) = &export.storage_key {
+            if let Err(err) = storage.delete(key).await {
+                log::warn!(
+                    "user_export event=cleanup_failed export_id={} error=\"{:?}\"",
+                    export.id,
+                    err,
+                );
+                continue;
+            }
+        }
+
+        log::info!("user_export event=expired export_id={}", export.id);
+    }
+
+    Ok(built)
+}
+
+/// Collects everything stored about the user into an archive and stores it under
+/// `export_key`, returns the archive size in bytes.
+async fn build(
+    pool: &PgPool,
+    storage: &dyn Storage,
+    export: &UserExport,
+) -> Result<usize, AppError> {
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let profile =
+        users_query::find_user(&mut tx, Tenant::Unscoped, export.user_id, &Fieldset::all()).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+    let email_change_requests =
+        users_query::find_email_change_requests(pool, export.user_id).await?;
+    let audit_events = audit_query::find_user_audit_events(pool, export.user_id).await?;
+    let avatar = match profile.avatar_updated_at {
+        Some(_) => {
+            storage
+                .get(&avatar_key(export.user_id, AvatarSize::Large))
+                .await?
+        }
+        None => None,
+    };
+
+    let mut document = DataExportDocument {
+        generated_at: Utc::now(),
+        profile,
+        email_change_requests,
+        audit_events,
+        avatar: None,
+    };
+
+    let bytes = match export.format {
+        UserExportFormat::Json => {
+            document.avatar = avatar.map(|bytes| STANDARD.encode(bytes));
+            serde_json::to_vec_pretty(&document)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?
+        }
+        UserExportFormat::Zip => {
+            let id = export.id;
+            tokio::task::spawn_blocking(move || write_zip(id, &document, avatar))
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))??
+        }
+    };
+
+    let size = bytes.len();
+    storage.put(&export_key(export), bytes).await?;
+
+    Ok(size)
+}
+
+fn write_zip(
+    id: Uuid,
+    document: &DataExportDocument,
+    avatar: Option<Vec<u8>>,
+) -> Result<Vec<u8>, AppError> {
+    let zip_error = |e: zip::result::ZipError| {
+        AppError::InternalServerError(format!("Failed to write export {}: {}", id, e))
+    };
+    let io_error = |e: std::io::Error| {
+        AppError::InternalServerError(format!("Failed to write export {}: {}", id, e))
+    };
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+    writer.start_file("user.json", options).map_err(zip_error)?;
+    serde_json::to_writer_pretty(&mut writer, document)
+        .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    if let Some(avatar) = avatar {
+        // PNG is already compressed.
+        writer
+            .start_file(
+                "avatar.png",
+                options.compression_method(CompressionMethod::Stored),
+            )
+            .map_err(zip_error)?;
+        writer.write_all(&avatar).map_err(io_error)?;
+    }
+
+    Ok(writer.finish().map_err(zip_error)?.into_inner())
+}
@@ -0,0 +1,256 @@
+use crate::{
+    configs::config_env::Config,
+    utils::{
+        backoff::Backoff,
+        errors::AppError,
+        webhook::{
+            sign_payload, validate_webhook_host, PublicResolver, DELIVERY_HEADER, EVENT_HEADER,
+            SIGNATURE_HEADER, TIMESTAMP_HEADER,
+        },
+    },
+    webhooks::{
+        entity::{ClaimedDelivery, WebhookDeliveryStatus},
+        webhooks_query,
+    },
+};
+use chrono::{Duration, Utc};
+use futures::future::join_all;
+use sqlx::PgPool;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+// Attempts are spaced 10s, 20s, 40s, ... apart, at most six hours.
+const RETRY_BACKOFF: Backoff = Backoff::new(10, 21600);
+
+#[derive(Debug, Clone)]
+pub struct WebhookDeliveryConfig {
+    pub interval: Duration,
+    pub batch_size: i64,
+    pub timeout: Duration,
+    pub max_attempts: i32,
+    pub disable_after: i32,
+    pub retention: Duration,
+    /// Lets endpoints resolve to private, loopback and link-local addresses, for local
+    /// development and tests only.
+    pub allow_private_networks: bool,
+}
+
+impl From<&Config> for WebhookDeliveryConfig {
+    fn from(config: &Config) -> Self {
+        WebhookDeliveryConfig {
+            interval: config.webhook_interval_time,
+            batch_size: config.webhook_batch_size,
+            timeout: config.webhook_timeout,
+            max_attempts: config.webhook_max_attempts,
+            disable_after: config.webhook_disable_after,
+            retention: config.webhook_retention_time,
+            allow_private_networks: config.webhook_allow_private_networks,
+        }
+    }
+}
+
+impl WebhookDeliveryConfig {
+    pub fn client(&self) -> reqwest::Client {
+        let builder = reqwest::Client::builder()
+            .timeout(
+                self.timeout
+                    .to_std()
+                    .unwrap_or(std::time::Duration::from_secs(10)),
+            )
+            .redirect(reqwest::redirect::Policy::none());
+
+        // A proxy would resolve the host itself and bypass the resolver.
+        let builder = match self.allow_private_networks {
+            true => builder,
+            false => builder.no_proxy().dns_resolver(Arc::new(PublicResolver)),
+        };
+
+        builder.build().expect("Valid HTTP client configuration")
+    }
+}
+
+#[derive(Debug)]
+struct Attempt {
+    response_status: Option<i32>,
+    error: Option<String>,
+}
+
+pub fn spawn(pool: PgPool, config: WebhookDeliveryConfig) -> JoinHandle<()> {
+    log::info!(
+        "webhook_delivery event=scheduled interval_seconds={} batch_size={} max_attempts={} disable_after={} retention_seconds={}",
+        config.interval.num_seconds(),
+        config.batch_size,
+        config.max_attempts,
+        config.disable_after,
+        config.retention.num_seconds(),
+    );
+
+    tokio::spawn(async move {
+        let client = config.client();
+        let period = config
+            .interval
+            .to_std()
+            .unwrap_or(std::time::Duration::from_secs(1));
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = run(&pool, &client, &config).await {
+                log::error!("webhook_delivery event=failed error=\"{:?}\"", err);
+            }
+        }
+    })
+}
+
+/// Sends every due delivery and removes finished deliveries past the retention, returns
+/// the number of successful deliveries. A batch is sent concurrently while its rows stay
+/// locked, a crash before the commit sends it again (at-least-once).
+pub async fn run(
+    pool: &PgPool,
+    client: &reqwest::Client,
+    config: &WebhookDeliveryConfig,
+) -> Result<u64, AppError> {
+    let mut delivered: u64 = 0;
+
+    loop {
+        let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+        let claimed = webhooks_query::claim_deliveries(&mut tx, config.batch_size).await?;
+        if claimed.is_empty() {
+            break;
+        }
+
+        let attempts = join_all(
+            claimed
+                .iter()
+                .map(|claimed| send(client, claimed, config.allow_private_networks)),
+        )
+        .await;
+
+        for (claimed, attempt) in claimed.iter().zip(attempts) {
+            let delivery = &claimed.delivery;
+
+            if attempt.error.is_none() {
+                webhooks_query::record_delivery_attempt(
+                    &mut tx,
+                    delivery.id,
+                    WebhookDeliveryStatus::SUCCEEDED,
+                    attempt.response_status,
+                    None,
+                    None,
+                )
+                .await?;
+                webhooks_query::reset_endpoint_failures(&mut tx, delivery.endpoint_id).await?;
+                delivered += 1;
+                continue;
+            }
+
+            let attempts = delivery.attempts + 1;
+            let (status, next_attempt_at) = match attempts >= config.max_attempts {
+                true => (WebhookDeliveryStatus::FAILED, None),
+                false => (
+                    WebhookDeliveryStatus::PENDING,
+                    Some(Utc::now() + RETRY_BACKOFF.delay_with_jitter(attempts)),
+                ),
+            };
+            log::warn!(
+                "webhook_delivery event=attempt_failed delivery_id={} endpoint_id={} attempts={} status={:?} error=\"{}\"",
+                delivery.id,
+                delivery.endpoint_id,
+                attempts,
+                status,
+                attempt.error.as_deref().unwrap_or_default(),
+            );
+            webhooks_query::record_delivery_attempt(
+                &mut tx,
+                delivery.id,
+                status,
+                attempt.response_status,
+                attempt.error,
+                next_attempt_at,
+            )
+            .await?;
+
+            if webhooks_query::record_endpoint_failure(
+                &mut tx,
+                delivery.endpoint_id,
+                config.disable_after,
+            )
+            .await?
+            {
+                log::warn!(
+                    "webhook_delivery event=endpoint_disabled endpoint_id={} consecutive_failures={}",
+                    delivery.endpoint_id,
+                    config.disable_after,
+                );
+            }
+        }
+
+        tx.commit().await.map_err(AppError::DatabaseError)?;
+    }
+
+    let removed =
+        webhooks_query::delete_finished_deliveries(pool, Utc::now() - config.retention).await?;
+    if delivered > 0 || removed > 0 {
+        log::info!(
+            "webhook_delivery event=completed delivered={} removed={}",
+            delivered,
+            removed
+        );
+    }
+
+    Ok(delivered)
+}
+
+/// Posts the stored payload signed with the endpoint secret, any response other than
+/// 2xx is a failed attempt.
+async fn send(
+    client: &reqwest::Client,
+    claimed: &ClaimedDelivery,
+    allow_private_networks: bool,
+) -> Attempt {
+    let delivery = &claimed.delivery;
+
+    if !allow_private_networks {
+        if let Err(error) = validate_webhook_host(&claimed.url) {
+            return Attempt {
+                response_status: None,
+                error: Some(error),
+            };
+        }
+    }
+
+    let body = delivery.payload.to_string();
+    let timestamp = Utc::now().timestamp();
+
+    let response = client
+        .post(&claimed.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(DELIVERY_HEADER, delivery.id.to_string())
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(
+            SIGNATURE_HEADER,
+            sign_payload(&claimed.secret, timestamp, body.as_bytes()),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    let response = match response {
+        Ok(response) => response,
+        Err(err) => {
+            return Attempt {
+                response_status: None,
+                error: Some(err.to_string()),
+            }
+        }
+    };
+
+    let status = response.status();
+
+    Attempt {
+        response_status: Some(status.as_u16() as i32),
+        error: (!status.is_success()).then(|| format!("Endpoint responded with {}", status)),
+    }
+}
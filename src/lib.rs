@@ -8,17 +8,41 @@ pub mod configs {
 pub mod middlewares {
     pub mod middleware_auth;
     pub mod middleware_logger;
+    pub mod middleware_request_id;
 }
 
 pub mod utils {
+    pub mod audit;
     pub mod auth;
+    pub mod avatar;
+    pub mod backoff;
     pub mod errors;
+    pub mod etag;
     pub mod jwt;
     pub mod logger;
+    pub mod mailer;
+    pub mod metadata;
     pub mod password;
+    pub mod preferences;
+    pub mod profile;
+    pub mod query_cursor;
+    pub mod query_fields;
+    pub mod query_filter;
     pub mod query_paginaton;
+    pub mod query_sort;
     pub mod response_data;
+    pub mod storage;
+    pub mod tenant;
     pub mod time;
+    pub mod token;
+    pub mod webhook;
+}
+
+pub mod jobs {
+    pub mod outbox_relay_job;
+    pub mod purge_users_job;
+    pub mod user_export_job;
+    pub mod webhook_delivery_job;
 }
 
 pub mod router;
@@ -28,34 +52,166 @@ pub mod server;
 // User
 pub mod users {
     pub mod dto {
+        pub mod avatar_users_dto;
+        pub mod bulk_users_dto;
         pub mod create_users_dto;
+        pub mod data_export_users_dto;
+        pub mod email_change_users_dto;
+        pub mod export_users_dto;
+        pub mod filter_users_dto;
         pub mod get_users_dto;
+        pub mod history_users_dto;
+        pub mod import_users_dto;
+        pub mod metadata_users_dto;
+        pub mod patch_users_dto;
+        pub mod preferences_users_dto;
+        pub mod search_users_dto;
+        pub mod status_users_dto;
         pub mod update_users_dto;
 
+        pub use avatar_users_dto::AvatarQuery;
         pub use create_users_dto::CreateUserDTO;
+        pub use data_export_users_dto::{
+            CreateDataExportDTO, DataExportDTO, DownloadDataExportQuery,
+        };
+        pub use email_change_users_dto::RequestEmailChangeDTO;
+        pub use export_users_dto::{ExportFormat, ExportUsersQuery};
+        pub use filter_users_dto::{UserFilter, UserFilterQuery};
         pub use get_users_dto::GetUserDTO;
+        pub use history_users_dto::{AsOfQuery, UserVersionDTO};
+        pub use metadata_users_dto::MetadataEntryDTO;
+        pub use patch_users_dto::{PatchUserDocument, UserPatch};
+        pub use preferences_users_dto::UserPreferencesDTO;
+        pub use search_users_dto::{SearchUserDTO, SearchUserQuery};
+        pub use status_users_dto::UpdateUserStatusDTO;
         pub use update_users_dto::*;
     }
 
     pub mod entity {
+        pub mod email_change_model;
+        pub mod user_export_model;
+        pub mod user_preferences_model;
+        pub mod user_status_transition_model;
         pub mod users_model;
 
+        pub use email_change_model::EmailChangeRequest;
+        pub use user_export_model::{UserExport, UserExportFormat, UserExportStatus};
+        pub use user_preferences_model::{
+            DateFormat, NotificationChannel, NotificationPreferences, NotificationTopic,
+            Preferences,
+        };
+        pub use user_status_transition_model::UserStatusTransition;
         pub use users_model::*;
     }
 
+    pub mod users_admin_handler;
+    pub mod users_admin_service;
     pub mod users_handler;
     pub mod users_query;
     pub mod users_service;
 }
 
+pub mod audit {
+    pub mod dto {
+        pub mod filter_audit_dto;
+
+        pub use filter_audit_dto::{AuditEventFilter, AuditEventFilterQuery};
+    }
+
+    pub mod entity {
+        pub mod audit_event_model;
+
+        pub use audit_event_model::*;
+    }
+
+    pub mod audit_handler;
+    pub mod audit_query;
+    pub mod audit_service;
+}
+
+pub mod events {
+    pub mod entity {
+        pub mod domain_event_model;
+        pub mod outbox_event_model;
+
+        pub use domain_event_model::*;
+        pub use outbox_event_model::*;
+    }
+
+    pub mod event_sink;
+    pub mod events_query;
+}
+
+pub mod organizations {
+    pub mod dto {
+        pub mod member_dto;
+        pub mod organization_dto;
+
+        pub use member_dto::{MemberDTO, UpdateMemberDTO};
+        pub use organization_dto::{CreateOrganizationDTO, UserOrganizationDTO};
+    }
+
+    pub mod entity {
+        pub mod organization_model;
+
+        pub use organization_model::*;
+    }
+
+    pub mod organizations_handler;
+    pub mod organizations_query;
+    pub mod organizations_service;
+}
+
+pub mod invitations {
+    pub mod dto {
+        pub mod invitation_dto;
+
+        pub use invitation_dto::CreateInvitationDTO;
+    }
+
+    pub mod entity {
+        pub mod invitation_model;
+
+        pub use invitation_model::*;
+    }
+
+    pub mod invitations_handler;
+    pub mod invitations_query;
+    pub mod invitations_service;
+}
+
 pub mod auth {
     pub mod dto {
+        pub mod email_change_dto;
+        pub mod invite_dto;
         pub mod jwt_dto;
         pub mod login_dto;
 
-        pub use jwt_dto::{Claims, JwtDto};
+        pub use email_change_dto::EmailChangeTokenDto;
+        pub use invite_dto::{AcceptInviteDto, InvitePreviewDTO, InviteTokenQuery};
+        pub use jwt_dto::{Claims, InviteClaims, InvitePurpose, JwtDto};
         pub use login_dto::LoginDto;
     }
     pub mod auth_handler;
     pub mod auth_service;
 }
+
+pub mod webhooks {
+    pub mod dto {
+        pub mod webhook_dto;
+
+        pub use webhook_dto::{CreateWebhookDTO, CreatedWebhookDTO, UpdateWebhookDTO};
+    }
+
+    pub mod entity {
+        pub mod webhook_delivery_model;
+        pub mod webhook_endpoint_model;
+
+        pub use webhook_delivery_model::*;
+        pub use webhook_endpoint_model::*;
+    }
+
+    pub mod webhooks_handler;
+    pub mod webhooks_query;
+    pub mod webhooks_service;
+}
@@ -1,8 +1,18 @@
 use dotenvy::dotenv;
+use std::sync::Arc;
 use web_server::{
     configs::config_load::{load_connection, load_env},
+    jobs::{
+        outbox_relay_job::{self, build_sink, OutboxRelayConfig},
+        purge_users_job::{self, PurgeUsersConfig},
+        user_export_job::{self, UserExportConfig},
+        webhook_delivery_job::{self, WebhookDeliveryConfig},
+    },
     server,
-    utils::logger,
+    utils::{
+        logger,
+        storage::{LocalStorage, Storage},
+    },
 };
 
 #[tokio::main]
@@ -12,6 +22,35 @@ async fn main() -> std::io::Result<()> {
     log::info!("Starting Actix application...");
     let config = load_env();
     let connection = load_connection(&config.db_url).await;
+    let storage: Arc<dyn Storage> = Arc::new(LocalStorage::new(config.storage_path.clone()));
+
+    if config.purge_enabled {
+        purge_users_job::spawn(
+            connection.clone(),
+            storage.clone(),
+            PurgeUsersConfig::from(&config),
+        );
+    }
+
+    if config.export_enabled {
+        user_export_job::spawn(
+            connection.clone(),
+            storage.clone(),
+            UserExportConfig::from(&config),
+        );
+    }
+
+    if config.outbox_enabled {
+        outbox_relay_job::spawn(
+            connection.clone(),
+            build_sink(&config.outbox_sinks, &connection),
+            OutboxRelayConfig::from(&config),
+        );
+    }
+
+    if config.webhook_enabled {
+        webhook_delivery_job::spawn(connection.clone(), WebhookDeliveryConfig::from(&config));
+    }
 
     server::start_server(config.clone(), connection, config.app_env == "producton").await
 }
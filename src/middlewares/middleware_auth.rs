@@ -1,9 +1,14 @@
+use crate::auth::auth_service::validate_login_status;
+use crate::auth::dto::Claims;
 use crate::server::AppState;
+use crate::users::users_query;
 use crate::utils::errors::AppError;
 use crate::utils::jwt::verify_jwt;
+use crate::utils::tenant::Tenant;
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
 use actix_web::{web, Error, HttpMessage};
 use futures::future::{ok, LocalBoxFuture, Ready};
+use sqlx::PgPool;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::task::{Context, Poll};
@@ -63,16 +68,46 @@ where
         let (http_request, payload) = req.into_parts();
 
         let fut = async move {
-            match verify_jwt(&http_request, &state) {
-                Ok(claims) => {
-                    let req = ServiceRequest::from_parts(http_request, payload);
-                    req.extensions_mut().insert(Arc::new(claims));
-                    service.call(req).await
-                }
-                Err(err) => Err(AppError::Unauthorized(err).into()),
-            }
+            let mut claims = verify_jwt(&http_request, &state).map_err(AppError::Unauthorized)?;
+            let pool = http_request
+                .app_data::<web::Data<PgPool>>()
+                .cloned()
+                .ok_or(AppError::InternalServerError(
+                    "Database connection is not configured".to_string(),
+                ))?;
+            validate_account(&pool, &mut claims).await?;
+
+            let req = ServiceRequest::from_parts(http_request, payload);
+            req.extensions_mut().insert(Arc::new(claims));
+            service.call(req).await
         };
 
         Box::pin(fut)
     }
 }
+
+/// Access tokens are only checked for their signature and expiry, so the account is
+/// re-read on every request. Suspending, locking, deleting or erasing an account takes
+/// effect at once instead of when its tokens expire, the role is the current one
+/// rather than the one the token was issued with, and a member removed from the
+/// token's organization loses access to it.
+async fn validate_account(pool: &PgPool, claims: &mut Claims) -> Result<(), AppError> {
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let access = users_query::find_user_access(&mut tx, claims.sub, claims.org).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    let (status, role, membership_role) = access.ok_or(AppError::Unauthorized(
+        "User is no longer active".to_string(),
+    ))?;
+    validate_login_status(&status)?;
+
+    if claims.org.is_some() && membership_role.is_none() {
+        return Err(AppError::Unauthorized(
+            "No longer a member of the active organization, log in again".to_string(),
+        ));
+    }
+
+    claims.role = role;
+    claims.membership_role = membership_role;
+    Ok(())
+}
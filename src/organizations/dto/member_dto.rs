@@ -0,0 +1,40 @@
+use crate::{
+    organizations::entity::MembershipRole,
+    users::{dto::GetUserDTO, entity::User},
+    utils::errors::AppError,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMemberDTO {
+    pub role: MembershipRole,
+}
+
+#[derive(Debug, FromRow)]
+pub struct MemberRow {
+    #[sqlx(flatten)]
+    pub user: User,
+    pub membership_role: MembershipRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberDTO {
+    pub user: GetUserDTO,
+    pub role: MembershipRole,
+    pub joined_at: DateTime<Utc>,
+}
+
+impl TryFrom<MemberRow> for MemberDTO {
+    type Error = AppError;
+
+    fn try_from(value: MemberRow) -> Result<Self, Self::Error> {
+        Ok(MemberDTO {
+            user: value.user.try_into()?,
+            role: value.membership_role,
+            joined_at: value.joined_at,
+        })
+    }
+}
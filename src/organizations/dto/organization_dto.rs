@@ -0,0 +1,22 @@
+use crate::organizations::entity::MembershipRole;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateOrganizationDTO {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+}
+
+/// An organization the caller belongs to, with the caller's role in it.
+#[derive(Debug, FromRow, Serialize)]
+pub struct UserOrganizationDTO {
+    pub id: Uuid,
+    pub name: String,
+    pub role: MembershipRole,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
@@ -0,0 +1,44 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Type};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Type, PartialEq, Default)]
+#[sqlx(type_name = "membership_role")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MembershipRole {
+    OWNER,
+    ADMIN,
+    #[default]
+    MEMBER,
+}
+
+impl MembershipRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MembershipRole::OWNER => "owner",
+            MembershipRole::ADMIN => "admin",
+            MembershipRole::MEMBER => "member",
+        }
+    }
+
+    pub fn can_manage_members(&self) -> bool {
+        matches!(self, MembershipRole::OWNER | MembershipRole::ADMIN)
+    }
+}
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct Membership {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role: MembershipRole,
+    pub created_at: DateTime<Utc>,
+}
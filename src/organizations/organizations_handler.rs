@@ -0,0 +1,108 @@
+use crate::{
+    middlewares::middleware_auth::JwtAuthMiddleware,
+    organizations::{
+        dto::{CreateOrganizationDTO, UpdateMemberDTO},
+        organizations_service,
+    },
+    server::AppState,
+    utils::{errors::AppError, query_paginaton::QueryPagination},
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_qs::actix::QsQuery;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub fn configure(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/organizations")
+            .wrap(JwtAuthMiddleware::new(app_state))
+            .service(web::resource("/{id}/members").route(web::get().to(find_members)))
+            .service(
+                web::resource("/{id}/members/{user_id}")
+                    .route(web::put().to(update_member))
+                    .route(web::delete().to(remove_member)),
+            )
+            .service(web::resource("/{id}").route(web::get().to(find)))
+            .service(
+                web::resource("")
+                    .route(web::get().to(find_all))
+                    .route(web::post().to(create)),
+            ),
+    );
+}
+
+async fn create(
+    pool: web::Data<PgPool>,
+    payload: web::Json<CreateOrganizationDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match organizations_service::create(&pool, payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_all(pool: web::Data<PgPool>, req: HttpRequest) -> Result<HttpResponse, AppError> {
+    match organizations_service::find_all(&pool, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match organizations_service::find(&pool, id.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_members(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    query_pagination: QsQuery<QueryPagination>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match organizations_service::find_members(
+        &pool,
+        id.into_inner(),
+        query_pagination.into_inner(),
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn update_member(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    payload: web::Json<UpdateMemberDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, user_id) = path.into_inner();
+
+    match organizations_service::update_member(&pool, id, user_id, payload.into_inner(), &req).await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn remove_member(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, user_id) = path.into_inner();
+
+    match organizations_service::remove_member(&pool, id, user_id, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,357 @@
+use crate::{
+    organizations::{
+        dto::{member_dto::MemberRow, MemberDTO, UserOrganizationDTO},
+        entity::{Membership, MembershipRole, Organization},
+    },
+    utils::{errors::AppError, query_paginaton::ResultWithPagination},
+};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+pub async fn create_organization(
+    conn: &mut PgConnection,
+    name: &str,
+) -> Result<Organization, AppError> {
+    let result = sqlx::query_as::<_, Organization>(
+        r#"--sql
+        INSERT INTO
+            organizations (name)
+        VALUES
+            ($1)
+        RETURNING
+            *
+        "#,
+    )
+    .bind(name)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn find_organization(pool: &PgPool, id: Uuid) -> Result<Organization, AppError> {
+    let result = sqlx::query_as::<_, Organization>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            organizations
+        WHERE
+            id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Organization with ID {} not found",
+        id
+    )))?;
+
+    Ok(result)
+}
+
+pub async fn find_user_organizations(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<UserOrganizationDTO>, AppError> {
+    let result = sqlx::query_as::<_, UserOrganizationDTO>(
+        r#"--sql
+        SELECT
+            organizations.id,
+            organizations.name,
+            memberships.role,
+            organizations.created_at,
+            organizations.updated_at
+        FROM
+            memberships
+            JOIN organizations ON organizations.id = memberships.organization_id
+        WHERE
+            memberships.user_id = $1
+        ORDER BY
+            memberships.created_at, organizations.id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn add_membership(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: MembershipRole,
+) -> Result<Membership, AppError> {
+    let result = sqlx::query_as::<_, Membership>(
+        r#"--sql
+        INSERT INTO
+            memberships (organization_id, user_id, role)
+        VALUES
+            ($1, $2, $3)
+        RETURNING
+            *
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .bind(role)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn add_memberships(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_ids: &[Uuid],
+    role: MembershipRole,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        INSERT INTO
+            memberships (organization_id, user_id, role)
+        SELECT
+            $1, user_id, $3
+        FROM
+            UNNEST($2::uuid[]) AS user_id
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_ids)
+    .bind(role)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn find_membership(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<Membership>, AppError> {
+    let result = sqlx::query_as::<_, Membership>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            memberships
+        WHERE
+            organization_id = $1
+            AND user_id = $2
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Whether the user is a member of any organization besides `organization_id`.
+pub async fn has_other_memberships(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, AppError> {
+    let result = sqlx::query_scalar::<_, bool>(
+        r#"--sql
+        SELECT
+            EXISTS (
+                SELECT
+                    1
+                FROM
+                    memberships
+                WHERE
+                    user_id = $1
+                    AND organization_id <> $2
+            )
+        "#,
+    )
+    .bind(user_id)
+    .bind(organization_id)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// The organization a login lands in when none is requested, the oldest membership.
+pub async fn find_default_membership(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<Membership>, AppError> {
+    let result = sqlx::query_as::<_, Membership>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            memberships
+        WHERE
+            user_id = $1
+        ORDER BY
+            created_at, organization_id
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn find_members(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    limit: i64,
+    offset: i64,
+    page: i64,
+) -> Result<ResultWithPagination<Vec<MemberDTO>>, AppError> {
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(*)
+        FROM
+            memberships
+        WHERE
+            organization_id = $1
+        "#,
+    )
+    .bind(organization_id)
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    let result: Vec<MemberDTO> = sqlx::query_as::<_, MemberRow>(
+        r#"--sql
+        SELECT
+            users.*,
+            memberships.role AS membership_role,
+            memberships.created_at AS joined_at
+        FROM
+            memberships
+            JOIN users ON users.id = memberships.user_id
+        WHERE
+            memberships.organization_id = $1
+        ORDER BY
+            memberships.created_at, users.id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(organization_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .into_iter()
+    .map(|row| row.try_into())
+    .collect::<Result<_, _>>()?;
+
+    Ok(ResultWithPagination::new(
+        limit,
+        page,
+        count,
+        result.len(),
+        result,
+    ))
+}
+
+/// Locks the owner memberships of the organization so concurrent demotions cannot
+/// leave it without an owner, returns how many there are.
+pub async fn lock_owners(conn: &mut PgConnection, organization_id: Uuid) -> Result<i64, AppError> {
+    let owners: Vec<Uuid> = sqlx::query_scalar(
+        r#"--sql
+        SELECT
+            user_id
+        FROM
+            memberships
+        WHERE
+            organization_id = $1
+            AND role = $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(organization_id)
+    .bind(MembershipRole::OWNER)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(owners.len() as i64)
+}
+
+pub async fn update_membership_role(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+    role: MembershipRole,
+) -> Result<Membership, AppError> {
+    let result = sqlx::query_as::<_, Membership>(
+        r#"--sql
+        UPDATE
+            memberships
+        SET
+            role = $1
+        WHERE
+            organization_id = $2
+            AND user_id = $3
+        RETURNING
+            *
+        "#,
+    )
+    .bind(role)
+    .bind(organization_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Member with ID {} not found",
+        user_id
+    )))?;
+
+    Ok(result)
+}
+
+pub async fn delete_membership(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    user_id: Uuid,
+) -> Result<Membership, AppError> {
+    let result = sqlx::query_as::<_, Membership>(
+        r#"--sql
+        DELETE FROM memberships
+        WHERE
+            organization_id = $1
+            AND user_id = $2
+        RETURNING
+            *
+        "#,
+    )
+    .bind(organization_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Member with ID {} not found",
+        user_id
+    )))?;
+
+    Ok(result)
+}
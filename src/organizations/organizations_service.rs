@@ -0,0 +1,247 @@
+use crate::{
+    audit::{audit_query, entity::AuditAction},
+    organizations::{
+        dto::{CreateOrganizationDTO, MemberDTO, UpdateMemberDTO, UserOrganizationDTO},
+        entity::{Membership, MembershipRole, Organization},
+        organizations_query,
+    },
+    utils::{
+        audit::{change, AuditContext},
+        auth::user_id_in_token,
+        errors::AppError,
+        query_paginaton::QueryPagination,
+        response_data::{ResponseData, ResponseDatas},
+        tenant::Tenant,
+    },
+};
+use actix_web::HttpRequest;
+use serde_json::{json, Value};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn create(
+    pool: &PgPool,
+    payload: CreateOrganizationDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<Organization>, AppError> {
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let user_id = user_id_in_token(req)?;
+
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+    let organization = organizations_query::create_organization(&mut tx, &payload.name).await?;
+    let membership = organizations_query::add_membership(
+        &mut tx,
+        organization.id,
+        user_id,
+        MembershipRole::OWNER,
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req).with_organization(organization.id),
+        AuditAction::UserMembershipAdded,
+        user_id,
+        membership_change(organization.id, Value::Null, json!(membership.role)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        organization,
+        "Data has been successfuly created.",
+    ))
+}
+
+pub async fn find_all(
+    pool: &PgPool,
+    req: &HttpRequest,
+) -> Result<ResponseData<Vec<UserOrganizationDTO>>, AppError> {
+    let user_id = user_id_in_token(req)?;
+
+    let result = organizations_query::find_user_organizations(pool, user_id).await?;
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+pub async fn find(
+    pool: &PgPool,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<Organization>, AppError> {
+    let mut conn = pool.acquire().await.map_err(AppError::DatabaseError)?;
+    caller_membership(&mut conn, id, req).await?;
+
+    let result = organizations_query::find_organization(pool, id).await?;
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+pub async fn find_members(
+    pool: &PgPool,
+    id: Uuid,
+    query_pagination: QueryPagination,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<MemberDTO>>, AppError> {
+    let mut tx = Tenant::Organization(id).begin(pool).await?;
+    caller_membership(&mut tx, id, req).await?;
+
+    if query_pagination.after.is_some() || query_pagination.before.is_some() {
+        return Err(AppError::BadRequest(
+            "Cursor pagination is not supported for members.".to_string(),
+        ));
+    }
+
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let result = organizations_query::find_members(&mut tx, id, limit, offset, page).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        result.data,
+    ))
+}
+
+pub async fn update_member(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+    payload: UpdateMemberDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<Membership>, AppError> {
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+
+    let caller = caller_membership(&mut tx, id, req).await?;
+    let owners = organizations_query::lock_owners(&mut tx, id).await?;
+    let target = organizations_query::find_membership(&mut tx, id, user_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Member with ID {} not found",
+            user_id
+        )))?;
+
+    if !caller.role.can_manage_members() {
+        return Err(AppError::Forbidden(
+            "Organization admin role is required to manage members".to_string(),
+        ));
+    }
+
+    // Only owners hand out or take away ownership.
+    let touches_owner =
+        target.role == MembershipRole::OWNER || payload.role == MembershipRole::OWNER;
+    if touches_owner && caller.role != MembershipRole::OWNER {
+        return Err(AppError::Forbidden(
+            "Organization owner role is required to change ownership".to_string(),
+        ));
+    }
+
+    if target.role == MembershipRole::OWNER && payload.role != MembershipRole::OWNER && owners <= 1
+    {
+        return Err(AppError::Conflict(
+            "An organization must keep at least one owner".to_string(),
+        ));
+    }
+
+    let membership =
+        organizations_query::update_membership_role(&mut tx, id, user_id, payload.role).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req).with_organization(id),
+        AuditAction::UserMembershipRoleChanged,
+        user_id,
+        membership_change(id, json!(target.role), json!(membership.role)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        membership,
+        "Data has been successfuly updated.",
+    ))
+}
+
+/// Removes a member, members may always remove themselves to leave the organization.
+pub async fn remove_member(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<Membership>, AppError> {
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+
+    let caller = caller_membership(&mut tx, id, req).await?;
+    let owners = organizations_query::lock_owners(&mut tx, id).await?;
+    let target = organizations_query::find_membership(&mut tx, id, user_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Member with ID {} not found",
+            user_id
+        )))?;
+
+    if caller.user_id != user_id {
+        if !caller.role.can_manage_members() {
+            return Err(AppError::Forbidden(
+                "Organization admin role is required to manage members".to_string(),
+            ));
+        }
+
+        if target.role == MembershipRole::OWNER && caller.role != MembershipRole::OWNER {
+            return Err(AppError::Forbidden(
+                "Organization owner role is required to remove an owner".to_string(),
+            ));
+        }
+    }
+
+    if target.role == MembershipRole::OWNER && owners <= 1 {
+        return Err(AppError::Conflict(
+            "An organization must keep at least one owner".to_string(),
+        ));
+    }
+
+    let membership = organizations_query::delete_membership(&mut tx, id, user_id).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req).with_organization(id),
+        AuditAction::UserMembershipRemoved,
+        user_id,
+        membership_change(id, json!(membership.role), Value::Null),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        membership,
+        "Data has been successfuly deleted.",
+    ))
+}
+
+/// Membership of the caller in the organization. Organizations the caller is not part
+/// of are reported as missing rather than forbidden, so their ids cannot be probed.
+async fn caller_membership(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    req: &HttpRequest,
+) -> Result<Membership, AppError> {
+    let user_id = user_id_in_token(req)?;
+
+    organizations_query::find_membership(conn, organization_id, user_id)
+        .await?
+        .ok_or(AppError::NotFound(format!(
+            "Organization with ID {} not found",
+            organization_id
+        )))
+}
+
+/// Membership changes are recorded on the user as `memberships.<organization id>`.
+pub fn membership_change(organization_id: Uuid, before: Value, after: Value) -> Value {
+    let field = format!("memberships.{}", organization_id);
+    json!({ &field: change(&field, before, after) })
+}
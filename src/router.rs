@@ -1,14 +1,35 @@
-use crate::{auth::auth_handler, server::AppState, users::users_handler};
+use crate::{
+    audit::audit_handler,
+    auth::auth_handler,
+    invitations::invitations_handler,
+    organizations::organizations_handler,
+    server::AppState,
+    users::{users_admin_handler, users_handler},
+    webhooks::webhooks_handler,
+};
 use actix_web::web;
 
 pub fn configure_v1(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
     cfg.service(
         web::scope("/api/V1")
             .configure(auth_handler::configure)
-            .configure(|cfg| users_handler::configure(cfg, app_state)),
+            .configure(|cfg| users_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| users_admin_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| organizations_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| invitations_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| webhooks_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| audit_handler::configure(cfg, app_state)),
     );
 }
 
 pub fn configure_v2(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
-    cfg.service(web::scope("/api/V2").configure(|cfg| users_handler::configure(cfg, app_state)));
+    cfg.service(
+        web::scope("/api/V2")
+            .configure(|cfg| users_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| users_admin_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| organizations_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| invitations_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| webhooks_handler::configure(cfg, app_state.clone()))
+            .configure(|cfg| audit_handler::configure(cfg, app_state)),
+    );
 }
@@ -1,9 +1,18 @@
 use crate::{
-    configs::{config_env, config_load::load_tls_config},
-    middlewares::middleware_logger,
+    configs::{
+        config_env,
+        config_load::{load_metadata_schema, load_tls_config},
+    },
+    middlewares::{middleware_logger, middleware_request_id::RequestIdMiddleware},
     router::{configure_v1, configure_v2},
-    utils::errors::{
-        json_error_handler, path_error_handler, qs_query_error_handler, query_error_handler,
+    users::entity::Preferences,
+    utils::{
+        errors::{
+            json_error_handler, path_error_handler, qs_query_error_handler, query_error_handler,
+        },
+        mailer::{LogMailer, Mailer},
+        metadata::MetadataSchema,
+        storage::{LocalStorage, Storage},
     },
 };
 use actix_cors::Cors;
@@ -19,6 +28,17 @@ pub struct AppState {
     pub refresh_key: Arc<String>,
     pub jwt_expiration_time: Arc<Duration>,
     pub jwt_refresh_expiration_time: Arc<Duration>,
+    pub invite_key: Arc<String>,
+    pub invite_expiration_time: Arc<Duration>,
+    pub email_change_expiration_time: Arc<Duration>,
+    pub download_key: Arc<String>,
+    pub download_expiration_time: Arc<Duration>,
+    pub app_base_url: Arc<String>,
+    pub storage: Arc<dyn Storage>,
+    pub avatar_max_size: Arc<usize>,
+    pub metadata_schema: Option<Arc<MetadataSchema>>,
+    pub preferences_defaults: Arc<Preferences>,
+    pub mailer: Arc<dyn Mailer>,
 }
 
 pub async fn start_server(
@@ -31,6 +51,17 @@ pub async fn start_server(
         refresh_key: Arc::new(config.jwt_refresh_key),
         jwt_expiration_time: Arc::new(config.jwt_expiration_time),
         jwt_refresh_expiration_time: Arc::new(config.jwt_refresh_expiration_time),
+        invite_key: Arc::new(config.jwt_invite_key),
+        invite_expiration_time: Arc::new(config.invite_expiration_time),
+        email_change_expiration_time: Arc::new(config.email_change_expiration_time),
+        download_key: Arc::new(config.jwt_download_key),
+        download_expiration_time: Arc::new(config.download_expiration_time),
+        app_base_url: Arc::new(config.app_base_url),
+        storage: Arc::new(LocalStorage::new(config.storage_path)),
+        avatar_max_size: Arc::new(config.avatar_max_size),
+        metadata_schema: load_metadata_schema(config.metadata_schema_path.as_deref()).map(Arc::new),
+        preferences_defaults: Arc::new(config.preferences_defaults),
+        mailer: Arc::new(LogMailer),
     });
 
     let server = HttpServer::new(move || {
@@ -46,6 +77,7 @@ pub async fn start_server(
             .app_data(web::JsonConfig::default().error_handler(json_error_handler))
             .app_data(web::PathConfig::default().error_handler(path_error_handler))
             .wrap(middleware_logger::LoggerMiddleware)
+            .wrap(RequestIdMiddleware)
             .wrap(cors_config)
             .app_data(web::Data::new(connection.clone()))
             .app_data(app_state.clone())
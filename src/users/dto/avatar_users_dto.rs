@@ -0,0 +1,8 @@
+use crate::utils::avatar::AvatarSize;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AvatarQuery {
+    #[serde(default)]
+    pub size: AvatarSize,
+}
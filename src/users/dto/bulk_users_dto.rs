@@ -0,0 +1,126 @@
+use crate::{
+    organizations::entity::MembershipRole,
+    users::{
+        dto::{status_users_dto::STATUS_REASON_MAX_LENGTH, UserFilter},
+        entity::UserStatus,
+    },
+    utils::errors::AppError,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+    /// Every item succeeds or the whole request is rolled back.
+    #[default]
+    Atomic,
+    /// Failed items are rolled back on their own, the rest is committed.
+    BestEffort,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum BulkAction {
+    SetStatus {
+        status: UserStatus,
+        reason: Option<String>,
+    },
+    SoftDelete,
+    Restore,
+    /// Platform admins only, the account is gone from every organization.
+    HardDelete,
+    /// Takes the user out of the active organization, the account itself stays.
+    RemoveMember,
+    /// Role in the active organization, the account's platform role is left alone.
+    AssignRole {
+        role: MembershipRole,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkOperation {
+    #[serde(flatten)]
+    pub action: BulkAction,
+    pub ids: Option<Vec<Uuid>>,
+    pub filter: Option<UserFilter>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkUsersDTO {
+    #[serde(default)]
+    pub mode: BulkMode,
+    pub operations: Vec<BulkOperation>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkItemStatus {
+    Succeeded,
+    Failed,
+    /// Succeeded but undone because another item of an atomic request failed.
+    RolledBack,
+    /// Not attempted because an earlier item of an atomic request failed.
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkItemResult {
+    pub operation: usize,
+    pub id: Uuid,
+    pub status: BulkItemStatus,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkUsersReport {
+    pub committed: bool,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+impl BulkOperation {
+    pub fn validate_target(&self, index: usize) -> Result<(), AppError> {
+        match (&self.ids, &self.filter) {
+            (Some(ids), None) if !ids.is_empty() => Ok(()),
+            (None, Some(_)) => Ok(()),
+            _ => Err(AppError::BadRequest(format!(
+                "Operation {} must target either a non-empty `ids` list or a `filter`.",
+                index
+            ))),
+        }
+    }
+
+    pub fn validate_reason(&self, index: usize) -> Result<(), AppError> {
+        match &self.action {
+            BulkAction::SetStatus {
+                reason: Some(reason),
+                ..
+            } if reason.is_empty() || reason.chars().count() as u64 > STATUS_REASON_MAX_LENGTH => {
+                Err(AppError::BadRequest(format!(
+                    "Operation {} must have a reason of 1 to {} characters.",
+                    index, STATUS_REASON_MAX_LENGTH
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl BulkUsersReport {
+    pub fn new(committed: bool, results: Vec<BulkItemResult>) -> Self {
+        BulkUsersReport {
+            committed,
+            succeeded: results
+                .iter()
+                .filter(|item| item.status == BulkItemStatus::Succeeded)
+                .count(),
+            failed: results
+                .iter()
+                .filter(|item| item.status == BulkItemStatus::Failed)
+                .count(),
+            results,
+        }
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    users::entity::{users_model::UserStatus, User},
+    users::entity::{
+        users_model::{UserRole, UserStatus},
+        User,
+    },
     utils::password::validate_password,
 };
 use chrono::Utc;
@@ -27,6 +30,14 @@ impl From<CreateUserDTO> for User {
             password: Some(value.password),
             email: Some(value.email),
             status: Some(UserStatus::ACTIVE),
+            role: Some(UserRole::USER),
+            display_name: None,
+            phone: None,
+            locale: None,
+            timezone: None,
+            bio: None,
+            avatar_updated_at: None,
+            metadata: None,
             created_at: Some(Utc::now()),
             updated_at: Some(Utc::now()),
             deleted_at: None,
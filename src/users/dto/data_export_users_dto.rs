@@ -0,0 +1,61 @@
+use crate::{
+    audit::entity::AuditEvent,
+    users::{
+        dto::GetUserDTO,
+        entity::{EmailChangeRequest, UserExport, UserExportFormat, UserExportStatus},
+    },
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct CreateDataExportDTO {
+    #[serde(default)]
+    pub format: UserExportFormat,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DownloadDataExportQuery {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DataExportDTO {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: UserExportStatus,
+    pub format: UserExportFormat,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Only set once the archive is ready, the link expires before the archive does.
+    pub download_url: Option<String>,
+}
+
+/// The `user.json` document of an export archive.
+#[derive(Debug, Serialize)]
+pub struct DataExportDocument {
+    pub generated_at: DateTime<Utc>,
+    pub profile: GetUserDTO,
+    pub email_change_requests: Vec<EmailChangeRequest>,
+    /// Audit events about the user or caused by the user.
+    pub audit_events: Vec<AuditEvent>,
+    /// Base64 encoded PNG in JSON exports, ZIP exports carry `avatar.png` instead.
+    pub avatar: Option<String>,
+}
+
+impl From<UserExport> for DataExportDTO {
+    fn from(export: UserExport) -> Self {
+        DataExportDTO {
+            id: export.id,
+            user_id: export.user_id,
+            status: export.status,
+            format: export.format,
+            created_at: export.created_at,
+            completed_at: export.completed_at,
+            expires_at: export.expires_at,
+            download_url: None,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RequestEmailChangeDTO {
+    #[validate(email, length(max = 255))]
+    pub email: String,
+}
@@ -0,0 +1,113 @@
+use crate::{
+    users::dto::{GetUserDTO, UserFilter},
+    utils::{errors::AppError, query_fields::Projectable},
+};
+use actix_web::web::Bytes;
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+    #[default]
+    Json,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ExportUsersQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub filter: UserFilter,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Turns exported rows into body chunks, one chunk per row so nothing but the
+/// row being written is held in memory.
+#[derive(Debug)]
+pub struct ExportEncoder {
+    format: ExportFormat,
+    rows: u64,
+}
+
+impl ExportEncoder {
+    pub fn new(format: ExportFormat) -> Self {
+        ExportEncoder { format, rows: 0 }
+    }
+
+    pub fn header(&self) -> Option<Bytes> {
+        match self.format {
+            ExportFormat::Csv => Some(Bytes::from(format!("{}\n", GetUserDTO::FIELDS.join(",")))),
+            ExportFormat::Ndjson => None,
+            ExportFormat::Json => Some(Bytes::from_static(b"[")),
+        }
+    }
+
+    pub fn row(&mut self, user: &GetUserDTO) -> Result<Bytes, AppError> {
+        let mut chunk: Vec<u8> = Vec::new();
+
+        match self.format {
+            ExportFormat::Csv => {
+                let value = serde_json::to_value(user)
+                    .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+                // Nested values (metadata) have no CSV shape, they are written as JSON text.
+                let record = GetUserDTO::FIELDS.iter().map(|field| match &value[*field] {
+                    Value::Null => String::new(),
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                });
+
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(&mut chunk);
+                writer
+                    .write_record(record)
+                    .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+                writer
+                    .flush()
+                    .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            }
+            ExportFormat::Ndjson => {
+                serde_json::to_writer(&mut chunk, user)
+                    .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+                chunk.push(b'\n');
+            }
+            ExportFormat::Json => {
+                if self.rows > 0 {
+                    chunk.push(b',');
+                }
+                serde_json::to_writer(&mut chunk, user)
+                    .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+            }
+        }
+
+        self.rows += 1;
+        Ok(Bytes::from(chunk))
+    }
+
+    pub fn footer(&self) -> Option<Bytes> {
+        match self.format {
+            ExportFormat::Json => Some(Bytes::from_static(b"]")),
+            _ => None,
+        }
+    }
+}
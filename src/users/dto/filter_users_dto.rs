@@ -0,0 +1,69 @@
+use crate::{
+    users::entity::UserStatus,
+    utils::{
+        metadata::metadata_filter_value,
+        query_filter::{DateRangeFilter, TextFilter},
+    },
+};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use sqlx::{Postgres, QueryBuilder};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct UserFilter {
+    pub name: Option<TextFilter>,
+    pub email: Option<TextFilter>,
+    pub status: Option<UserStatus>,
+    pub created_at: Option<DateRangeFilter>,
+    pub updated_at: Option<DateRangeFilter>,
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct UserFilterQuery {
+    #[serde(default)]
+    pub filter: UserFilter,
+}
+
+impl UserFilter {
+    /// Appends the `WHERE` clause for this filter, listing only active users unless
+    /// another status is requested.
+    pub fn push_where(&self, query_builder: &mut QueryBuilder<'_, Postgres>) {
+        query_builder
+            .push(" WHERE status = ")
+            .push_bind(self.status.clone().unwrap_or(UserStatus::ACTIVE));
+
+        if let Some(name) = &self.name {
+            name.push_conditions(query_builder, "name");
+        }
+
+        if let Some(email) = &self.email {
+            email.push_conditions(query_builder, "email");
+        }
+
+        if let Some(created_at) = &self.created_at {
+            created_at.push_conditions(query_builder, "created_at");
+        }
+
+        if let Some(updated_at) = &self.updated_at {
+            updated_at.push_conditions(query_builder, "updated_at");
+        }
+
+        // Containment keeps the query on the GIN (jsonb_path_ops) index.
+        if let Some(metadata) = self
+            .metadata
+            .as_ref()
+            .filter(|metadata| !metadata.is_empty())
+        {
+            let document: Map<String, Value> = metadata
+                .iter()
+                .map(|(key, value)| (key.clone(), metadata_filter_value(value)))
+                .collect();
+
+            query_builder
+                .push(" AND metadata @> ")
+                .push_bind(Value::Object(document));
+        }
+    }
+}
@@ -1,6 +1,18 @@
-use crate::users::entity::{users_model::UserStatus, User};
+use crate::{
+    users::entity::{
+        users_model::{UserRole, UserStatus},
+        User,
+    },
+    utils::{
+        errors::AppError,
+        query_cursor::{CursorValue, Cursorable},
+        query_fields::{Fieldset, Projectable},
+        query_sort::Sortable,
+    },
+};
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use serde_json::Value;
 use uuid::Uuid;
 
 #[derive(Debug, Serialize, Clone)]
@@ -9,21 +21,99 @@ pub struct GetUserDTO {
     pub name: String,
     pub email: String,
     pub status: UserStatus,
+    pub role: UserRole,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_updated_at: Option<DateTime<Utc>>,
+    pub metadata: Value,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
 }
 
-impl From<User> for GetUserDTO {
-    fn from(value: User) -> Self {
-        GetUserDTO {
-            id: value.id.unwrap(),
-            name: value.name.unwrap(),
-            email: value.email.unwrap(),
-            status: value.status.unwrap(),
-            created_at: value.created_at.unwrap(),
-            updated_at: value.updated_at.unwrap(),
+impl GetUserDTO {
+    /// Builds the DTO from a row selected with `fieldset`. Columns the fieldset left out
+    /// get placeholder values that `Fieldset::project` strips again, a selected column
+    /// missing from the row is an error.
+    pub fn from_row(value: User, fieldset: &Fieldset) -> Result<Self, AppError> {
+        Ok(GetUserDTO {
+            id: column(value.id, "id", fieldset)?,
+            name: column(value.name, "name", fieldset)?,
+            email: column(value.email, "email", fieldset)?,
+            status: column(value.status, "status", fieldset)?,
+            role: column(value.role, "role", fieldset)?,
+            display_name: value.display_name,
+            phone: value.phone,
+            locale: value.locale,
+            timezone: value.timezone,
+            bio: value.bio,
+            avatar_updated_at: value.avatar_updated_at,
+            metadata: column(value.metadata, "metadata", fieldset)?,
+            created_at: column(value.created_at, "created_at", fieldset)?,
+            updated_at: column(value.updated_at, "updated_at", fieldset)?,
             deleted_at: value.deleted_at,
+        })
+    }
+}
+
+impl TryFrom<User> for GetUserDTO {
+    type Error = AppError;
+
+    fn try_from(value: User) -> Result<Self, Self::Error> {
+        GetUserDTO::from_row(value, &Fieldset::all())
+    }
+}
+
+fn column<T: Default>(value: Option<T>, name: &str, fieldset: &Fieldset) -> Result<T, AppError> {
+    match value {
+        Some(value) => Ok(value),
+        None if !fieldset.contains(name) => Ok(T::default()),
+        None => Err(AppError::InternalServerError(format!(
+            "User row is missing the '{}' column",
+            name
+        ))),
+    }
+}
+
+impl Projectable for GetUserDTO {
+    const FIELDS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "email",
+        "status",
+        "role",
+        "display_name",
+        "phone",
+        "locale",
+        "timezone",
+        "bio",
+        "avatar_updated_at",
+        "metadata",
+        "created_at",
+        "updated_at",
+        "deleted_at",
+    ];
+    const INCLUDES: &'static [&'static str] = &[];
+}
+
+impl Sortable for GetUserDTO {
+    const SORTABLE_COLUMNS: &'static [&'static str] =
+        &["id", "name", "email", "created_at", "updated_at"];
+    const DEFAULT_SORT: &'static str = "-created_at";
+}
+
+impl Cursorable for GetUserDTO {
+    fn cursor_value(&self, column: &str) -> Option<CursorValue> {
+        match column {
+            "id" => Some(CursorValue::Uuid(self.id)),
+            "name" => Some(CursorValue::Text(self.name.clone())),
+            "email" => Some(CursorValue::Text(self.email.clone())),
+            "created_at" => Some(CursorValue::Timestamp(self.created_at)),
+            "updated_at" => Some(CursorValue::Timestamp(self.updated_at)),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,46 @@
+use crate::{
+    users::{dto::GetUserDTO, entity::User},
+    utils::errors::AppError,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AsOfQuery {
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, FromRow)]
+pub struct UserVersionRow {
+    pub version: i64,
+    pub operation: String,
+    pub valid_from: DateTime<Utc>,
+    pub valid_to: DateTime<Utc>,
+    #[sqlx(flatten)]
+    pub user: User,
+}
+
+/// A past version of the user record, current during `[valid_from, valid_to)`.
+#[derive(Debug, Serialize)]
+pub struct UserVersionDTO {
+    pub version: i64,
+    pub operation: String,
+    pub valid_from: DateTime<Utc>,
+    pub valid_to: DateTime<Utc>,
+    pub record: GetUserDTO,
+}
+
+impl TryFrom<UserVersionRow> for UserVersionDTO {
+    type Error = AppError;
+
+    fn try_from(value: UserVersionRow) -> Result<Self, Self::Error> {
+        Ok(UserVersionDTO {
+            version: value.version,
+            operation: value.operation,
+            valid_from: value.valid_from,
+            valid_to: value.valid_to,
+            record: value.user.try_into()?,
+        })
+    }
+}
@@ -0,0 +1,167 @@
+use crate::{
+    users::dto::CreateUserDTO,
+    utils::errors::{format_validation_errors, AppError},
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+pub const CSV_CONTENT_TYPE: &str = "text/csv";
+pub const NDJSON_CONTENT_TYPES: &[&str] = &["application/x-ndjson", "application/ndjson"];
+
+#[derive(Debug, Deserialize)]
+pub struct ImportUsersQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportUserRow {
+    pub name: String,
+    pub email: String,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+/// Upload row keyed by its line number, rows that cannot be decoded carry the parse error.
+pub type ParsedImportRow = (u64, Result<ImportUserRow, String>);
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    Created,
+    Invited,
+    Duplicate,
+    Invalid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportRowResult {
+    pub line: u64,
+    pub email: Option<String>,
+    pub status: ImportRowStatus,
+    pub id: Option<Uuid>,
+    pub invite_link: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ImportUsersReport {
+    pub dry_run: bool,
+    pub total: usize,
+    pub created: usize,
+    pub invited: usize,
+    pub duplicates: usize,
+    pub invalid: usize,
+    pub rows: Vec<ImportRowResult>,
+}
+
+impl ImportUserRow {
+    /// Applies the `CreateUserDTO` rules, the password is optional for imports since
+    /// accounts without one receive an invite link.
+    pub fn validate_row(&self) -> Result<(), String> {
+        let dto = CreateUserDTO {
+            name: self.name.clone(),
+            email: self.email.clone(),
+            password: self.password.clone().unwrap_or_default(),
+        };
+
+        match dto.validate() {
+            Ok(()) => Ok(()),
+            Err(mut errors) => {
+                if self.password.is_none() {
+                    errors.errors_mut().remove("password");
+                }
+
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(format_validation_errors(&errors))
+                }
+            }
+        }
+    }
+}
+
+impl ImportUsersReport {
+    pub fn new(dry_run: bool, rows: Vec<ImportRowResult>) -> Self {
+        let count =
+            |status: ImportRowStatus| rows.iter().filter(|row| row.status == status).count();
+
+        ImportUsersReport {
+            dry_run,
+            total: rows.len(),
+            created: count(ImportRowStatus::Created),
+            invited: count(ImportRowStatus::Invited),
+            duplicates: count(ImportRowStatus::Duplicate),
+            invalid: count(ImportRowStatus::Invalid),
+            rows,
+        }
+    }
+}
+
+/// Parses a CSV (with a `name,email,password` header) or NDJSON upload.
+pub fn parse_import(content_type: &str, body: &[u8]) -> Result<Vec<ParsedImportRow>, AppError> {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    if mime == CSV_CONTENT_TYPE {
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(body);
+
+        let headers = reader
+            .headers()
+            .map_err(|e| AppError::BadRequest(format!("Invalid CSV header: {}", e)))?
+            .clone();
+
+        let rows = reader
+            .records()
+            .enumerate()
+            .map(|(i, record)| match record {
+                Ok(record) => {
+                    let line = record.position().map(|p| p.line()).unwrap_or(i as u64 + 2);
+                    let row = record
+                        .deserialize::<ImportUserRow>(Some(&headers))
+                        .map(|mut row| {
+                            row.password = row.password.filter(|password| !password.is_empty());
+                            row
+                        })
+                        .map_err(|e| e.to_string());
+                    (line, row)
+                }
+                Err(e) => (i as u64 + 2, Err(e.to_string())),
+            })
+            .collect();
+
+        return Ok(rows);
+    }
+
+    if NDJSON_CONTENT_TYPES.contains(&mime.as_str()) {
+        let body = std::str::from_utf8(body)
+            .map_err(|_| AppError::BadRequest("NDJSON upload must be UTF-8".to_string()))?;
+
+        let rows = body
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                let row = serde_json::from_str::<ImportUserRow>(line).map_err(|e| e.to_string());
+                (i as u64 + 1, row)
+            })
+            .collect();
+
+        return Ok(rows);
+    }
+
+    Err(AppError::UnsupportedMediaType(format!(
+        "Import requires Content-Type {} or {}",
+        CSV_CONTENT_TYPE,
+        NDJSON_CONTENT_TYPES.join(", ")
+    )))
+}
@@ -0,0 +1,8 @@
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Serialize)]
+pub struct MetadataEntryDTO {
+    pub key: String,
+    pub value: Value,
+}
@@ -0,0 +1,114 @@
+use crate::{
+    users::dto::GetUserDTO,
+    utils::{
+        errors::AppError,
+        profile::{validate_locale, validate_phone, validate_timezone},
+    },
+};
+use json_patch::{Patch, PatchErrorKind};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use validator::Validate;
+
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+/// Writable representation of a user that patches are applied against. Email is
+/// left out, it changes through the confirmed email change flow, and so is the status,
+/// which only admins change.
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct PatchUserDocument {
+    #[validate(length(min = 3, max = 255))]
+    pub name: String,
+
+    #[serde(default)]
+    #[validate(length(min = 1, max = 100))]
+    pub display_name: Option<String>,
+
+    #[serde(default)]
+    #[validate(custom(function = "validate_phone"))]
+    pub phone: Option<String>,
+
+    #[serde(default)]
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: Option<String>,
+
+    #[serde(default)]
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
+
+    #[serde(default)]
+    #[validate(length(max = 500))]
+    pub bio: Option<String>,
+}
+
+impl From<GetUserDTO> for PatchUserDocument {
+    fn from(value: GetUserDTO) -> Self {
+        PatchUserDocument {
+            name: value.name,
+            display_name: value.display_name,
+            phone: value.phone,
+            locale: value.locale,
+            timezone: value.timezone,
+            bio: value.bio,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum UserPatch {
+    /// RFC 7396 JSON Merge Patch.
+    Merge(Value),
+    /// RFC 6902 JSON Patch.
+    Json(Patch),
+}
+
+impl UserPatch {
+    pub fn parse(content_type: &str, body: &[u8]) -> Result<Self, AppError> {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+
+        let invalid = |e: serde_json::Error| AppError::BadRequest(format!("Invalid patch: {}", e));
+
+        match mime.as_str() {
+            MERGE_PATCH_CONTENT_TYPE => Ok(UserPatch::Merge(
+                serde_json::from_slice(body).map_err(invalid)?,
+            )),
+            JSON_PATCH_CONTENT_TYPE => Ok(UserPatch::Json(
+                serde_json::from_slice(body).map_err(invalid)?,
+            )),
+            _ => Err(AppError::UnsupportedMediaType(format!(
+                "PATCH requires Content-Type {} or {}",
+                MERGE_PATCH_CONTENT_TYPE, JSON_PATCH_CONTENT_TYPE
+            ))),
+        }
+    }
+
+    /// Applies the patch to `current` and returns the resulting document, validated.
+    pub fn apply(&self, current: PatchUserDocument) -> Result<PatchUserDocument, AppError> {
+        let mut document = serde_json::to_value(current)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        match self {
+            UserPatch::Merge(patch) => json_patch::merge(&mut document, patch),
+            UserPatch::Json(patch) => {
+                json_patch::patch(&mut document, patch).map_err(|e| match e.kind {
+                    PatchErrorKind::TestFailed => AppError::Conflict(e.to_string()),
+                    _ => AppError::BadRequest(e.to_string()),
+                })?
+            }
+        }
+
+        let document: PatchUserDocument = serde_json::from_value(document)
+            .map_err(|e| AppError::BadRequest(format!("Invalid patched document: {}", e)))?;
+
+        document.validate().map_err(AppError::ValidationError)?;
+
+        Ok(document)
+    }
+}
@@ -0,0 +1,22 @@
+use crate::users::entity::Preferences;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::prelude::FromRow;
+
+/// Stored preferences of an existing user, the columns are empty while the user
+/// has never saved any.
+#[derive(Debug, FromRow)]
+pub struct UserPreferencesRow {
+    pub schema_version: Option<i32>,
+    pub document: Option<Value>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserPreferencesDTO {
+    pub schema_version: i32,
+    #[serde(flatten)]
+    pub preferences: Preferences,
+    pub updated_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,41 @@
+use crate::{
+    users::{dto::GetUserDTO, entity::User},
+    utils::errors::AppError,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct SearchUserQuery {
+    #[validate(length(min = 2, max = 255))]
+    pub q: String,
+}
+
+#[derive(Debug, FromRow)]
+pub struct SearchUserRow {
+    #[sqlx(flatten)]
+    pub user: User,
+    pub rank: f32,
+    pub highlight: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchUserDTO {
+    #[serde(flatten)]
+    pub user: GetUserDTO,
+    pub rank: f32,
+    pub highlight: String,
+}
+
+impl TryFrom<SearchUserRow> for SearchUserDTO {
+    type Error = AppError;
+
+    fn try_from(value: SearchUserRow) -> Result<Self, Self::Error> {
+        Ok(SearchUserDTO {
+            user: value.user.try_into()?,
+            rank: value.rank,
+            highlight: value.highlight,
+        })
+    }
+}
@@ -0,0 +1,15 @@
+use crate::users::entity::UserStatus;
+use serde::Deserialize;
+use validator::Validate;
+
+pub const STATUS_REASON_MAX_LENGTH: u64 = 500;
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateUserStatusDTO {
+    pub status: UserStatus,
+
+    /// Kept with the transition, e.g. why an account was suspended.
+    #[validate(length(min = 1, max = STATUS_REASON_MAX_LENGTH))]
+    pub reason: Option<String>,
+}
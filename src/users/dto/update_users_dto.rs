@@ -1,24 +1,46 @@
 use crate::{
-    users::entity::{User, UserStatus},
-    utils::password::validate_password,
+    users::entity::User,
+    utils::{
+        password::validate_password,
+        profile::{validate_locale, validate_phone, validate_timezone},
+    },
 };
 use chrono::Utc;
-use serde::Deserialize;
+use serde::{de::IgnoredAny, Deserialize};
 use validator::Validate;
 
+/// Email is not updatable here, changes go through `POST /users/{id}/email-change`
+/// so both addresses can confirm. Status changes go through the admin status endpoint.
 #[derive(Debug, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateUserDTO {
+    /// Only read to point clients that still send it to the email change flow.
+    #[serde(default)]
+    pub email: Option<IgnoredAny>,
+
     #[validate(length(min = 3, max = 255))]
     pub name: Option<String>,
 
-    #[validate(email)]
-    pub email: Option<String>,
+    #[validate(length(min = 1, max = 100))]
+    pub display_name: Option<String>,
+
+    #[validate(custom(function = "validate_phone"))]
+    pub phone: Option<String>,
+
+    #[validate(custom(function = "validate_locale"))]
+    pub locale: Option<String>,
+
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: Option<String>,
 
-    pub status: Option<UserStatus>,
+    #[validate(length(max = 500))]
+    pub bio: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Validate)]
 pub struct UpdateUserPasswordDto {
+    pub current_password: String,
+
     #[validate(custom(function = "validate_password"))]
     pub password: String,
 }
@@ -28,24 +50,17 @@ impl From<UpdateUserDTO> for User {
         User {
             id: None,
             name: value.name,
-            email: value.email,
-            password: None,
-            status: value.status,
-            created_at: None,
-            updated_at: Some(Utc::now()),
-            deleted_at: None,
-        }
-    }
-}
-
-impl From<UpdateUserPasswordDto> for User {
-    fn from(value: UpdateUserPasswordDto) -> Self {
-        User {
-            id: None,
-            name: None,
             email: None,
-            password: Some(value.password),
+            password: None,
             status: None,
+            role: None,
+            display_name: value.display_name,
+            phone: value.phone,
+            locale: value.locale,
+            timezone: value.timezone,
+            bio: value.bio,
+            avatar_updated_at: None,
+            metadata: None,
             created_at: None,
             updated_at: Some(Utc::now()),
             deleted_at: None,
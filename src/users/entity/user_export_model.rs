@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{prelude::FromRow, Type};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Type, PartialEq)]
+#[sqlx(type_name = "user_export_status")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum UserExportStatus {
+    PENDING,
+    PROCESSING,
+    COMPLETED,
+    FAILED,
+    EXPIRED,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Type, PartialEq, Default)]
+#[sqlx(type_name = "user_export_format", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserExportFormat {
+    #[default]
+    Json,
+    Zip,
+}
+
+#[derive(Debug, FromRow, Clone)]
+pub struct UserExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: UserExportStatus,
+    pub format: UserExportFormat,
+    pub storage_key: Option<String>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl UserExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            UserExportFormat::Json => "application/json",
+            UserExportFormat::Zip => "application/zip",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            UserExportFormat::Json => "json",
+            UserExportFormat::Zip => "zip",
+        }
+    }
+}
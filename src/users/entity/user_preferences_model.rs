@@ -0,0 +1,58 @@
+use crate::utils::profile::{validate_locale, validate_timezone};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use validator::Validate;
+
+/// Effective preferences of a user, the stored document merged over the defaults from
+/// the configuration.
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Preferences {
+    #[validate(custom(function = "validate_locale"))]
+    pub language: String,
+
+    #[validate(custom(function = "validate_timezone"))]
+    pub timezone: String,
+
+    pub date_format: DateFormat,
+
+    pub notifications: NotificationPreferences,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum DateFormat {
+    #[serde(rename = "YYYY-MM-DD")]
+    YearMonthDay,
+    #[serde(rename = "DD/MM/YYYY")]
+    DayMonthYear,
+    #[serde(rename = "MM/DD/YYYY")]
+    MonthDayYear,
+    #[serde(rename = "DD.MM.YYYY")]
+    DayMonthYearDotted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotificationPreferences {
+    /// Channels notifications may be delivered on.
+    pub channels: BTreeSet<NotificationChannel>,
+
+    /// Optional topics the user agreed to receive.
+    pub opt_ins: BTreeSet<NotificationTopic>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationChannel {
+    Email,
+    Sms,
+    Push,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationTopic {
+    SecurityAlerts,
+    ProductUpdates,
+    Newsletter,
+}
@@ -0,0 +1,16 @@
+use crate::users::entity::UserStatus;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::prelude::FromRow;
+use uuid::Uuid;
+
+#[derive(Debug, FromRow, Serialize)]
+pub struct UserStatusTransition {
+    pub id: i64,
+    pub user_id: Uuid,
+    pub from_status: UserStatus,
+    pub to_status: UserStatus,
+    pub reason: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
@@ -1,23 +1,68 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::{prelude::FromRow, Type};
 use uuid::Uuid;
 
-#[derive(Debug, Deserialize, Serialize, Clone, Type, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, Clone, Type, PartialEq, Default)]
 #[sqlx(type_name = "user_status")]
 #[serde(rename_all = "UPPERCASE")]
 pub enum UserStatus {
+    #[default]
     ACTIVE,
     DELETED,
+    SUSPENDED,
+    LOCKED,
+    PENDING,
 }
 
-#[derive(Debug, FromRow)]
+impl UserStatus {
+    /// Statuses the account may move to from this one. Accounts are only PENDING when
+    /// created without a password and leave it by accepting their invitation.
+    pub fn transitions(&self) -> &'static [UserStatus] {
+        match self {
+            UserStatus::PENDING => &[UserStatus::ACTIVE, UserStatus::DELETED],
+            UserStatus::ACTIVE => &[
+                UserStatus::SUSPENDED,
+                UserStatus::LOCKED,
+                UserStatus::DELETED,
+            ],
+            UserStatus::SUSPENDED => &[UserStatus::ACTIVE, UserStatus::DELETED],
+            UserStatus::LOCKED => &[UserStatus::ACTIVE, UserStatus::DELETED],
+            UserStatus::DELETED => &[UserStatus::ACTIVE],
+        }
+    }
+
+    pub fn can_transition_to(&self, status: &UserStatus) -> bool {
+        self.transitions().contains(status)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Type, PartialEq, Default)]
+#[sqlx(type_name = "user_role")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum UserRole {
+    #[default]
+    USER,
+    ADMIN,
+}
+
+#[derive(Debug, FromRow, Default)]
+#[sqlx(default)]
 pub struct User {
     pub id: Option<Uuid>,
     pub name: Option<String>,
     pub email: Option<String>,
     pub password: Option<String>,
     pub status: Option<UserStatus>,
+    pub role: Option<UserRole>,
+    pub display_name: Option<String>,
+    pub phone: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub bio: Option<String>,
+    pub avatar_updated_at: Option<DateTime<Utc>>,
+    pub metadata: Option<Value>,
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
     pub deleted_at: Option<DateTime<Utc>>,
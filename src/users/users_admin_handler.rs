@@ -0,0 +1,148 @@
+use crate::{
+    middlewares::middleware_auth::JwtAuthMiddleware,
+    server::AppState,
+    users::{
+        dto::{
+            bulk_users_dto::BulkUsersDTO, import_users_dto::ImportUsersQuery, ExportUsersQuery,
+            UpdateUserStatusDTO,
+        },
+        users_admin_service,
+    },
+    utils::{errors::AppError, query_paginaton::QueryPagination},
+};
+use actix_web::{
+    http::header::{self, ContentDisposition, DispositionParam, DispositionType},
+    web, HttpRequest, HttpResponse,
+};
+use serde_qs::actix::QsQuery;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+const IMPORT_MAX_PAYLOAD: usize = 10 * 1024 * 1024;
+
+pub fn configure(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/admin/users")
+            .wrap(JwtAuthMiddleware::new(app_state))
+            .service(
+                web::resource("/import")
+                    .app_data(web::PayloadConfig::new(IMPORT_MAX_PAYLOAD))
+                    .route(web::post().to(import)),
+            )
+            .service(web::resource("/export").route(web::get().to(export)))
+            .service(web::resource("/bulk").route(web::post().to(bulk)))
+            .service(web::resource("/{id}/status").route(web::put().to(set_status)))
+            .service(
+                web::resource("/{id}/status-transitions")
+                    .route(web::get().to(find_status_transitions)),
+            )
+            .service(web::resource("/{id}/anonymize").route(web::post().to(anonymize))),
+    );
+}
+
+async fn import(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    query: web::Query<ImportUsersQuery>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    match users_admin_service::import(
+        &pool,
+        &app_state,
+        content_type,
+        &body,
+        query.into_inner(),
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn export(
+    pool: web::Data<PgPool>,
+    query: QsQuery<ExportUsersQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let query = query.into_inner();
+    let format = query.format;
+
+    match users_admin_service::export(&pool, query, &req) {
+        Ok(body) => Ok(HttpResponse::Ok()
+            .content_type(format.content_type())
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!(
+                    "users.{}",
+                    format.extension()
+                ))],
+            })
+            .streaming(body)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn bulk(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    payload: web::Json<BulkUsersDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_admin_service::bulk(&pool, &app_state, payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn set_status(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    payload: web::Json<UpdateUserStatusDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_admin_service::set_status(&pool, id.into_inner(), payload.into_inner(), &req).await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_status_transitions(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    query_pagination: QsQuery<QueryPagination>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_admin_service::find_status_transitions(
+        &pool,
+        id.into_inner(),
+        query_pagination.into_inner(),
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn anonymize(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_admin_service::anonymize(&pool, &app_state, id.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
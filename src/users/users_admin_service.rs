@@ -0,0 +1,820 @@
+use crate::{
+    audit::{audit_query, entity::AuditAction},
+    auth::auth_service::generate_invite_link,
+    events::{entity::DomainEvent, events_query},
+    organizations::{
+        entity::MembershipRole, organizations_query, organizations_service::membership_change,
+    },
+    server::AppState,
+    users::{
+        dto::{
+            bulk_users_dto::{
+                BulkAction, BulkItemResult, BulkItemStatus, BulkMode, BulkUsersDTO, BulkUsersReport,
+            },
+            export_users_dto::ExportEncoder,
+            import_users_dto::{
+                parse_import, ImportRowResult, ImportRowStatus, ImportUserRow, ImportUsersQuery,
+                ImportUsersReport,
+            },
+            ExportUsersQuery, GetUserDTO, UpdateUserStatusDTO,
+        },
+        entity::{User, UserRole, UserStatus, UserStatusTransition},
+        users_query, users_service,
+    },
+    utils::{
+        audit::{diff, redact_before, AuditContext},
+        auth::{
+            organization_id_in_token, user_id_in_token, validate_admin_in_token,
+            validate_org_admin_in_token,
+        },
+        errors::AppError,
+        password::{hash_password, UNUSABLE_PASSWORD},
+        query_paginaton::QueryPagination,
+        response_data::{ResponseData, ResponseDatas},
+        tenant::Tenant,
+    },
+};
+use actix_web::{web, web::Bytes, HttpRequest};
+use chrono::Utc;
+use futures::{channel::mpsc, stream, SinkExt, Stream, StreamExt, TryStreamExt};
+use serde_json::{json, Value};
+use sqlx::{Acquire, PgConnection, PgPool};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+use validator::Validate;
+
+pub const IMPORT_MAX_ROWS: usize = 10_000;
+const IMPORT_BATCH_SIZE: usize = 500;
+const IMPORT_HASH_CHUNK_SIZE: usize = 50;
+const IMPORT_HASH_CONCURRENCY: usize = 4;
+pub const BULK_MAX_ITEMS: usize = 10_000;
+// Rows buffered between the database cursor and a slow client.
+const EXPORT_BUFFER_SIZE: usize = 256;
+
+pub async fn import(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    content_type: &str,
+    body: &[u8],
+    query: ImportUsersQuery,
+    req: &HttpRequest,
+) -> Result<ResponseData<ImportUsersReport>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+
+    let parsed = parse_import(content_type, body)?;
+
+    if parsed.len() > IMPORT_MAX_ROWS {
+        return Err(AppError::BadRequest(format!(
+            "Import is limited to {} rows per upload.",
+            IMPORT_MAX_ROWS
+        )));
+    }
+
+    let mut results: Vec<ImportRowResult> = Vec::with_capacity(parsed.len());
+    let mut candidates: Vec<(usize, ImportUserRow)> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for (line, row) in parsed {
+        let mut result = ImportRowResult {
+            line,
+            email: None,
+            status: ImportRowStatus::Invalid,
+            id: None,
+            invite_link: None,
+            error: None,
+        };
+
+        match row {
+            Err(err) => result.error = Some(err),
+            Ok(row) => {
+                result.email = Some(row.email.clone());
+
+                if let Err(err) = row.validate_row() {
+                    result.error = Some(err);
+                } else if !seen.insert(row.email.clone()) {
+                    result.status = ImportRowStatus::Duplicate;
+                    result.error = Some("Email appears more than once in the upload.".to_string());
+                } else {
+                    candidates.push((results.len(), row));
+                }
+            }
+        }
+
+        results.push(result);
+    }
+
+    let emails: Vec<String> = candidates
+        .iter()
+        .map(|(_, row)| row.email.clone())
+        .collect();
+    // Emails are unique across organizations, so are the imported accounts until they
+    // are added to this one.
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let existing: HashSet<String> = users_query::find_existing_emails(&mut tx, &emails)
+        .await?
+        .into_iter()
+        .collect();
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    candidates.retain(|(index, row)| {
+        if existing.contains(&row.email) {
+            results[*index].status = ImportRowStatus::Duplicate;
+            results[*index].error = Some("Email already exists.".to_string());
+            false
+        } else {
+            true
+        }
+    });
+
+    if query.dry_run {
+        for (index, row) in &candidates {
+            results[*index].status = match row.password {
+                Some(_) => ImportRowStatus::Created,
+                None => ImportRowStatus::Invited,
+            };
+        }
+
+        return Ok(ResponseData::new(
+            ImportUsersReport::new(true, results),
+            "Data has been successfuly validated.",
+        ));
+    }
+
+    // Hashing is CPU bound, keep it off the async workers. Hashed in chunks on a few
+    // blocking threads, so a large upload neither holds one thread for its whole
+    // duration nor takes every thread of the pool.
+    let mut chunks: Vec<Vec<(usize, ImportUserRow)>> = Vec::new();
+    let mut candidates = candidates.into_iter().peekable();
+    while candidates.peek().is_some() {
+        chunks.push(candidates.by_ref().take(IMPORT_HASH_CHUNK_SIZE).collect());
+    }
+    let users: Vec<(usize, User)> = stream::iter(chunks)
+        .map(|chunk| web::block(move || build_import_users(chunk)))
+        .buffered(IMPORT_HASH_CONCURRENCY)
+        .map(|result| result.map_err(|e| AppError::InternalServerError(e.to_string()))?)
+        .try_concat()
+        .await?;
+
+    let invited: HashSet<usize> = users
+        .iter()
+        .filter(|(_, user)| user.password.as_deref() == Some(UNUSABLE_PASSWORD))
+        .map(|(index, _)| *index)
+        .collect();
+    let indexes: HashMap<String, usize> = users
+        .iter()
+        .filter_map(|(index, user)| user.email.clone().map(|email| (email, *index)))
+        .collect();
+
+    let context = AuditContext::from_request(req);
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let mut inserted: Vec<(Uuid, String)> = Vec::new();
+
+    let mut users = users.into_iter().map(|(_, user)| user).peekable();
+    while users.peek().is_some() {
+        let batch: Vec<User> = users.by_ref().take(IMPORT_BATCH_SIZE).collect();
+        let created = users_query::insert_users_batch(&mut tx, batch).await?;
+        let ids: Vec<Uuid> = created.iter().map(|user| user.id).collect();
+        events_query::insert_events(
+            &mut tx,
+            ids.iter()
+                .map(|id| DomainEvent::UserRegistered { user_id: *id })
+                .collect(),
+        )
+        .await?;
+        organizations_query::add_memberships(
+            &mut tx,
+            organization_id,
+            &ids,
+            MembershipRole::MEMBER,
+        )
+        .await?;
+
+        audit_query::insert_audit_events(
+            &mut tx,
+            &context,
+            AuditAction::UserImported,
+            created
+                .iter()
+                .map(|user| (user.id, diff(None, Some(user))))
+                .collect(),
+        )
+        .await?;
+        audit_query::insert_audit_events(
+            &mut tx,
+            &context,
+            AuditAction::UserMembershipAdded,
+            ids.iter()
+                .map(|id| {
+                    (
+                        *id,
+                        membership_change(
+                            organization_id,
+                            Value::Null,
+                            json!(MembershipRole::MEMBER),
+                        ),
+                    )
+                })
+                .collect(),
+        )
+        .await?;
+
+        inserted.extend(created.into_iter().map(|user| (user.id, user.email)));
+    }
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    let inserted_emails: HashSet<&String> = inserted.iter().map(|(_, email)| email).collect();
+    for (email, index) in &indexes {
+        if !inserted_emails.contains(email) {
+            results[*index].status = ImportRowStatus::Duplicate;
+            results[*index].error = Some("Email already exists.".to_string());
+        }
+    }
+
+    for (id, email) in inserted {
+        let index = indexes[&email];
+        results[index].id = Some(id);
+
+        if invited.contains(&index) {
+            results[index].status = ImportRowStatus::Invited;
+            results[index].invite_link = Some(generate_invite_link(id, app_state)?);
+        } else {
+            results[index].status = ImportRowStatus::Created;
+        }
+    }
+
+    Ok(ResponseData::new(
+        ImportUsersReport::new(false, results),
+        "Data has been successfuly imported.",
+    ))
+}
+
+/// Streams the users matching the export filter as body chunks. The query runs in its
+/// own task and is cancelled as soon as the client goes away.
+pub fn export(
+    pool: &PgPool,
+    query: ExportUsersQuery,
+    req: &HttpRequest,
+) -> Result<impl Stream<Item = Result<Bytes, AppError>>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let tenant = Tenant::from_request(req)?;
+
+    let (sender, receiver) = mpsc::channel(EXPORT_BUFFER_SIZE);
+    let pool = pool.clone();
+    let filter = query.filter;
+
+    tokio::spawn(async move {
+        let mut errors = sender.clone();
+
+        let result = async {
+            let mut tx = tenant.begin(&pool).await?;
+            users_query::stream_users(&mut tx, tenant, &filter, sender).await?;
+            tx.commit().await.map_err(AppError::DatabaseError)
+        };
+
+        if let Err(err) = result.await {
+            log::error!("user export failed error={}", err);
+            let _ = errors.send(Err(err)).await;
+        }
+    });
+
+    let mut encoder = ExportEncoder::new(query.format);
+    let header = encoder.header();
+    let footer = encoder.footer();
+
+    let rows = receiver.map(move |row| row.and_then(|user| encoder.row(&user)));
+
+    Ok(stream::iter(header.map(Ok))
+        .chain(rows)
+        .chain(stream::iter(footer.map(Ok))))
+}
+
+/// Users to insert for the validated import rows.
+fn build_import_users(rows: Vec<(usize, ImportUserRow)>) -> Result<Vec<(usize, User)>, AppError> {
+    rows.into_iter()
+        .map(|(index, row)| {
+            // Invited users stay PENDING until they set a password.
+            let (password, status) = match &row.password {
+                Some(password) => (hash_password(password)?, UserStatus::ACTIVE),
+                None => (UNUSABLE_PASSWORD.to_string(), UserStatus::PENDING),
+            };
+
+            Ok((
+                index,
+                User {
+                    id: Some(Uuid::new_v4()),
+                    name: Some(row.name),
+                    email: Some(row.email),
+                    password: Some(password),
+                    status: Some(status),
+                    role: Some(UserRole::USER),
+                    display_name: None,
+                    phone: None,
+                    locale: None,
+                    timezone: None,
+                    bio: None,
+                    avatar_updated_at: None,
+                    metadata: None,
+                    created_at: Some(Utc::now()),
+                    updated_at: Some(Utc::now()),
+                    deleted_at: None,
+                },
+            ))
+        })
+        .collect()
+}
+
+pub async fn bulk(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    payload: BulkUsersDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<BulkUsersReport>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+    let tenant = Tenant::Organization(organization_id);
+    let caller = user_id_in_token(req)?;
+    let platform_admin = validate_admin_in_token(req).is_ok();
+
+    if payload.operations.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one operation is required.".to_string(),
+        ));
+    }
+
+    for (index, operation) in payload.operations.iter().enumerate() {
+        operation.validate_target(index)?;
+        operation.validate_reason(index)?;
+    }
+
+    let mut tx = tenant.begin(pool).await?;
+
+    // Resolve every target up front so filters see the table as it was before the request.
+    let mut targets: Vec<(usize, &BulkAction, Vec<Uuid>)> = Vec::new();
+    let mut total = 0;
+
+    for (index, operation) in payload.operations.iter().enumerate() {
+        let ids = match (&operation.ids, &operation.filter) {
+            (Some(ids), _) => {
+                let mut seen = HashSet::new();
+                ids.iter().copied().filter(|id| seen.insert(*id)).collect()
+            }
+            (None, Some(filter)) => {
+                let remaining = BULK_MAX_ITEMS.saturating_sub(total) as i64 + 1;
+                users_query::find_user_ids(&mut tx, tenant, filter, remaining).await?
+            }
+            (None, None) => Vec::new(),
+        };
+
+        total += ids.len();
+        if total > BULK_MAX_ITEMS {
+            return Err(AppError::BadRequest(format!(
+                "Bulk requests are limited to {} items.",
+                BULK_MAX_ITEMS
+            )));
+        }
+
+        targets.push((index, &operation.action, ids));
+    }
+
+    let context = AuditContext::from_request(req);
+    let mut results: Vec<BulkItemResult> = Vec::with_capacity(total);
+    let mut keys: Vec<String> = Vec::new();
+    let mut aborted = false;
+
+    for (index, action, ids) in targets {
+        for id in ids {
+            if aborted {
+                results.push(BulkItemResult {
+                    operation: index,
+                    id,
+                    status: BulkItemStatus::Skipped,
+                    error: None,
+                });
+                continue;
+            }
+
+            let outcome = match payload.mode {
+                BulkMode::Atomic => {
+                    apply_action(
+                        &mut tx,
+                        organization_id,
+                        &context,
+                        caller,
+                        platform_admin,
+                        id,
+                        action,
+                    )
+                    .await
+                }
+                BulkMode::BestEffort => {
+                    let mut savepoint =
+                        (&mut *tx).begin().await.map_err(AppError::DatabaseError)?;
+                    let outcome = apply_action(
+                        &mut savepoint,
+                        organization_id,
+                        &context,
+                        caller,
+                        platform_admin,
+                        id,
+                        action,
+                    )
+                    .await;
+
+                    match outcome {
+                        Ok(_) => savepoint.commit().await,
+                        Err(_) => savepoint.rollback().await,
+                    }
+                    .map_err(AppError::DatabaseError)?;
+
+                    outcome
+                }
+            };
+
+            let (status, error) = match outcome {
+                Ok(deleted_keys) => {
+                    keys.extend(deleted_keys);
+                    (BulkItemStatus::Succeeded, None)
+                }
+                Err(err) => {
+                    aborted = payload.mode == BulkMode::Atomic;
+                    (BulkItemStatus::Failed, Some(err.message()))
+                }
+            };
+
+            results.push(BulkItemResult {
+                operation: index,
+                id,
+                status,
+                error,
+            });
+        }
+    }
+
+    if aborted {
+        tx.rollback().await.map_err(AppError::DatabaseError)?;
+
+        for result in results.iter_mut() {
+            if result.status == BulkItemStatus::Succeeded {
+                result.status = BulkItemStatus::RolledBack;
+            }
+        }
+
+        return Ok(ResponseData::new(
+            BulkUsersReport::new(false, results),
+            "Data has been rolled back.",
+        ));
+    }
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    users_service::delete_user_files(app_state.storage.as_ref(), keys).await;
+
+    Ok(ResponseData::new(
+        BulkUsersReport::new(true, results),
+        "Data has been successfuly processed.",
+    ))
+}
+
+pub async fn anonymize(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<GetUserDTO>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+    let tenant = Tenant::Organization(organization_id);
+
+    let mut tx = tenant.begin(pool).await?;
+    validate_account_change(
+        &mut tx,
+        organization_id,
+        id,
+        validate_admin_in_token(req).is_ok(),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    let result = users_service::erase(pool, app_state, tenant, id, None, req).await?;
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly anonymized.",
+    ))
+}
+
+pub async fn set_status(
+    pool: &PgPool,
+    id: Uuid,
+    payload: UpdateUserStatusDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<GetUserDTO>, AppError> {
+    validate_org_admin_in_token(req)?;
+    payload.validate().map_err(AppError::ValidationError)?;
+    let organization_id = organization_id_in_token(req)?;
+    let tenant = Tenant::Organization(organization_id);
+
+    if id == user_id_in_token(req)? {
+        return Err(AppError::Forbidden(
+            "Admins cannot change their own status".to_string(),
+        ));
+    }
+
+    let context = AuditContext::from_request(req);
+    let mut tx = tenant.begin(pool).await?;
+    validate_account_change(
+        &mut tx,
+        organization_id,
+        id,
+        validate_admin_in_token(req).is_ok(),
+    )
+    .await?;
+    let before = users_query::find_user_snapshot(&mut tx, id).await?;
+    users_service::transition_status(
+        &mut tx,
+        tenant,
+        id,
+        payload.status,
+        payload.reason.as_deref(),
+        &context,
+    )
+    .await?;
+    let after = users_query::find_user_snapshot(&mut tx, id)
+        .await?
+        .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+    let changes = diff(before.as_ref(), Some(&after));
+    let event = match after.status {
+        UserStatus::DELETED => DomainEvent::UserDeleted {
+            user_id: id,
+            permanent: false,
+        },
+        _ => DomainEvent::user_updated(id, &changes),
+    };
+    events_query::insert_event(&mut tx, event).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &context,
+        AuditAction::UserStatusChanged,
+        id,
+        changes,
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        after,
+        "Data has been successfuly updated.",
+    ))
+}
+
+pub async fn find_status_transitions(
+    pool: &PgPool,
+    id: Uuid,
+    query_pagination: QueryPagination,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<UserStatusTransition>>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let tenant = Tenant::from_request(req)?;
+
+    if query_pagination.after.is_some() || query_pagination.before.is_some() {
+        return Err(AppError::BadRequest(
+            "Cursor pagination is not supported for status transitions.".to_string(),
+        ));
+    }
+
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let mut tx = tenant.begin(pool).await?;
+    let result =
+        users_query::find_status_transitions(&mut tx, tenant, id, limit, offset, page).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        result.data,
+    ))
+}
+
+/// Returns the storage keys of a hard deleted user, to pass to
+/// [`users_service::delete_user_files`] once the transaction is committed.
+async fn apply_action(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    context: &AuditContext,
+    caller: Uuid,
+    platform_admin: bool,
+    id: Uuid,
+    action: &BulkAction,
+) -> Result<Vec<String>, AppError> {
+    // An admin cannot lock out, demote or delete themselves by accident of a filter.
+    if id == caller {
+        return Err(AppError::Forbidden(
+            "Bulk operations cannot target the caller".to_string(),
+        ));
+    }
+
+    match action {
+        BulkAction::AssignRole { .. } | BulkAction::RemoveMember => {}
+        BulkAction::HardDelete if !platform_admin => {
+            return Err(AppError::Forbidden(
+                "Platform admin role is required to delete accounts, remove the member instead"
+                    .to_string(),
+            ))
+        }
+        _ => validate_account_change(conn, organization_id, id, platform_admin).await?,
+    }
+
+    let tenant = Tenant::Organization(organization_id);
+    let before = users_query::find_user_snapshot(conn, id).await?;
+    let mut keys = Vec::new();
+
+    let audit_action = match action {
+        BulkAction::SetStatus { status, reason } => {
+            users_service::transition_status(
+                conn,
+                tenant,
+                id,
+                status.clone(),
+                reason.as_deref(),
+                context,
+            )
+            .await?;
+            AuditAction::UserStatusChanged
+        }
+        BulkAction::SoftDelete => {
+            users_service::transition_status(conn, tenant, id, UserStatus::DELETED, None, context)
+                .await?;
+            AuditAction::UserSoftDeleted
+        }
+        BulkAction::Restore => match before.as_ref().map(|user| &user.status) {
+            Some(status) if *status != UserStatus::DELETED => {
+                return Err(AppError::Conflict(format!(
+                    "User with ID {} is not deleted",
+                    id
+                )))
+            }
+            _ => {
+                users_service::transition_status(
+                    conn,
+                    tenant,
+                    id,
+                    UserStatus::ACTIVE,
+                    None,
+                    context,
+                )
+                .await?;
+                AuditAction::UserStatusChanged
+            }
+        },
+        BulkAction::HardDelete => match before.as_ref().map(|user| &user.status) {
+            Some(status) if *status != UserStatus::DELETED => {
+                return Err(AppError::Conflict(format!(
+                    "User with ID {} must be soft deleted first",
+                    id
+                )))
+            }
+            _ => {
+                // Written first, the event goes to the organizations the delete removes
+                // the user from.
+                events_query::insert_event(
+                    conn,
+                    DomainEvent::UserDeleted {
+                        user_id: id,
+                        permanent: true,
+                    },
+                )
+                .await?;
+                // Erased before the delete, the cascade would drop the export rows and
+                // with them the storage keys of their archives.
+                keys = users_service::erase_user_data(conn, &[id]).await?;
+                users_query::hard_delete_user(conn, tenant, id).await?;
+                // The history trigger just copied the deleted row.
+                users_query::delete_users_history(conn, &[id]).await?;
+                AuditAction::UserDeleted
+            }
+        },
+        BulkAction::AssignRole { role } => {
+            return assign_membership_role(conn, organization_id, context, id, *role)
+                .await
+                .map(|()| Vec::new())
+        }
+        BulkAction::RemoveMember => {
+            return remove_membership(conn, organization_id, context, id)
+                .await
+                .map(|()| Vec::new())
+        }
+    };
+
+    let after = users_query::find_user_snapshot(conn, id).await?;
+    let changes = match action {
+        // Nothing is left to erase later, so the log keeps field names only.
+        BulkAction::HardDelete => redact_before(diff(before.as_ref(), None)),
+        _ => diff(before.as_ref(), after.as_ref()),
+    };
+    let event = match (action, after.as_ref().map(|user| &user.status)) {
+        (BulkAction::HardDelete, _) => None,
+        (_, Some(UserStatus::DELETED)) => Some(DomainEvent::UserDeleted {
+            user_id: id,
+            permanent: false,
+        }),
+        _ => Some(DomainEvent::user_updated(id, &changes)),
+    };
+    if let Some(event) = event {
+        events_query::insert_event(conn, event).await?;
+    }
+    audit_query::insert_audit_event(conn, context, audit_action, id, changes).await?;
+
+    Ok(keys)
+}
+
+/// Roles are per organization, the bulk assignment changes the membership in the active
+/// one and leaves the account and its other memberships alone. Ownership is handed out
+/// one member at a time, where the last owner is protected.
+async fn assign_membership_role(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    context: &AuditContext,
+    id: Uuid,
+    role: MembershipRole,
+) -> Result<(), AppError> {
+    let before = organizations_query::find_membership(conn, organization_id, id)
+        .await?
+        .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    if before.role == MembershipRole::OWNER || role == MembershipRole::OWNER {
+        return Err(AppError::Forbidden(
+            "Ownership cannot be changed in bulk, update the member instead".to_string(),
+        ));
+    }
+
+    let after =
+        organizations_query::update_membership_role(conn, organization_id, id, role).await?;
+    audit_query::insert_audit_event(
+        conn,
+        context,
+        AuditAction::UserMembershipRoleChanged,
+        id,
+        membership_change(organization_id, json!(before.role), json!(after.role)),
+    )
+    .await
+}
+
+/// Owners are removed one at a time, where the last owner is protected.
+async fn remove_membership(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    context: &AuditContext,
+    id: Uuid,
+) -> Result<(), AppError> {
+    let membership = organizations_query::find_membership(conn, organization_id, id)
+        .await?
+        .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    if membership.role == MembershipRole::OWNER {
+        return Err(AppError::Forbidden(
+            "Owners cannot be removed in bulk, remove the member instead".to_string(),
+        ));
+    }
+
+    organizations_query::delete_membership(conn, organization_id, id).await?;
+    audit_query::insert_audit_event(
+        conn,
+        context,
+        AuditAction::UserMembershipRemoved,
+        id,
+        membership_change(organization_id, json!(membership.role), Value::Null),
+    )
+    .await
+}
+
+/// Status changes and erasure apply to the account in every organization, organization
+/// admins only make them for accounts that belong to theirs alone.
+async fn validate_account_change(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    id: Uuid,
+    platform_admin: bool,
+) -> Result<(), AppError> {
+    if platform_admin {
+        return Ok(());
+    }
+
+    if organizations_query::find_membership(conn, organization_id, id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::NotFound(format!("User with ID {} not found", id)));
+    }
+
+    if organizations_query::has_other_memberships(conn, organization_id, id).await? {
+        return Err(AppError::Forbidden(format!(
+            "User with ID {} also belongs to other organizations, only platform admins can change the account",
+            id
+        )));
+    }
+
+    Ok(())
+}
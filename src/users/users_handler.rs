@@ -3,10 +3,31 @@ use std::collections::HashMap;
 use crate::{
     middlewares::middleware_auth::JwtAuthMiddleware,
     server::AppState,
-    users::{dto::UpdateUserDTO, users_service},
-    utils::{errors::AppError, query_paginaton::QueryPagination},
+    users::{
+        dto::{
+            AsOfQuery, AvatarQuery, CreateDataExportDTO, DownloadDataExportQuery,
+            RequestEmailChangeDTO, SearchUserQuery, UpdateUserDTO, UpdateUserPasswordDto,
+            UserFilterQuery, UserPatch,
+        },
+        users_service,
+    },
+    utils::{
+        avatar::AVATAR_CONTENT_TYPE,
+        errors::AppError,
+        etag::{etag_for, is_not_modified},
+        query_fields::QueryFields,
+        query_paginaton::QueryPagination,
+    },
 };
-use actix_web::{web, HttpRequest, HttpResponse};
+use actix_multipart::Multipart;
+use actix_web::{
+    http::header::{
+        self, CacheControl, CacheDirective, ContentDisposition, DispositionParam, DispositionType,
+        ETag, HttpDate, LastModified,
+    },
+    web, HttpRequest, HttpResponse,
+};
+use serde_json::Value;
 use serde_qs::actix::QsQuery;
 use sqlx::PgPool;
 use uuid::Uuid;
@@ -15,28 +36,338 @@ pub fn configure(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
     cfg.service(
         web::scope("/users")
             .wrap(JwtAuthMiddleware::new(app_state))
+            .service(web::resource("/search").route(web::get().to(search)))
+            .service(
+                web::resource("/{id}/email-change").route(web::post().to(request_email_change)),
+            )
+            .service(web::resource("/{id}/export").route(web::post().to(request_export)))
+            .service(web::resource("/{id}/exports/{export_id}").route(web::get().to(find_export)))
+            .service(web::resource("/{id}/history").route(web::get().to(find_history)))
+            .service(web::resource("/{id}/password").route(web::put().to(update_password)))
+            .service(web::resource("/{id}/metadata").route(web::get().to(find_metadata)))
+            .service(
+                web::resource("/{id}/preferences")
+                    .route(web::get().to(find_preferences))
+                    .route(web::put().to(update_preferences)),
+            )
+            .service(
+                web::resource("/{id}/metadata/{key}")
+                    .route(web::get().to(find_metadata_key))
+                    .route(web::put().to(set_metadata))
+                    .route(web::delete().to(delete_metadata)),
+            )
+            .service(
+                web::resource("/{id}/avatar")
+                    .route(web::get().to(find_avatar))
+                    .route(web::put().to(update_avatar)),
+            )
             .service(
                 web::resource("/{id}")
                     .route(web::get().to(find))
                     .route(web::put().to(update))
+                    .route(web::patch().to(patch))
                     .route(web::delete().to(delete)),
             )
             .service(web::resource("").route(web::get().to(find_all))),
     );
+
+    // Authorized by the signed token in the link, not by the access token.
+    cfg.service(
+        web::scope("/exports").service(
+            web::resource("/{export_id}/download")
+                .name("user_export_download")
+                .route(web::get().to(download_export)),
+        ),
+    );
+}
+
+async fn find(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    query_fields: web::Query<QueryFields>,
+    as_of: web::Query<AsOfQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::find(
+        &pool,
+        id.into_inner(),
+        query_fields.into_inner(),
+        as_of.into_inner().as_of,
+        &req,
+    )
+    .await
+    {
+        Ok((_, etag)) if is_not_modified(&req, &etag) => Ok(HttpResponse::NotModified()
+            .insert_header(ETag(etag))
+            .finish()),
+        Ok((response, etag)) => Ok(HttpResponse::Ok().insert_header(ETag(etag)).json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_history(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    query_pagination: QsQuery<QueryPagination>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::find_history(&pool, id.into_inner(), query_pagination.into_inner(), &req)
+        .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
 }
 
-async fn find(pool: web::Data<PgPool>, id: web::Path<Uuid>) -> Result<HttpResponse, AppError> {
-    match users_service::find(&pool, id.into_inner()).await {
+async fn request_email_change(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    payload: web::Json<RequestEmailChangeDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::request_email_change(
+        &pool,
+        &app_state,
+        id.into_inner(),
+        payload.into_inner(),
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Accepted().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn request_export(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    payload: web::Json<CreateDataExportDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::request_export(&pool, id.into_inner(), payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Accepted().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_export(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, export_id) = path.into_inner();
+
+    match users_service::find_export(&pool, &app_state, id, export_id, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn download_export(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    export_id: web::Path<Uuid>,
+    query: web::Query<DownloadDataExportQuery>,
+) -> Result<HttpResponse, AppError> {
+    match users_service::download_export(&pool, &app_state, export_id.into_inner(), &query.token)
+        .await
+    {
+        Ok((bytes, export)) => Ok(HttpResponse::Ok()
+            .content_type(export.format.content_type())
+            .insert_header(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(format!(
+                    "user-data-{}.{}",
+                    export.created_at.format("%Y%m%d"),
+                    export.format.extension()
+                ))],
+            })
+            .insert_header(CacheControl(vec![CacheDirective::NoStore]))
+            .body(bytes)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_metadata(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::find_metadata(&pool, id.into_inner(), None, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_metadata_key(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, key) = path.into_inner();
+
+    match users_service::find_metadata(&pool, id, Some(key), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn set_metadata(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    path: web::Path<(Uuid, String)>,
+    value: web::Json<Value>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, key) = path.into_inner();
+
+    match users_service::set_metadata(&pool, &app_state, id, key, value.into_inner(), &req).await {
         Ok(response) => Ok(HttpResponse::Ok().json(response)),
         Err(err) => Err(err),
     }
 }
 
+async fn delete_metadata(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    path: web::Path<(Uuid, String)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, key) = path.into_inner();
+
+    match users_service::delete_metadata(&pool, &app_state, id, key, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_preferences(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::find_preferences(&pool, &app_state, id.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn update_preferences(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    payload: web::Json<Value>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::update_preferences(
+        &pool,
+        &app_state,
+        id.into_inner(),
+        payload.into_inner(),
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+// Avatars change rarely and are revalidated cheaply through their ETag.
+const AVATAR_MAX_AGE: u32 = 86400;
+
+async fn find_avatar(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    query: web::Query<AvatarQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let size = query.into_inner().size;
+
+    match users_service::find_avatar(&pool, &app_state, id.into_inner(), size, &req).await {
+        Ok((bytes, avatar_updated_at)) => {
+            let etag = etag_for(avatar_updated_at, Some(size.as_str()));
+            let cache_control = CacheControl(vec![
+                CacheDirective::Private,
+                CacheDirective::MaxAge(AVATAR_MAX_AGE),
+            ]);
+            let last_modified = LastModified(HttpDate::from(std::time::SystemTime::from(
+                avatar_updated_at,
+            )));
+
+            if is_not_modified(&req, &etag) {
+                return Ok(HttpResponse::NotModified()
+                    .insert_header(ETag(etag))
+                    .insert_header(cache_control)
+                    .finish());
+            }
+
+            Ok(HttpResponse::Ok()
+                .content_type(AVATAR_CONTENT_TYPE)
+                .insert_header(ETag(etag))
+                .insert_header(cache_control)
+                .insert_header(last_modified)
+                .body(bytes))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+async fn update_avatar(
+    pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    payload: Multipart,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::update_avatar(&pool, &app_state, id.into_inner(), payload, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok()
+            .insert_header(ETag(etag_for(response.data.updated_at, None)))
+            .json(response)),
+        Err(err) => Err(err),
+    }
+}
+
 async fn find_all(
     pool: web::Data<PgPool>,
     query_pagination: QsQuery<QueryPagination>,
+    query_filter: QsQuery<UserFilterQuery>,
+    query_fields: QsQuery<QueryFields>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, AppError> {
-    match users_service::find_all(&pool, query_pagination.into_inner()).await {
+    match users_service::find_all(
+        &pool,
+        query_pagination.into_inner(),
+        query_filter.into_inner().filter,
+        query_fields.into_inner(),
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn search(
+    pool: web::Data<PgPool>,
+    search_query: web::Query<SearchUserQuery>,
+    query_pagination: QsQuery<QueryPagination>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::search(
+        &pool,
+        search_query.into_inner(),
+        query_pagination.into_inner(),
+        &req,
+    )
+    .await
+    {
         Ok(response) => Ok(HttpResponse::Ok().json(response)),
         Err(err) => Err(err),
     }
@@ -49,13 +380,51 @@ async fn update(
     req: HttpRequest,
 ) -> Result<HttpResponse, AppError> {
     match users_service::update(&pool, id.into_inner(), payload.into_inner(), &req).await {
-        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Ok(response) => Ok(HttpResponse::Ok()
+            .insert_header(ETag(etag_for(response.data.updated_at, None)))
+            .json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn update_password(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    payload: web::Json<UpdateUserPasswordDto>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match users_service::update_password(&pool, id.into_inner(), payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok()
+            .insert_header(ETag(etag_for(response.data.updated_at, None)))
+            .json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn patch(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    body: web::Bytes,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let patch = UserPatch::parse(content_type, &body)?;
+
+    match users_service::patch(&pool, id.into_inner(), patch, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok()
+            .insert_header(ETag(etag_for(response.data.updated_at, None)))
+            .json(response)),
         Err(err) => Err(err),
     }
 }
 
 async fn delete(
     pool: web::Data<PgPool>,
+    app_state: web::Data<AppState>,
     id: web::Path<Uuid>,
     query: web::Query<HashMap<String, String>>,
     req: HttpRequest,
@@ -63,14 +432,23 @@ async fn delete(
     let mode = query.get("mode").map(|s| s.as_str());
 
     if let Some("hard") = mode {
-        match users_service::delete(&pool, id.into_inner(), &req).await {
+        match users_service::delete(&pool, &app_state, id.into_inner(), &req).await {
+            Ok(response) => return Ok(HttpResponse::Ok().json(response)),
+            Err(err) => return Err(err),
+        }
+    }
+
+    if let Some("anonymize") = mode {
+        match users_service::anonymize(&pool, &app_state, id.into_inner(), &req).await {
             Ok(response) => return Ok(HttpResponse::Ok().json(response)),
             Err(err) => return Err(err),
         }
     }
 
     match users_service::soft_delete(&pool, id.into_inner(), &req).await {
-        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Ok(response) => Ok(HttpResponse::Ok()
+            .insert_header(ETag(etag_for(response.data.updated_at, None)))
+            .json(response)),
         Err(err) => Err(err),
     }
 }
@@ -1,31 +1,49 @@
 use crate::{
     auth::dto::login_dto::GetLoginDto,
+    organizations::entity::MembershipRole,
     users::{
-        dto::{CreateUserDTO, GetUserDTO, UpdateUserDTO, UpdateUserPasswordDto},
-        entity::{User, UserStatus},
+        dto::{
+            history_users_dto::UserVersionRow, preferences_users_dto::UserPreferencesRow,
+            search_users_dto::SearchUserRow, CreateUserDTO, GetUserDTO, PatchUserDocument,
+            SearchUserDTO, UpdateUserDTO, UserFilter, UserVersionDTO,
+        },
+        entity::{
+            EmailChangeRequest, User, UserExport, UserExportFormat, UserExportStatus, UserRole,
+            UserStatus, UserStatusTransition,
+        },
     },
     utils::{
         errors::AppError,
+        password::UNUSABLE_PASSWORD,
+        query_cursor::split_page,
+        query_fields::Fieldset,
         query_paginaton::{QueryPagination, ResultWithPagination},
+        query_sort::push_order_by,
+        tenant::Tenant,
     },
 };
 use chrono::{DateTime, Utc};
-use sqlx::{PgPool, QueryBuilder};
+use futures::{channel::mpsc::Sender, SinkExt, TryStreamExt};
+use serde_json::Value;
+use sqlx::{Acquire, PgConnection, PgPool, QueryBuilder};
 use uuid::Uuid;
 
-pub async fn login_users_query(pool: &PgPool, email: &str) -> Result<GetLoginDto, AppError> {
+pub async fn login_users_query(
+    conn: &mut PgConnection,
+    email: &str,
+) -> Result<GetLoginDto, AppError> {
     let result: GetLoginDto = sqlx::query_as::<_, GetLoginDto>(
         "--sql
         SELECT
-            id, password, email
+            id, password, email, role, status
         FROM 
             users
         WHERE 
-            email = $1 AND status = 'ACTIVE'
+            email = $1 AND status != 'DELETED'
         ",
     )
     .bind(email)
-    .fetch_optional(pool)
+    .fetch_optional(conn)
     .await
     .map_err(AppError::DatabaseError)?
     .ok_or(AppError::NotFound(format!(
@@ -36,58 +54,131 @@ pub async fn login_users_query(pool: &PgPool, email: &str) -> Result<GetLoginDto
     Ok(result)
 }
 
-pub async fn delete_user(pool: &PgPool, id: Uuid) -> Result<GetUserDTO, AppError> {
-    let result: GetUserDTO = sqlx::query_as::<_, User>(
+/// Current status and role of the account behind an access token and its role in the
+/// token's organization, `None` once the account is gone. The membership role is
+/// `None` when the token has no organization or the membership was removed.
+pub async fn find_user_access(
+    conn: &mut PgConnection,
+    id: Uuid,
+    organization_id: Option<Uuid>,
+) -> Result<Option<(UserStatus, UserRole, Option<MembershipRole>)>, AppError> {
+    let result = sqlx::query_as::<_, (UserStatus, UserRole, Option<MembershipRole>)>(
         r#"--sql
-        DELETE FROM users
+        SELECT
+            users.status,
+            users.role,
+            memberships.role
+        FROM
+            users
+            LEFT JOIN memberships ON memberships.user_id = users.id
+            AND memberships.organization_id = $2
         WHERE
-            id = $1 AND status = $2
-        RETURNING 
-            *
+            users.id = $1
         "#,
     )
     .bind(id)
-    .bind(UserStatus::DELETED)
-    .fetch_optional(pool)
+    .bind(organization_id)
+    .fetch_optional(conn)
     .await
-    .map_err(AppError::DatabaseError)?
-    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
-    .into();
+    .map_err(AppError::DatabaseError)?;
 
     Ok(result)
 }
 
-pub async fn find_user(pool: &PgPool, id: Uuid) -> Result<GetUserDTO, AppError> {
-    let result: GetUserDTO = sqlx::query_as::<_, User>(
+/// Resolves why a versioned mutation matched no row: the row is missing (or in the
+/// wrong state) or the client's `If-Match` version is stale.
+async fn version_mismatch_error(conn: &mut PgConnection, id: Uuid, deleted: bool) -> AppError {
+    let exists = sqlx::query_scalar::<_, bool>(
         r#"--sql
-        SELECT
-        *
-        FROM
-            users
+        SELECT EXISTS (
+            SELECT
+                1
+            FROM
+                users
+            WHERE
+                id = $1 AND (status = $2) = $3
+        )
+        "#,
+    )
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .bind(deleted)
+    .fetch_one(conn)
+    .await;
+
+    match exists {
+        Ok(true) => AppError::PreconditionFailed(format!(
+            "User with ID {} has been modified since it was retrieved",
+            id
+        )),
+        Ok(false) => AppError::NotFound(format!("User with ID {} not found", id)),
+        Err(err) => AppError::DatabaseError(err),
+    }
+}
+
+pub async fn delete_user(
+    conn: &mut PgConnection,
+    id: Uuid,
+    versions: Option<Vec<DateTime<Utc>>>,
+) -> Result<GetUserDTO, AppError> {
+    let result = sqlx::query_as::<_, User>(
+        r#"--sql
+        DELETE FROM users
         WHERE
             id = $1 AND status = $2
+            AND ($3::timestamptz[] IS NULL OR updated_at = ANY($3))
+        RETURNING 
+            *
         "#,
     )
     .bind(id)
-    .bind(UserStatus::ACTIVE)
-    .fetch_optional(pool)
+    .bind(UserStatus::DELETED)
+    .bind(versions)
+    .fetch_optional(&mut *conn)
     .await
-    .map_err(AppError::DatabaseError)?
-    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
-    .into();
+    .map_err(AppError::DatabaseError)?;
 
-    Ok(result)
+    match result {
+        Some(user) => GetUserDTO::try_from(user),
+        None => Err(version_mismatch_error(conn, id, true).await),
+    }
+}
+
+pub async fn find_user(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+    fieldset: &Fieldset,
+) -> Result<GetUserDTO, AppError> {
+    let mut query_builder = QueryBuilder::new(format!(
+        "SELECT {} FROM users",
+        fieldset.columns::<GetUserDTO>(&["id", "updated_at"])
+    ));
+    query_builder.push(" WHERE id = ").push_bind(id);
+    query_builder
+        .push(" AND status != ")
+        .push_bind(UserStatus::DELETED);
+    tenant.push_condition(&mut query_builder, "id");
+
+    let result = query_builder
+        .build_query_as::<User>()
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(AppError::DatabaseError)?
+        .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    GetUserDTO::from_row(result, fieldset)
 }
 
 pub async fn update_user(
-    pool: &PgPool,
+    conn: &mut PgConnection,
     id: Uuid,
     payload: UpdateUserDTO,
+    versions: Option<Vec<DateTime<Utc>>>,
 ) -> Result<GetUserDTO, AppError> {
     enum DataType {
         Text(String),
         DateTime(Option<DateTime<Utc>>),
-        UserStatus(UserStatus),
     }
 
     let input: User = payload.into();
@@ -99,12 +190,18 @@ pub async fn update_user(
         updates.push(("name", DataType::Text(name)));
     }
 
-    if let Some(email) = input.email {
-        updates.push(("email", DataType::Text(email)));
-    }
+    let profile = [
+        ("display_name", input.display_name),
+        ("phone", input.phone),
+        ("locale", input.locale),
+        ("timezone", input.timezone),
+        ("bio", input.bio),
+    ];
 
-    if let Some(status) = input.status {
-        updates.push(("status", DataType::UserStatus(status)));
+    for (col, value) in profile {
+        if let Some(value) = value {
+            updates.push((col, DataType::Text(value)));
+        }
     }
 
     if let Some(updated_at) = input.updated_at {
@@ -119,7 +216,6 @@ pub async fn update_user(
         match val {
             DataType::Text(value) => query_builder.push_bind(value),
             DataType::DateTime(value) => query_builder.push_bind(value),
-            DataType::UserStatus(value) => query_builder.push_bind(value),
         };
     }
 
@@ -127,107 +223,247 @@ pub async fn update_user(
     query_builder
         .push(" AND status != ")
         .push_bind(UserStatus::DELETED);
+    if let Some(versions) = versions {
+        query_builder
+            .push(" AND updated_at = ANY(")
+            .push_bind(versions)
+            .push(")");
+    }
     query_builder.push(" RETURNING *");
 
     let query = query_builder.build_query_as::<User>();
 
-    let result: GetUserDTO = query
-        .fetch_optional(pool)
+    let result = query
+        .fetch_optional(&mut *conn)
         .await
-        .map_err(AppError::DatabaseError)?
-        .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
-        .into();
+        .map_err(AppError::DatabaseError)?;
 
-    Ok(result)
+    match result {
+        Some(user) => GetUserDTO::try_from(user),
+        None => Err(version_mismatch_error(conn, id, false).await),
+    }
 }
 
-pub async fn create_user(pool: &PgPool, payload: CreateUserDTO) -> Result<Uuid, AppError> {
-    let User {
-        id,
-        name,
-        email,
-        password,
-        status,
-        created_at,
-        updated_at,
-        deleted_at,
-    } = payload.into();
+pub async fn touch_user_avatar(conn: &mut PgConnection, id: Uuid) -> Result<GetUserDTO, AppError> {
+    let now = Utc::now();
 
-    let user_id: Uuid = sqlx::query_scalar(
+    let result: GetUserDTO = sqlx::query_as::<_, User>(
         r#"--sql
-        INSERT INTO
-            users (id, name, email, password, status, created_at, updated_at, deleted_at)
-        VALUES
-            ($1, $2, $3, $4, $5, $6, $7, $8)
-        RETURNING id
+        UPDATE
+            users
+        SET
+            avatar_updated_at = $1,
+            updated_at = $1
+        WHERE
+            id = $2 AND status = $3
+        RETURNING
+            *
         "#,
     )
+    .bind(now)
     .bind(id)
-    .bind(name)
-    .bind(email)
-    .bind(password)
-    .bind(status)
-    .bind(created_at)
-    .bind(updated_at)
-    .bind(deleted_at)
-    .fetch_one(pool)
+    .bind(UserStatus::ACTIVE)
+    .fetch_optional(conn)
     .await
-    .map_err(|e| match e {
-        sqlx::Error::Database(err) if err.is_unique_violation() => match err.constraint() {
-            Some(constraint) => AppError::Conflict(format!("{} already exists.", constraint)),
-            None => AppError::Conflict("Unique constraint violation.".to_string()),
-        },
-        _ => AppError::DatabaseError(e),
-    })?;
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
+    .try_into()?;
 
-    Ok(user_id)
+    Ok(result)
 }
 
-pub async fn find_all_user(
-    pool: &PgPool,
-    query_pagination: QueryPagination,
-) -> Result<ResultWithPagination<Vec<GetUserDTO>>, AppError> {
-    let (limit, offset, page, order) = query_pagination.paginate();
+pub async fn find_user_for_update(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<GetUserDTO, AppError> {
+    let result: GetUserDTO = sqlx::query_as::<_, User>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            users
+        WHERE
+            id = $1 AND status != $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
+    .try_into()?;
 
-    let count: i64 = sqlx::query_scalar::<_, i64>(
-        "--sql
+    Ok(result)
+}
+
+/// Locks the user in any status and returns it as it is before a mutation, the
+/// snapshot the audit log diffs against.
+pub async fn find_user_snapshot(
+    conn: &mut PgConnection,
+    id: Uuid,
+) -> Result<Option<GetUserDTO>, AppError> {
+    let result = sqlx::query_as::<_, User>(
+        r#"--sql
         SELECT
-            COUNT(*)
+            *
         FROM
             users
-        ",
+        WHERE
+            id = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    result.map(GetUserDTO::try_from).transpose()
+}
+
+/// Reconstructs the user as it was at `as_of`, whatever its status was then.
+pub async fn find_user_as_of(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+    as_of: DateTime<Utc>,
+) -> Result<GetUserDTO, AppError> {
+    let past = sqlx::query_as::<_, User>(
+        r#"--sql
+        SELECT
+            (jsonb_populate_record(NULL::users, record)).*
+        FROM
+            users_history
+        WHERE
+            id = $1
+            AND valid_from <= $2
+            AND valid_to > $2
+            AND in_tenant(id, $3)
+        ORDER BY
+            history_id DESC
+        LIMIT 1
+        "#,
     )
-    .fetch_one(pool)
+    .bind(id)
+    .bind(as_of)
+    .bind(tenant.organization_id())
+    .fetch_optional(&mut *conn)
     .await
     .map_err(AppError::DatabaseError)?;
 
-    let mut query = String::new();
-    for (key, value) in order.iter() {
-        query = format!(
-            "--sql
-            SELECT 
-                *
+    if let Some(user) = past {
+        return GetUserDTO::try_from(user);
+    }
+
+    // History covers everything from creation up to the last change, so a user
+    // created before `as_of` without a matching version is in its current state.
+    let result: GetUserDTO = sqlx::query_as::<_, User>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            users
+        WHERE
+            id = $1
+            AND created_at <= $2
+            AND in_tenant(id, $3)
+        "#,
+    )
+    .bind(id)
+    .bind(as_of)
+    .bind(tenant.organization_id())
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "User with ID {} not found at {}",
+        id,
+        as_of.to_rfc3339()
+    )))?
+    .try_into()?;
+
+    Ok(result)
+}
+
+pub async fn find_user_history(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+    limit: i64,
+    offset: i64,
+    page: i64,
+) -> Result<ResultWithPagination<Vec<UserVersionDTO>>, AppError> {
+    // An unchanged user has no versions yet, tell it apart from an unknown one.
+    let exists: bool = sqlx::query_scalar::<_, bool>(
+        r#"--sql
+        SELECT EXISTS (
+            SELECT
+                1
             FROM
                 users
-            WHERE 
-                status = $1
-            ORDER BY {} {}
-            LIMIT $2 OFFSET $3
-            ",
-            key, value
-        );
+            WHERE
+                id = $1
+                AND in_tenant(id, $2)
+        )
+        "#,
+    )
+    .bind(id)
+    .bind(tenant.organization_id())
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    if !exists {
+        return Err(AppError::NotFound(format!("User with ID {} not found", id)));
     }
 
-    let result: Vec<GetUserDTO> = sqlx::query_as::<_, User>(&query)
-        .bind(UserStatus::ACTIVE)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
-        .await
-        .map_err(AppError::DatabaseError)?
-        .into_iter()
-        .map(|user| user.into())
-        .collect();
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(*)
+        FROM
+            users_history
+        WHERE
+            id = $1
+            AND in_tenant(id, $2)
+        "#,
+    )
+    .bind(id)
+    .bind(tenant.organization_id())
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    let result: Vec<UserVersionDTO> = sqlx::query_as::<_, UserVersionRow>(
+        r#"--sql
+        SELECT
+            history_id AS version,
+            operation,
+            valid_from,
+            valid_to,
+            (jsonb_populate_record(NULL::users, record)).*
+        FROM
+            users_history
+        WHERE
+            id = $1
+            AND in_tenant(id, $4)
+        ORDER BY
+            history_id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(id)
+    .bind(limit)
+    .bind(offset)
+    .bind(tenant.organization_id())
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .into_iter()
+    .map(|row| row.try_into())
+    .collect::<Result<_, _>>()?;
 
     Ok(ResultWithPagination::new(
         limit,
@@ -238,66 +474,1513 @@ pub async fn find_all_user(
     ))
 }
 
-pub async fn delete_user_with_status(pool: &PgPool, id: Uuid) -> Result<GetUserDTO, AppError> {
+pub async fn replace_user(
+    conn: &mut PgConnection,
+    id: Uuid,
+    document: PatchUserDocument,
+) -> Result<GetUserDTO, AppError> {
     let result: GetUserDTO = sqlx::query_as::<_, User>(
         r#"--sql
         UPDATE
             users
-        SET 
-            status = $1,
-            updated_at = $2,
-            deleted_at = $3
-        WHERE 
-            id = $4 AND status != $5
-        RETURNING 
-           *
+        SET
+            name = $1,
+            display_name = $2,
+            phone = $3,
+            locale = $4,
+            timezone = $5,
+            bio = $6,
+            updated_at = $7
+        WHERE
+            id = $8
+        RETURNING
+            *
         "#,
     )
-    .bind(UserStatus::DELETED)
-    .bind(Utc::now())
+    .bind(document.name)
+    .bind(document.display_name)
+    .bind(document.phone)
+    .bind(document.locale)
+    .bind(document.timezone)
+    .bind(document.bio)
     .bind(Utc::now())
     .bind(id)
-    .bind(UserStatus::DELETED)
-    .fetch_optional(pool)
+    .fetch_optional(conn)
     .await
-    .map_err(AppError::DatabaseError)?
+    .map_err(|e| match e {
+        sqlx::Error::Database(err) if err.is_unique_violation() => match err.constraint() {
+            Some(constraint) => AppError::Conflict(format!("{} already exists.", constraint)),
+            None => AppError::Conflict("Unique constraint violation.".to_string()),
+        },
+        _ => AppError::DatabaseError(e),
+    })?
     .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
-    .into();
+    .try_into()?;
 
     Ok(result)
 }
 
-pub async fn update_user_password(
-    pool: &PgPool,
-    id: Uuid,
-    payload: UpdateUserPasswordDto,
-) -> Result<GetUserDTO, AppError> {
+pub async fn create_user(
+    conn: &mut PgConnection,
+    payload: CreateUserDTO,
+) -> Result<Uuid, AppError> {
     let User {
+        id,
+        name,
+        email,
         password,
+        status,
+        role,
+        created_at,
         updated_at,
+        deleted_at,
         ..
     } = payload.into();
 
-    let result: GetUserDTO = sqlx::query_as::<_, User>(
+    let user_id: Uuid = sqlx::query_scalar(
         r#"--sql
-        UPDATE
-            users
-        SET 
-            password = $1 
-            updated_at = $2
-        WHERE 
-            id = $3
-        RETURNING 
-           *
+        INSERT INTO
+            users (id, name, email, password, status, role, created_at, updated_at, deleted_at)
+        VALUES
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id
         "#,
     )
+    .bind(id)
+    .bind(name)
+    .bind(email)
     .bind(password)
+    .bind(status)
+    .bind(role)
+    .bind(created_at)
     .bind(updated_at)
-    .fetch_optional(pool)
+    .bind(deleted_at)
+    .fetch_one(conn)
     .await
-    .map_err(AppError::DatabaseError)?
-    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
-    .into();
+    .map_err(|e| match e {
+        sqlx::Error::Database(err) if err.is_unique_violation() => match err.constraint() {
+            Some(constraint) => AppError::Conflict(format!("{} already exists.", constraint)),
+            None => AppError::Conflict("Unique constraint violation.".to_string()),
+        },
+        _ => AppError::DatabaseError(e),
+    })?;
+
+    Ok(user_id)
+}
+
+/// Activates a PENDING account with its first password.
+pub async fn set_initial_password(
+    conn: &mut PgConnection,
+    id: Uuid,
+    password: String,
+) -> Result<GetUserDTO, AppError> {
+    let result: GetUserDTO = sqlx::query_as::<_, User>(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            password = $1,
+            status = $2,
+            updated_at = $3
+        WHERE
+            id = $4 AND status = $5 AND password = $6
+        RETURNING
+            *
+        "#,
+    )
+    .bind(password)
+    .bind(UserStatus::ACTIVE)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(UserStatus::PENDING)
+    .bind(UNUSABLE_PASSWORD)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::Conflict(
+        "Invitation has already been accepted.".to_string(),
+    ))?
+    .try_into()?;
+
+    Ok(result)
+}
+
+pub async fn find_existing_emails(
+    conn: &mut PgConnection,
+    emails: &[String],
+) -> Result<Vec<String>, AppError> {
+    let result: Vec<String> = sqlx::query_scalar(
+        r#"--sql
+        SELECT
+            email
+        FROM
+            users
+        WHERE
+            email = ANY($1)
+        "#,
+    )
+    .bind(emails)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Inserts users with a single multi-row statement, rows whose email already exists
+/// are skipped and missing from the returned `(id, email)` list.
+pub async fn insert_users_batch(
+    conn: &mut PgConnection,
+    users: Vec<User>,
+) -> Result<Vec<GetUserDTO>, AppError> {
+    if users.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query_builder = QueryBuilder::new(
+        "INSERT INTO users (id, name, email, password, status, role, created_at, updated_at) ",
+    );
+
+    query_builder.push_values(users, |mut row, user| {
+        row.push_bind(user.id)
+            .push_bind(user.name)
+            .push_bind(user.email)
+            .push_bind(user.password)
+            .push_bind(user.status)
+            .push_bind(user.role)
+            .push_bind(user.created_at)
+            .push_bind(user.updated_at);
+    });
+    query_builder.push(" ON CONFLICT (email) DO NOTHING RETURNING *");
+
+    let result: Vec<GetUserDTO> = query_builder
+        .build_query_as::<User>()
+        .fetch_all(conn)
+        .await
+        .map_err(AppError::DatabaseError)?
+        .into_iter()
+        .map(GetUserDTO::try_from)
+        .collect::<Result<_, _>>()?;
+
+    Ok(result)
+}
+
+pub async fn find_all_user(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    query_pagination: QueryPagination,
+    filter: UserFilter,
+    fieldset: &Fieldset,
+) -> Result<ResultWithPagination<Vec<GetUserDTO>>, AppError> {
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let sort_keys = query_pagination.sort_keys::<GetUserDTO>()?;
+    let cursor = query_pagination.cursor(&sort_keys)?;
+
+    let mut count_query = QueryBuilder::new("SELECT COUNT(*) FROM users");
+    filter.push_where(&mut count_query);
+    tenant.push_condition(&mut count_query, "id");
+
+    let count: i64 = count_query
+        .build_query_scalar::<i64>()
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    let sort_columns: Vec<&'static str> = sort_keys.iter().map(|key| key.column).collect();
+    let mut query_builder = QueryBuilder::new(format!(
+        "SELECT {} FROM users",
+        fieldset.columns::<GetUserDTO>(&sort_columns)
+    ));
+    filter.push_where(&mut query_builder);
+    tenant.push_condition(&mut query_builder, "id");
+
+    match &cursor {
+        Some(cursor) => {
+            cursor.push_condition(&mut query_builder, &sort_keys);
+            push_order_by(&mut query_builder, &cursor.query_keys(&sort_keys));
+        }
+        None => push_order_by(&mut query_builder, &sort_keys),
+    }
+
+    query_builder.push(" LIMIT ").push_bind(limit + 1);
+    query_builder.push(" OFFSET ").push_bind(offset);
+
+    let rows: Vec<GetUserDTO> = query_builder
+        .build_query_as::<User>()
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(AppError::DatabaseError)?
+        .into_iter()
+        .map(|user| GetUserDTO::from_row(user, fieldset))
+        .collect::<Result<_, _>>()?;
+
+    let (result, next_cursor, prev_cursor) =
+        split_page(rows, limit, &sort_keys, cursor.as_ref(), offset > 0);
+
+    Ok(
+        ResultWithPagination::new(limit, page, count, result.len(), result)
+            .with_cursors(next_cursor, prev_cursor),
+    )
+}
+
+/// Streams every user matching `filter` into `sender` as rows arrive from the database.
+/// Stops early once the receiving side (the HTTP response) has been dropped.
+pub async fn stream_users(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    filter: &UserFilter,
+    mut sender: Sender<Result<GetUserDTO, AppError>>,
+) -> Result<(), AppError> {
+    let mut query_builder = QueryBuilder::new(format!(
+        "SELECT {} FROM users",
+        Fieldset::all().columns::<GetUserDTO>(&[])
+    ));
+    filter.push_where(&mut query_builder);
+    tenant.push_condition(&mut query_builder, "id");
+    query_builder.push(" ORDER BY created_at ASC, id ASC");
+
+    let mut rows = query_builder.build_query_as::<User>().fetch(&mut *conn);
+
+    while let Some(user) = rows.try_next().await.map_err(AppError::DatabaseError)? {
+        if sender.send(GetUserDTO::try_from(user)).await.is_err() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+// Lower than the pg_trgm default (0.6) so misspelled names still match.
+const SEARCH_SIMILARITY_THRESHOLD: &str = "0.3";
+
+pub async fn search_users(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    search: &str,
+    limit: i64,
+    offset: i64,
+    page: i64,
+) -> Result<ResultWithPagination<Vec<SearchUserDTO>>, AppError> {
+    let mut tx = conn.begin().await.map_err(AppError::DatabaseError)?;
+
+    sqlx::query("SELECT set_config('pg_trgm.word_similarity_threshold', $1, true)")
+        .bind(SEARCH_SIMILARITY_THRESHOLD)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(*)
+        FROM
+            users
+        WHERE
+            status = $1
+            AND (
+                $2 <% name
+                OR $2 <% email
+                OR search_vector @@ plainto_tsquery('simple', $2)
+            )
+            AND in_tenant(id, $3)
+        "#,
+    )
+    .bind(UserStatus::ACTIVE)
+    .bind(search)
+    .bind(tenant.organization_id())
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    let result: Vec<SearchUserDTO> = sqlx::query_as::<_, SearchUserRow>(
+        r#"--sql
+        SELECT
+            *,
+            GREATEST(
+                word_similarity($2, name),
+                word_similarity($2, email),
+                ts_rank(search_vector, plainto_tsquery('simple', $2))
+            ) AS rank,
+            ts_headline(
+                'simple',
+                html_escape(name || ' ' || email),
+                plainto_tsquery('simple', $2),
+                'StartSel=<mark>, StopSel=</mark>, HighlightAll=true'
+            ) AS highlight
+        FROM
+            users
+        WHERE
+            status = $1
+            AND (
+                $2 <% name
+                OR $2 <% email
+                OR search_vector @@ plainto_tsquery('simple', $2)
+            )
+            AND in_tenant(id, $5)
+        ORDER BY
+            rank DESC, id
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(UserStatus::ACTIVE)
+    .bind(search)
+    .bind(limit)
+    .bind(offset)
+    .bind(tenant.organization_id())
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .into_iter()
+    .map(|row| row.try_into())
+    .collect::<Result<_, _>>()?;
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResultWithPagination::new(
+        limit,
+        page,
+        count,
+        result.len(),
+        result,
+    ))
+}
+
+pub async fn delete_user_with_status(
+    conn: &mut PgConnection,
+    id: Uuid,
+    versions: Option<Vec<DateTime<Utc>>>,
+) -> Result<GetUserDTO, AppError> {
+    let result = sqlx::query_as::<_, User>(
+        r#"--sql
+        UPDATE
+            users
+        SET 
+            status = $1,
+            updated_at = $2,
+            deleted_at = $3
+        WHERE 
+            id = $4 AND status != $5
+            AND ($6::timestamptz[] IS NULL OR updated_at = ANY($6))
+        RETURNING 
+           *
+        "#,
+    )
+    .bind(UserStatus::DELETED)
+    .bind(Utc::now())
+    .bind(Utc::now())
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .bind(versions)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    match result {
+        Some(user) => GetUserDTO::try_from(user),
+        None => Err(version_mismatch_error(conn, id, false).await),
+    }
+}
+
+/// Locks the user and returns its password hash, deleted users have none to change.
+pub async fn find_user_password(conn: &mut PgConnection, id: Uuid) -> Result<String, AppError> {
+    let result = sqlx::query_scalar::<_, String>(
+        r#"--sql
+        SELECT
+            password
+        FROM
+            users
+        WHERE
+            id = $1 AND status != $2
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    Ok(result)
+}
+
+pub async fn update_user_password(
+    conn: &mut PgConnection,
+    id: Uuid,
+    password: String,
+) -> Result<GetUserDTO, AppError> {
+    let result: GetUserDTO = sqlx::query_as::<_, User>(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            password = $1,
+            updated_at = $2
+        WHERE
+            id = $3 AND status != $4
+        RETURNING
+            *
+        "#,
+    )
+    .bind(password)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
+    .try_into()?;
+
+    Ok(result)
+}
+
+pub async fn count_purgeable_users(
+    conn: &mut PgConnection,
+    cutoff: DateTime<Utc>,
+) -> Result<i64, AppError> {
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(*)
+        FROM
+            users
+        WHERE
+            status = $1 AND deleted_at < $2 AND anonymized_at IS NULL
+        "#,
+    )
+    .bind(UserStatus::DELETED)
+    .bind(cutoff)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(count)
+}
+
+/// Locks the next batch of soft-deleted users whose retention has passed.
+pub async fn lock_purgeable_users(
+    conn: &mut PgConnection,
+    cutoff: DateTime<Utc>,
+    limit: i64,
+) -> Result<Vec<Uuid>, AppError> {
+    let ids: Vec<Uuid> = sqlx::query_scalar(
+        r#"--sql
+        SELECT
+            id
+        FROM
+            users
+        WHERE
+            status = $1 AND deleted_at < $2 AND anonymized_at IS NULL
+        ORDER BY
+            deleted_at
+        LIMIT $3
+        FOR UPDATE SKIP LOCKED
+        "#,
+    )
+    .bind(UserStatus::DELETED)
+    .bind(cutoff)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(ids)
+}
+
+pub async fn purge_users(conn: &mut PgConnection, ids: &[Uuid]) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        DELETE FROM users
+        WHERE
+            id = ANY ($1)
+        "#,
+    )
+    .bind(ids)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Batch counterpart of [`anonymize_user`] for the purge job.
+pub async fn anonymize_users(conn: &mut PgConnection, ids: &[Uuid]) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            name = 'Deleted user',
+            email = 'deleted-' || id || '@anonymized.invalid',
+            password = $2,
+            display_name = NULL,
+            phone = NULL,
+            locale = NULL,
+            timezone = NULL,
+            bio = NULL,
+            avatar_updated_at = NULL,
+            metadata = '{}',
+            updated_at = $3,
+            anonymized_at = $3
+        WHERE
+            id = ANY ($1)
+        "#,
+    )
+    .bind(ids)
+    .bind(UNUSABLE_PASSWORD)
+    .bind(Utc::now())
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Scrubs every personal field of the user to a fixed placeholder and marks the row
+/// deleted and anonymized. The id is kept so rows referencing the user stay valid.
+pub async fn anonymize_user(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+    versions: Option<Vec<DateTime<Utc>>>,
+) -> Result<GetUserDTO, AppError> {
+    let result = sqlx::query_as::<_, User>(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            name = 'Deleted user',
+            email = 'deleted-' || id || '@anonymized.invalid',
+            password = $1,
+            display_name = NULL,
+            phone = NULL,
+            locale = NULL,
+            timezone = NULL,
+            bio = NULL,
+            avatar_updated_at = NULL,
+            metadata = '{}',
+            status = $2,
+            updated_at = $3,
+            deleted_at = COALESCE(deleted_at, $3),
+            anonymized_at = $3
+        WHERE
+            id = $4 AND anonymized_at IS NULL
+            AND ($5::timestamptz[] IS NULL OR updated_at = ANY($5))
+            AND in_tenant(id, $6)
+        RETURNING
+            *
+        "#,
+    )
+    .bind(UNUSABLE_PASSWORD)
+    .bind(UserStatus::DELETED)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(versions)
+    .bind(tenant.organization_id())
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    if let Some(user) = result {
+        return GetUserDTO::try_from(user);
+    }
+
+    let anonymized = sqlx::query_scalar::<_, bool>(
+        r#"--sql
+        SELECT
+            anonymized_at IS NOT NULL
+        FROM
+            users
+        WHERE
+            id = $1
+            AND in_tenant(id, $2)
+        "#,
+    )
+    .bind(id)
+    .bind(tenant.organization_id())
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Err(match anonymized {
+        None => AppError::NotFound(format!("User with ID {} not found", id)),
+        Some(true) => AppError::Conflict(format!("User with ID {} has been anonymized", id)),
+        Some(false) => AppError::PreconditionFailed(format!(
+            "User with ID {} has been modified since it was retrieved",
+            id
+        )),
+    })
+}
+
+/// Removes email change requests of the user, they hold addresses the user owned.
+pub async fn delete_email_change_requests(
+    conn: &mut PgConnection,
+    ids: &[Uuid],
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        DELETE FROM email_change_requests
+        WHERE
+            user_id = ANY ($1)
+        "#,
+    )
+    .bind(ids)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Removes every export of the users, returns the storage keys of built archives.
+pub async fn delete_user_exports(
+    conn: &mut PgConnection,
+    ids: &[Uuid],
+) -> Result<Vec<String>, AppError> {
+    let keys: Vec<Option<String>> = sqlx::query_scalar(
+        r#"--sql
+        DELETE FROM user_exports
+        WHERE
+            user_id = ANY ($1)
+        RETURNING
+            storage_key
+        "#,
+    )
+    .bind(ids)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(keys.into_iter().flatten().collect())
+}
+
+/// Removes the past versions of the users, they hold the personal data the erasure
+/// is meant to remove.
+pub async fn delete_users_history(conn: &mut PgConnection, ids: &[Uuid]) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        DELETE FROM users_history
+        WHERE
+            id = ANY ($1)
+        "#,
+    )
+    .bind(ids)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn find_user_ids(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    filter: &UserFilter,
+    limit: i64,
+) -> Result<Vec<Uuid>, AppError> {
+    let mut query_builder = QueryBuilder::new("SELECT id FROM users");
+    filter.push_where(&mut query_builder);
+    tenant.push_condition(&mut query_builder, "id");
+    query_builder.push(" ORDER BY id LIMIT ").push_bind(limit);
+
+    let result = query_builder
+        .build_query_scalar::<Uuid>()
+        .fetch_all(conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Locks the user and returns its status and whether it has been anonymized.
+pub async fn lock_user_status(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+) -> Result<(UserStatus, bool), AppError> {
+    let result = sqlx::query_as::<_, (UserStatus, bool)>(
+        r#"--sql
+        SELECT
+            status, anonymized_at IS NOT NULL
+        FROM
+            users
+        WHERE
+            id = $1
+            AND in_tenant(id, $2)
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .bind(tenant.organization_id())
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    Ok(result)
+}
+
+/// Writes the status as is, transitions are checked by `users_service::transition_status`.
+pub async fn set_user_status(
+    conn: &mut PgConnection,
+    id: Uuid,
+    status: UserStatus,
+) -> Result<(), AppError> {
+    let deleted_at = match status {
+        UserStatus::DELETED => Some(Utc::now()),
+        _ => None,
+    };
+
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            status = $1,
+            updated_at = $2,
+            deleted_at = $3
+        WHERE
+            id = $4
+        "#,
+    )
+    .bind(status)
+    .bind(Utc::now())
+    .bind(deleted_at)
+    .bind(id)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn insert_status_transition(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    from_status: UserStatus,
+    to_status: UserStatus,
+    reason: Option<&str>,
+    actor_id: Option<Uuid>,
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        INSERT INTO
+            user_status_transitions (user_id, from_status, to_status, reason, actor_id)
+        VALUES
+            ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(user_id)
+    .bind(from_status)
+    .bind(to_status)
+    .bind(reason)
+    .bind(actor_id)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn find_status_transitions(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+    limit: i64,
+    offset: i64,
+    page: i64,
+) -> Result<ResultWithPagination<Vec<UserStatusTransition>>, AppError> {
+    // Counted through the user so unknown ids and other tenants read as missing.
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(user_status_transitions.id)
+        FROM
+            users
+            LEFT JOIN user_status_transitions ON user_status_transitions.user_id = users.id
+        WHERE
+            users.id = $1
+            AND in_tenant(users.id, $2)
+        GROUP BY
+            users.id
+        "#,
+    )
+    .bind(id)
+    .bind(tenant.organization_id())
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    let result = sqlx::query_as::<_, UserStatusTransition>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            user_status_transitions
+        WHERE
+            user_id = $1
+        ORDER BY
+            id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(ResultWithPagination::new(
+        limit,
+        page,
+        count,
+        result.len(),
+        result,
+    ))
+}
+
+/// Drops the transitions of erased users, their reasons may describe the person.
+pub async fn delete_status_transitions(
+    conn: &mut PgConnection,
+    ids: &[Uuid],
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        DELETE FROM user_status_transitions
+        WHERE
+            user_id = ANY($1)
+        "#,
+    )
+    .bind(ids)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Permanently deletes a soft-deleted user.
+pub async fn hard_delete_user(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+) -> Result<(), AppError> {
+    let result = sqlx::query(
+        r#"--sql
+        DELETE FROM users
+        WHERE
+            id = $1
+            AND status = $2
+            AND in_tenant(id, $3)
+        "#,
+    )
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .bind(tenant.organization_id())
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("User with ID {} not found", id)));
+    }
+
+    Ok(())
+}
+
+pub async fn find_user_metadata(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+) -> Result<Value, AppError> {
+    let result = sqlx::query_scalar::<_, Value>(
+        r#"--sql
+        SELECT
+            metadata
+        FROM
+            users
+        WHERE
+            id = $1 AND status != $2
+            AND in_tenant(id, $3)
+        "#,
+    )
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .bind(tenant.organization_id())
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    Ok(result)
+}
+
+/// Sets one top-level metadata key and returns the whole updated document.
+pub async fn set_user_metadata_key(
+    conn: &mut PgConnection,
+    id: Uuid,
+    key: &str,
+    value: Value,
+) -> Result<Value, AppError> {
+    let result = sqlx::query_scalar::<_, Value>(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            metadata = jsonb_set(metadata, ARRAY[$1], $2, true),
+            updated_at = $3
+        WHERE
+            id = $4 AND status = $5
+        RETURNING
+            metadata
+        "#,
+    )
+    .bind(key)
+    .bind(value)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(UserStatus::ACTIVE)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    Ok(result)
+}
+
+/// Removes one top-level metadata key, `None` when the user has no such key.
+pub async fn delete_user_metadata_key(
+    conn: &mut PgConnection,
+    id: Uuid,
+    key: &str,
+) -> Result<Option<Value>, AppError> {
+    let result = sqlx::query_scalar::<_, Value>(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            metadata = metadata - $1,
+            updated_at = $2
+        WHERE
+            id = $3 AND status = $4 AND metadata ? $1
+        RETURNING
+            metadata
+        "#,
+    )
+    .bind(key)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(UserStatus::ACTIVE)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn find_user_preferences(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+) -> Result<UserPreferencesRow, AppError> {
+    let result = sqlx::query_as::<_, UserPreferencesRow>(
+        r#"--sql
+        SELECT
+            user_preferences.schema_version,
+            user_preferences.document,
+            user_preferences.updated_at
+        FROM
+            users
+            LEFT JOIN user_preferences ON user_preferences.user_id = users.id
+        WHERE
+            users.id = $1 AND users.status != $2
+            AND in_tenant(users.id, $3)
+        "#,
+    )
+    .bind(id)
+    .bind(UserStatus::DELETED)
+    .bind(tenant.organization_id())
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    Ok(result)
+}
+
+/// Replaces the stored preferences document of the user, returns when it was written.
+pub async fn upsert_user_preferences(
+    conn: &mut PgConnection,
+    id: Uuid,
+    schema_version: i32,
+    document: Value,
+) -> Result<DateTime<Utc>, AppError> {
+    let result = sqlx::query_scalar::<_, DateTime<Utc>>(
+        r#"--sql
+        INSERT INTO
+            user_preferences (user_id, schema_version, document, updated_at)
+        SELECT
+            id, $2, $3, $4
+        FROM
+            users
+        WHERE
+            id = $1 AND status = $5
+        ON CONFLICT (user_id) DO UPDATE
+        SET
+            schema_version = EXCLUDED.schema_version,
+            document = EXCLUDED.document,
+            updated_at = EXCLUDED.updated_at
+        RETURNING
+            updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(schema_version)
+    .bind(document)
+    .bind(Utc::now())
+    .bind(UserStatus::ACTIVE)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?;
+
+    Ok(result)
+}
+
+pub async fn delete_user_preferences(
+    conn: &mut PgConnection,
+    ids: &[Uuid],
+) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        DELETE FROM user_preferences
+        WHERE
+            user_id = ANY($1)
+        "#,
+    )
+    .bind(ids)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Replaces any pending email change of the user with a new one.
+pub async fn create_email_change_request(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    new_email: &str,
+    confirm_token_hash: &str,
+    cancel_token_hash: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<EmailChangeRequest, AppError> {
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            email_change_requests
+        SET
+            cancelled_at = $1
+        WHERE
+            user_id = $2 AND confirmed_at IS NULL AND cancelled_at IS NULL
+        "#,
+    )
+    .bind(Utc::now())
+    .bind(user_id)
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    let result = sqlx::query_as::<_, EmailChangeRequest>(
+        r#"--sql
+        INSERT INTO
+            email_change_requests (user_id, new_email, confirm_token_hash, cancel_token_hash, expires_at)
+        VALUES
+            ($1, $2, $3, $4, $5)
+        RETURNING
+            id, user_id, new_email, created_at, expires_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(new_email)
+    .bind(confirm_token_hash)
+    .bind(cancel_token_hash)
+    .bind(expires_at)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Pending, unexpired email change matching `confirm_token_hash`, for the page that
+/// asks to confirm it.
+pub async fn find_email_change_to_confirm(
+    conn: &mut PgConnection,
+    confirm_token_hash: &str,
+) -> Result<EmailChangeRequest, AppError> {
+    let result = sqlx::query_as::<_, EmailChangeRequest>(
+        r#"--sql
+        SELECT
+            id, user_id, new_email, created_at, expires_at
+        FROM
+            email_change_requests
+        WHERE
+            confirm_token_hash = $1
+            AND confirmed_at IS NULL AND cancelled_at IS NULL
+            AND expires_at > $2
+        "#,
+    )
+    .bind(confirm_token_hash)
+    .bind(Utc::now())
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::BadRequest(
+        "Email change link is invalid or has expired.".to_string(),
+    ))?;
+
+    Ok(result)
+}
+
+/// Pending email change matching `cancel_token_hash`, for the page that asks to
+/// cancel it.
+pub async fn find_email_change_to_cancel(
+    conn: &mut PgConnection,
+    cancel_token_hash: &str,
+) -> Result<EmailChangeRequest, AppError> {
+    let result = sqlx::query_as::<_, EmailChangeRequest>(
+        r#"--sql
+        SELECT
+            id, user_id, new_email, created_at, expires_at
+        FROM
+            email_change_requests
+        WHERE
+            cancel_token_hash = $1 AND confirmed_at IS NULL AND cancelled_at IS NULL
+        "#,
+    )
+    .bind(cancel_token_hash)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::BadRequest(
+        "Email change link is invalid or the change is no longer pending.".to_string(),
+    ))?;
+
+    Ok(result)
+}
+
+/// Locks the pending, unexpired email change matching `confirm_token_hash` and marks
+/// it confirmed, the caller applies the new email in the same transaction.
+pub async fn confirm_email_change_request(
+    conn: &mut PgConnection,
+    confirm_token_hash: &str,
+) -> Result<EmailChangeRequest, AppError> {
+    let result = sqlx::query_as::<_, EmailChangeRequest>(
+        r#"--sql
+        UPDATE
+            email_change_requests
+        SET
+            confirmed_at = $1
+        WHERE
+            confirm_token_hash = $2
+            AND confirmed_at IS NULL AND cancelled_at IS NULL
+            AND expires_at > $1
+        RETURNING
+            id, user_id, new_email, created_at, expires_at
+        "#,
+    )
+    .bind(Utc::now())
+    .bind(confirm_token_hash)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::BadRequest(
+        "Email change link is invalid or has expired.".to_string(),
+    ))?;
+
+    Ok(result)
+}
+
+pub async fn cancel_email_change_request(
+    conn: &mut PgConnection,
+    cancel_token_hash: &str,
+) -> Result<EmailChangeRequest, AppError> {
+    let result = sqlx::query_as::<_, EmailChangeRequest>(
+        r#"--sql
+        UPDATE
+            email_change_requests
+        SET
+            cancelled_at = $1
+        WHERE
+            cancel_token_hash = $2 AND confirmed_at IS NULL AND cancelled_at IS NULL
+        RETURNING
+            id, user_id, new_email, created_at, expires_at
+        "#,
+    )
+    .bind(Utc::now())
+    .bind(cancel_token_hash)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::BadRequest(
+        "Email change link is invalid or the change is no longer pending.".to_string(),
+    ))?;
+
+    Ok(result)
+}
+
+pub async fn update_user_email(
+    conn: &mut PgConnection,
+    id: Uuid,
+    email: &str,
+) -> Result<GetUserDTO, AppError> {
+    let result: GetUserDTO = sqlx::query_as::<_, User>(
+        r#"--sql
+        UPDATE
+            users
+        SET
+            email = $1,
+            updated_at = $2
+        WHERE
+            id = $3 AND status = $4
+        RETURNING
+            *
+        "#,
+    )
+    .bind(email)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(UserStatus::ACTIVE)
+    .fetch_optional(conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(err) if err.is_unique_violation() => {
+            AppError::Conflict("Email is already in use by another account.".to_string())
+        }
+        _ => AppError::DatabaseError(e),
+    })?
+    .ok_or(AppError::NotFound(format!("User with ID {} not found", id)))?
+    .try_into()?;
+
+    Ok(result)
+}
+
+pub async fn find_email_change_requests(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<EmailChangeRequest>, AppError> {
+    let result = sqlx::query_as::<_, EmailChangeRequest>(
+        r#"--sql
+        SELECT
+            id, user_id, new_email, created_at, expires_at
+        FROM
+            email_change_requests
+        WHERE
+            user_id = $1
+        ORDER BY
+            created_at
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Queues an export for the user, or returns the one already queued or running.
+pub async fn create_user_export(
+    conn: &mut PgConnection,
+    user_id: Uuid,
+    format: UserExportFormat,
+) -> Result<UserExport, AppError> {
+    let created = sqlx::query_as::<_, UserExport>(
+        r#"--sql
+        INSERT INTO
+            user_exports (user_id, format)
+        VALUES
+            ($1, $2)
+        ON CONFLICT (user_id) WHERE status IN ('PENDING', 'PROCESSING') DO NOTHING
+        RETURNING
+            *
+        "#,
+    )
+    .bind(user_id)
+    .bind(format)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    if let Some(export) = created {
+        return Ok(export);
+    }
+
+    let result = sqlx::query_as::<_, UserExport>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            user_exports
+        WHERE
+            user_id = $1 AND status IN ($2, $3)
+        "#,
+    )
+    .bind(user_id)
+    .bind(UserExportStatus::PENDING)
+    .bind(UserExportStatus::PROCESSING)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn find_user_export(pool: &PgPool, id: Uuid) -> Result<UserExport, AppError> {
+    let result = sqlx::query_as::<_, UserExport>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            user_exports
+        WHERE
+            id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Export with ID {} not found",
+        id
+    )))?;
+
+    Ok(result)
+}
+
+/// Claims the oldest queued export, exports left `PROCESSING` since before
+/// `stale_before` belonged to a worker that died and are claimed again.
+pub async fn claim_user_export(
+    pool: &PgPool,
+    stale_before: DateTime<Utc>,
+) -> Result<Option<UserExport>, AppError> {
+    let result = sqlx::query_as::<_, UserExport>(
+        r#"--sql
+        UPDATE
+            user_exports
+        SET
+            status = $1,
+            started_at = $2
+        WHERE
+            id = (
+                SELECT
+                    id
+                FROM
+                    user_exports
+                WHERE
+                    status = $3 OR (status = $1 AND started_at < $4)
+                ORDER BY
+                    created_at
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+        RETURNING
+            *
+        "#,
+    )
+    .bind(UserExportStatus::PROCESSING)
+    .bind(Utc::now())
+    .bind(UserExportStatus::PENDING)
+    .bind(stale_before)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Returns `false` when the export is gone, e.g. the user was erased while it was built.
+pub async fn complete_user_export(
+    pool: &PgPool,
+    id: Uuid,
+    storage_key: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<bool, AppError> {
+    let result = sqlx::query(
+        r#"--sql
+        UPDATE
+            user_exports
+        SET
+            status = $1,
+            storage_key = $2,
+            completed_at = $3,
+            expires_at = $4
+        WHERE
+            id = $5
+        "#,
+    )
+    .bind(UserExportStatus::COMPLETED)
+    .bind(storage_key)
+    .bind(Utc::now())
+    .bind(expires_at)
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn fail_user_export(pool: &PgPool, id: Uuid, error: &str) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            user_exports
+        SET
+            status = $1,
+            error = $2,
+            completed_at = $3
+        WHERE
+            id = $4
+        "#,
+    )
+    .bind(UserExportStatus::FAILED)
+    .bind(error)
+    .bind(Utc::now())
+    .bind(id)
+    .execute(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Marks completed exports past `expires_at` as expired, returns them so the
+/// caller can remove the archives from storage.
+pub async fn expire_user_exports(pool: &PgPool) -> Result<Vec<UserExport>, AppError> {
+    let result = sqlx::query_as::<_, UserExport>(
+        r#"--sql
+        UPDATE
+            user_exports
+        SET
+            status = $1
+        WHERE
+            status = $2 AND expires_at <= $3
+        RETURNING
+            *
+        "#,
+    )
+    .bind(UserExportStatus::EXPIRED)
+    .bind(UserExportStatus::COMPLETED)
+    .bind(Utc::now())
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
 
     Ok(result)
 }
@@ -1,33 +1,177 @@
 use crate::{
+    audit::{audit_query, entity::AuditAction},
+    auth::{
+        auth_service::{CANCEL_EMAIL_CHANGE_PATH, CONFIRM_EMAIL_CHANGE_PATH},
+        dto::Claims,
+    },
+    events::{entity::DomainEvent, events_query},
+    server::AppState,
     users::{
-        dto::{GetUserDTO, UpdateUserDTO},
+        dto::{
+            preferences_users_dto::UserPreferencesRow, CreateDataExportDTO, DataExportDTO,
+            GetUserDTO, MetadataEntryDTO, PatchUserDocument, RequestEmailChangeDTO, SearchUserDTO,
+            SearchUserQuery, UpdateUserDTO, UpdateUserPasswordDto, UserFilter, UserPatch,
+            UserPreferencesDTO, UserVersionDTO,
+        },
+        entity::{
+            EmailChangeRequest, Preferences, UserExport, UserExportStatus, UserRole, UserStatus,
+        },
         users_query,
     },
     utils::{
-        auth::validate_user_id_in_token,
+        audit::{change, diff, redact_before, AuditContext},
+        auth::{
+            validate_org_admin_in_token, validate_owner_or_admin_in_token,
+            validate_user_id_in_token,
+        },
+        avatar::{avatar_key, render_avatars, AvatarSize},
         errors::AppError,
+        etag::{etag_for, required_versions},
+        jwt::verify_download_jwt,
+        mailer::Mail,
+        metadata::validate_metadata_key,
+        password::{hash_password, verify_password, UNUSABLE_PASSWORD},
+        preferences::{self, PREFERENCES_SCHEMA_VERSION},
+        query_fields::{Fieldset, QueryFields},
         query_paginaton::QueryPagination,
         response_data::{ResponseData, ResponseDatas},
+        storage::Storage,
+        tenant::Tenant,
+        token::{generate_token, hash_token},
     },
 };
-use actix_web::HttpRequest;
-use sqlx::PgPool;
+use actix_multipart::Multipart;
+use actix_web::{http::header::EntityTag, web, HttpRequest};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::{json, Map, Value};
+use sqlx::{PgConnection, PgPool};
 use uuid::Uuid;
 use validator::Validate;
 
-pub async fn find(pool: &PgPool, id: Uuid) -> Result<ResponseData<GetUserDTO>, AppError> {
-    let result = users_query::find_user(pool, id).await?;
-    Ok(ResponseData::new(
-        result,
-        "Data has been successfuly retrieved.",
+pub async fn find(
+    pool: &PgPool,
+    id: Uuid,
+    query_fields: QueryFields,
+    as_of: Option<DateTime<Utc>>,
+    req: &HttpRequest,
+) -> Result<(ResponseData<Value>, EntityTag), AppError> {
+    let fieldset = query_fields.fieldset::<GetUserDTO>()?;
+    query_fields.includes::<GetUserDTO>()?;
+
+    let tenant = Tenant::for_target(req, &id)?;
+    let mut tx = tenant.begin(pool).await?;
+    let result = match as_of {
+        Some(as_of) => {
+            validate_owner_or_admin_in_token(req, &id)?;
+            users_query::find_user_as_of(&mut tx, tenant, id, as_of).await?
+        }
+        None => users_query::find_user(&mut tx, tenant, id, &fieldset).await?,
+    };
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+    let etag = etag_for(result.updated_at, fieldset.signature().as_deref());
+
+    Ok((
+        ResponseData::new(
+            fieldset.project(&result)?,
+            "Data has been successfuly retrieved.",
+        ),
+        etag,
     ))
 }
 
 pub async fn find_all(
     pool: &PgPool,
     query_pagination: QueryPagination,
-) -> Result<ResponseDatas<Vec<GetUserDTO>>, AppError> {
-    let result = users_query::find_all_user(pool, query_pagination).await?;
+    filter: UserFilter,
+    query_fields: QueryFields,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<Value>>, AppError> {
+    let fieldset = query_fields.fieldset::<GetUserDTO>()?;
+    query_fields.includes::<GetUserDTO>()?;
+
+    // Suspended, locked, pending and deleted accounts are only listed for admins.
+    if filter
+        .status
+        .as_ref()
+        .is_some_and(|status| *status != UserStatus::ACTIVE)
+    {
+        validate_org_admin_in_token(req)?;
+    }
+
+    let tenant = Tenant::from_request(req)?;
+    let mut tx = tenant.begin(pool).await?;
+    let result =
+        users_query::find_all_user(&mut tx, tenant, query_pagination, filter, &fieldset).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+    let data = result
+        .data
+        .iter()
+        .map(|user| fieldset.project(user))
+        .collect::<Result<Vec<Value>, AppError>>()?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        data,
+    )
+    .with_cursors(result.next_cursor, result.prev_cursor))
+}
+
+pub async fn search(
+    pool: &PgPool,
+    mut search_query: SearchUserQuery,
+    query_pagination: QueryPagination,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<SearchUserDTO>>, AppError> {
+    search_query.q = search_query.q.trim().to_string();
+    search_query.validate().map_err(AppError::ValidationError)?;
+
+    let tenant = Tenant::from_request(req)?;
+
+    if query_pagination.after.is_some() || query_pagination.before.is_some() {
+        return Err(AppError::BadRequest(
+            "Cursor pagination is not supported for search.".to_string(),
+        ));
+    }
+
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let mut tx = tenant.begin(pool).await?;
+    let result =
+        users_query::search_users(&mut tx, tenant, &search_query.q, limit, offset, page).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        result.data,
+    ))
+}
+
+pub async fn find_history(
+    pool: &PgPool,
+    id: Uuid,
+    query_pagination: QueryPagination,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<UserVersionDTO>>, AppError> {
+    validate_owner_or_admin_in_token(req, &id)?;
+
+    if query_pagination.after.is_some() || query_pagination.before.is_some() {
+        return Err(AppError::BadRequest(
+            "Cursor pagination is not supported for history.".to_string(),
+        ));
+    }
+
+    let tenant = Tenant::for_target(req, &id)?;
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let mut tx = tenant.begin(pool).await?;
+    let result = users_query::find_user_history(&mut tx, tenant, id, limit, offset, page).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
 
     Ok(ResponseDatas::new(
         result.limit,
@@ -46,9 +190,112 @@ pub async fn update(
 ) -> Result<ResponseData<GetUserDTO>, AppError> {
     validate_user_id_in_token(req, &id)?;
 
+    if payload.email.is_some() {
+        return Err(AppError::BadRequest(format!(
+            "Email cannot be updated here, request a change with POST /api/V1/users/{}/email-change.",
+            id
+        )));
+    }
+
     payload.validate().map_err(AppError::ValidationError)?;
 
-    let result = users_query::update_user(pool, id, payload).await?;
+    let versions = required_versions(req)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let before = users_query::find_user_snapshot(&mut tx, id).await?;
+    let result = users_query::update_user(&mut tx, id, payload, versions).await?;
+    let changes = diff(before.as_ref(), Some(&result));
+    events_query::insert_event(&mut tx, DomainEvent::user_updated(id, &changes)).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserUpdated,
+        id,
+        changes,
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly updated.",
+    ))
+}
+
+/// Changes the password of the caller, who has to confirm the current one. Accounts
+/// without a password yet set one through their invitation instead.
+pub async fn update_password(
+    pool: &PgPool,
+    id: Uuid,
+    payload: UpdateUserPasswordDto,
+    req: &HttpRequest,
+) -> Result<ResponseData<GetUserDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let current = users_query::find_user_password(&mut tx, id).await?;
+    if current == UNUSABLE_PASSWORD {
+        return Err(AppError::InvalidCredentials("invalid password".to_string()));
+    }
+    verify_password(&payload.current_password, &current)?;
+
+    let password = hash_password(&payload.password)?;
+    let result = users_query::update_user_password(&mut tx, id, password).await?;
+    events_query::insert_event(&mut tx, DomainEvent::PasswordChanged { user_id: id }).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserPasswordChanged,
+        id,
+        json!({ "password": change("password", Value::Null, Value::Null) }),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly updated.",
+    ))
+}
+
+pub async fn patch(
+    pool: &PgPool,
+    id: Uuid,
+    patch: UserPatch,
+    req: &HttpRequest,
+) -> Result<ResponseData<GetUserDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+
+    let versions = required_versions(req)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+
+    let current = users_query::find_user_for_update(&mut tx, id).await?;
+
+    if let Some(versions) = versions {
+        if !versions.contains(&current.updated_at) {
+            return Err(AppError::PreconditionFailed(format!(
+                "User with ID {} has been modified since it was retrieved",
+                id
+            )));
+        }
+    }
+
+    let document = patch.apply(PatchUserDocument::from(current.clone()))?;
+    let result = users_query::replace_user(&mut tx, id, document).await?;
+    let changes = diff(Some(&current), Some(&result));
+    events_query::insert_event(&mut tx, DomainEvent::user_updated(id, &changes)).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserUpdated,
+        id,
+        changes,
+    )
+    .await?;
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
 
     Ok(ResponseData::new(
         result,
@@ -58,12 +305,43 @@ pub async fn update(
 
 pub async fn delete(
     pool: &PgPool,
+    app_state: &web::Data<AppState>,
     id: Uuid,
     req: &HttpRequest,
 ) -> Result<ResponseData<GetUserDTO>, AppError> {
     validate_user_id_in_token(req, &id)?;
 
-    let result = users_query::delete_user(pool, id).await?;
+    let versions = required_versions(req)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    // Written first, the event goes to the organizations the delete removes the user from.
+    events_query::insert_event(
+        &mut tx,
+        DomainEvent::UserDeleted {
+            user_id: id,
+            permanent: true,
+        },
+    )
+    .await?;
+    // Erased before the delete, the cascade would drop the export rows and with them
+    // the storage keys of their archives. Nothing is left to erase later, so the log
+    // keeps field names only.
+    let keys = erase_user_data(&mut tx, &[id]).await?;
+    let result = users_query::delete_user(&mut tx, id, versions).await?;
+    // The history trigger just copied the deleted row.
+    users_query::delete_users_history(&mut tx, &[id]).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserDeleted,
+        id,
+        redact_before(diff(Some(&result), None)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    delete_user_files(app_state.storage.as_ref(), keys).await;
+
     Ok(ResponseData::new(
         result,
         "Data has been successfuly deleted.",
@@ -77,9 +355,709 @@ pub async fn soft_delete(
 ) -> Result<ResponseData<GetUserDTO>, AppError> {
     validate_user_id_in_token(req, &id)?;
 
-    let result = users_query::delete_user_with_status(pool, id).await?;
+    let versions = required_versions(req)?;
+
+    let context = AuditContext::from_request(req);
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let before = users_query::find_user_snapshot(&mut tx, id).await?;
+    let result = users_query::delete_user_with_status(&mut tx, id, versions).await?;
+    events_query::insert_event(
+        &mut tx,
+        DomainEvent::UserDeleted {
+            user_id: id,
+            permanent: false,
+        },
+    )
+    .await?;
+    if let Some(before) = &before {
+        users_query::insert_status_transition(
+            &mut tx,
+            id,
+            before.status.clone(),
+            UserStatus::DELETED,
+            None,
+            context.actor_id,
+        )
+        .await?;
+    }
+    audit_query::insert_audit_event(
+        &mut tx,
+        &context,
+        AuditAction::UserSoftDeleted,
+        id,
+        diff(before.as_ref(), Some(&result)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly deleted.",
+    ))
+}
+
+/// Moves the user to `status` when its lifecycle allows it and records the transition
+/// with its reason. Callers check who may change the status and audit the change.
+pub async fn transition_status(
+    conn: &mut PgConnection,
+    tenant: Tenant,
+    id: Uuid,
+    status: UserStatus,
+    reason: Option<&str>,
+    context: &AuditContext,
+) -> Result<(), AppError> {
+    let (current, anonymized) = users_query::lock_user_status(conn, tenant, id).await?;
+
+    // Anonymized accounts stay deleted, there is nothing left to restore.
+    if anonymized {
+        return Err(AppError::Conflict(format!(
+            "User with ID {} has been anonymized",
+            id
+        )));
+    }
+
+    if current == status {
+        return Err(AppError::Conflict(format!(
+            "User with ID {} already has status {:?}",
+            id, status
+        )));
+    }
+
+    if !current.can_transition_to(&status) {
+        return Err(AppError::Conflict(format!(
+            "User with ID {} cannot change status from {:?} to {:?}",
+            id, current, status
+        )));
+    }
+
+    users_query::set_user_status(conn, id, status.clone()).await?;
+    users_query::insert_status_transition(conn, id, current, status, reason, context.actor_id).await
+}
+
+pub async fn anonymize(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<GetUserDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+
+    let versions = required_versions(req)?;
+    let result = erase(pool, app_state, Tenant::Unscoped, id, versions, req).await?;
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly anonymized.",
+    ))
+}
+
+/// Right-to-erasure: replaces the personal data of the user with placeholders and
+/// drops everything derived from it (email changes, exports, avatars, values in the
+/// audit log). The row is kept so references to the id stay valid. Callers check who
+/// may erase the user.
+pub async fn erase(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    tenant: Tenant,
+    id: Uuid,
+    versions: Option<Vec<DateTime<Utc>>>,
+    req: &HttpRequest,
+) -> Result<GetUserDTO, AppError> {
+    let mut tx = tenant.begin(pool).await?;
+    let before = users_query::find_user_snapshot(&mut tx, id).await?;
+    let user = users_query::anonymize_user(&mut tx, tenant, id, versions).await?;
+    events_query::insert_event(
+        &mut tx,
+        DomainEvent::UserDeleted {
+            user_id: id,
+            permanent: true,
+        },
+    )
+    .await?;
+    let keys = erase_user_data(&mut tx, &[id]).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserAnonymized,
+        id,
+        redact_before(diff(before.as_ref(), Some(&user))),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    delete_user_files(app_state.storage.as_ref(), keys).await;
+
+    Ok(user)
+}
+
+/// Drops everything derived from the personal data of the users: email changes,
+/// exports, history, status transitions, preferences and the values in the audit
+/// log. Shared by the erasure and the purge job. Runs after the users were anonymized
+/// since the history trigger copies every replaced row. Returns the storage keys to
+/// pass to [`delete_user_files`] once the transaction is committed.
+pub async fn erase_user_data(
+    conn: &mut PgConnection,
+    ids: &[Uuid],
+) -> Result<Vec<String>, AppError> {
+    users_query::delete_email_change_requests(conn, ids).await?;
+    let export_keys = users_query::delete_user_exports(conn, ids).await?;
+    users_query::delete_users_history(conn, ids).await?;
+    users_query::delete_status_transitions(conn, ids).await?;
+    users_query::delete_user_preferences(conn, ids).await?;
+    audit_query::redact_user_audit_events(conn, ids).await?;
+
+    let avatar_keys = ids
+        .iter()
+        .flat_map(|id| AvatarSize::ALL.iter().map(|size| avatar_key(*id, *size)));
+
+    Ok(avatar_keys.chain(export_keys).collect())
+}
+
+/// Files go after the commit, a failure here leaves an unreferenced file behind
+/// rather than personal data in the database.
+pub async fn delete_user_files(storage: &dyn Storage, keys: Vec<String>) {
+    for key in keys {
+        if let Err(err) = storage.delete(&key).await {
+            log::warn!(
+                "user_erasure event=cleanup_failed key={} error=\"{:?}\"",
+                key,
+                err
+            );
+        }
+    }
+}
+
+pub async fn update_avatar(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    payload: Multipart,
+    req: &HttpRequest,
+) -> Result<ResponseData<GetUserDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+
+    let upload = read_avatar_upload(payload, *app_state.avatar_max_size).await?;
+    let avatars = web::block(move || render_avatars(&upload))
+        .await
+        .map_err(|e| AppError::InternalServerError(e.to_string()))??;
+
+    for (size, bytes) in avatars {
+        app_state.storage.put(&avatar_key(id, size), bytes).await?;
+    }
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let before = users_query::find_user_snapshot(&mut tx, id).await?;
+    let result = users_query::touch_user_avatar(&mut tx, id).await?;
+    let changes = diff(before.as_ref(), Some(&result));
+    events_query::insert_event(&mut tx, DomainEvent::user_updated(id, &changes)).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserAvatarUpdated,
+        id,
+        changes,
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly updated.",
+    ))
+}
+
+/// Reads the `avatar` file field of a multipart upload, rejecting it as soon as it
+/// grows past `max_size` instead of buffering the whole body first.
+async fn read_avatar_upload(mut payload: Multipart, max_size: usize) -> Result<Vec<u8>, AppError> {
+    let invalid = |e: actix_multipart::MultipartError| AppError::BadRequest(e.to_string());
+
+    while let Some(mut field) = payload.try_next().await.map_err(invalid)? {
+        if field.name() != Some("avatar") {
+            continue;
+        }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        while let Some(chunk) = field.try_next().await.map_err(invalid)? {
+            if bytes.len() + chunk.len() > max_size {
+                return Err(AppError::PayloadTooLarge(format!(
+                    "Avatar must not be larger than {} bytes.",
+                    max_size
+                )));
+            }
+            bytes.extend_from_slice(&chunk);
+        }
+
+        return Ok(bytes);
+    }
+
+    Err(AppError::BadRequest(
+        "Multipart field 'avatar' is required.".to_string(),
+    ))
+}
+
+pub async fn find_avatar(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    size: AvatarSize,
+    req: &HttpRequest,
+) -> Result<(Vec<u8>, DateTime<Utc>), AppError> {
+    let not_found = || AppError::NotFound(format!("Avatar for user with ID {} not found", id));
+
+    let tenant = Tenant::for_target(req, &id)?;
+    let mut tx = tenant.begin(pool).await?;
+    let user = users_query::find_user(&mut tx, tenant, id, &Fieldset::all()).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+    let avatar_updated_at = user.avatar_updated_at.ok_or_else(not_found)?;
+
+    let bytes = app_state
+        .storage
+        .get(&avatar_key(id, size))
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok((bytes, avatar_updated_at))
+}
+
+pub async fn find_metadata(
+    pool: &PgPool,
+    id: Uuid,
+    key: Option<String>,
+    req: &HttpRequest,
+) -> Result<ResponseData<Value>, AppError> {
+    let tenant = Tenant::for_target(req, &id)?;
+    let mut tx = tenant.begin(pool).await?;
+    let metadata = users_query::find_user_metadata(&mut tx, tenant, id).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    let result = match key {
+        Some(key) => {
+            validate_metadata_key(&key)?;
+
+            let value = metadata
+                .get(&key)
+                .cloned()
+                .ok_or(AppError::NotFound(format!(
+                    "Metadata key '{}' not found",
+                    key
+                )))?;
+
+            serde_json::to_value(MetadataEntryDTO { key, value })
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?
+        }
+        None => metadata,
+    };
+
     Ok(ResponseData::new(
         result,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+pub async fn set_metadata(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    key: String,
+    value: Value,
+    req: &HttpRequest,
+) -> Result<ResponseData<MetadataEntryDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+    validate_metadata_key(&key)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+
+    let before = users_query::find_user_snapshot(&mut tx, id).await?;
+    let metadata = users_query::set_user_metadata_key(&mut tx, id, &key, value.clone()).await?;
+
+    // Validated on the stored document so concurrent writes to other keys are included.
+    if let Some(schema) = &app_state.metadata_schema {
+        schema.validate(&metadata)?;
+    }
+
+    let previous = before
+        .and_then(|user| user.metadata.get(&key).cloned())
+        .unwrap_or(Value::Null);
+    let changes = json!({ format!("metadata.{}", key): change(&key, previous, value.clone()) });
+    events_query::insert_event(&mut tx, DomainEvent::user_updated(id, &changes)).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserMetadataUpdated,
+        id,
+        changes,
+    )
+    .await?;
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        MetadataEntryDTO { key, value },
+        "Data has been successfuly updated.",
+    ))
+}
+
+pub async fn delete_metadata(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    key: String,
+    req: &HttpRequest,
+) -> Result<ResponseData<Value>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+    validate_metadata_key(&key)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+
+    let before = users_query::find_user_snapshot(&mut tx, id).await?;
+    let metadata = match users_query::delete_user_metadata_key(&mut tx, id, &key).await? {
+        Some(metadata) => metadata,
+        None => {
+            // Distinguish a missing user from a missing key.
+            users_query::find_user_metadata(&mut tx, Tenant::Unscoped, id).await?;
+            return Err(AppError::NotFound(format!(
+                "Metadata key '{}' not found",
+                key
+            )));
+        }
+    };
+
+    if let Some(schema) = &app_state.metadata_schema {
+        schema.validate(&metadata)?;
+    }
+
+    let previous = before
+        .and_then(|user| user.metadata.get(&key).cloned())
+        .unwrap_or(Value::Null);
+    let changes = json!({ format!("metadata.{}", key): change(&key, previous, Value::Null) });
+    events_query::insert_event(&mut tx, DomainEvent::user_updated(id, &changes)).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserMetadataDeleted,
+        id,
+        changes,
+    )
+    .await?;
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        metadata,
         "Data has been successfuly deleted.",
     ))
 }
+
+pub async fn find_preferences(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<UserPreferencesDTO>, AppError> {
+    validate_owner_or_admin_in_token(req, &id)?;
+
+    let tenant = Tenant::for_target(req, &id)?;
+    let mut tx = tenant.begin(pool).await?;
+    let row = users_query::find_user_preferences(&mut tx, tenant, id).await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        stored_preferences(&app_state.preferences_defaults, row)?,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+/// Replaces the preferences of the user. The body is sparse, keys left out follow the
+/// defaults from the configuration. Clients written against an older schema send their
+/// `schema_version` and the document is upgraded before it is stored.
+pub async fn update_preferences(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    payload: Value,
+    req: &HttpRequest,
+) -> Result<ResponseData<UserPreferencesDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+
+    let Value::Object(mut document) = payload else {
+        return Err(AppError::BadRequest(
+            "Preferences must be a JSON object.".to_string(),
+        ));
+    };
+    // Read-only fields of the response, accepted so a fetched document can be sent back.
+    document.remove("updated_at");
+    let schema_version = match document.remove("schema_version") {
+        None => PREFERENCES_SCHEMA_VERSION,
+        Some(version) => version
+            .as_i64()
+            .and_then(|version| i32::try_from(version).ok())
+            .ok_or(AppError::BadRequest(
+                "schema_version must be an integer.".to_string(),
+            ))?,
+    };
+    let document = preferences::upgrade(document, schema_version)?;
+    let after = preferences::resolve(&app_state.preferences_defaults, &document)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+
+    let row = users_query::find_user_preferences(&mut tx, Tenant::Unscoped, id).await?;
+    let before = stored_preferences(&app_state.preferences_defaults, row)?;
+    let updated_at = users_query::upsert_user_preferences(
+        &mut tx,
+        id,
+        PREFERENCES_SCHEMA_VERSION,
+        Value::Object(document),
+    )
+    .await?;
+    let changes = diff(Some(&before.preferences), Some(&after));
+    events_query::insert_event(
+        &mut tx,
+        DomainEvent::user_updated(id, &json!({ "preferences": changes })),
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserPreferencesUpdated,
+        id,
+        changes,
+    )
+    .await?;
+
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        UserPreferencesDTO {
+            schema_version: PREFERENCES_SCHEMA_VERSION,
+            preferences: after,
+            updated_at: Some(updated_at),
+        },
+        "Data has been successfuly updated.",
+    ))
+}
+
+/// Stored documents are upgraded on read, they are only rewritten on the next update.
+fn stored_preferences(
+    defaults: &Preferences,
+    row: UserPreferencesRow,
+) -> Result<UserPreferencesDTO, AppError> {
+    let document = match row.document {
+        Some(Value::Object(document)) => preferences::upgrade(
+            document,
+            row.schema_version.unwrap_or(PREFERENCES_SCHEMA_VERSION),
+        )?,
+        _ => Map::new(),
+    };
+
+    Ok(UserPreferencesDTO {
+        schema_version: PREFERENCES_SCHEMA_VERSION,
+        preferences: preferences::resolve(defaults, &document)?,
+        updated_at: row.updated_at,
+    })
+}
+
+pub async fn request_email_change(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    payload: RequestEmailChangeDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<EmailChangeRequest>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    let user = users_query::find_user(&mut tx, Tenant::Unscoped, id, &Fieldset::all()).await?;
+
+    if user.email.eq_ignore_ascii_case(&payload.email) {
+        return Err(AppError::BadRequest(
+            "New email must differ from the current one.".to_string(),
+        ));
+    }
+
+    // Checked again when the change is confirmed, the address may be taken meanwhile.
+    if !users_query::find_existing_emails(&mut tx, std::slice::from_ref(&payload.email))
+        .await?
+        .is_empty()
+    {
+        return Err(AppError::Conflict(
+            "Email is already in use by another account.".to_string(),
+        ));
+    }
+
+    let confirm_token = generate_token();
+    let cancel_token = generate_token();
+    let expires_at = Utc::now() + *app_state.email_change_expiration_time;
+
+    let result = users_query::create_email_change_request(
+        &mut tx,
+        id,
+        &payload.email,
+        &hash_token(&confirm_token),
+        &hash_token(&cancel_token),
+        expires_at,
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserEmailChangeRequested,
+        id,
+        json!({ "pending_email": change("pending_email", Value::Null, json!(result.new_email)) }),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    app_state
+        .mailer
+        .send(Mail {
+            to: result.new_email.clone(),
+            subject: "Confirm your new email address".to_string(),
+            body: format!(
+                "Confirm this address for your account: {}{}?token={}\nThe link expires at {}.",
+                app_state.app_base_url, CONFIRM_EMAIL_CHANGE_PATH, confirm_token, expires_at
+            ),
+        })
+        .await?;
+
+    app_state
+        .mailer
+        .send(Mail {
+            to: user.email,
+            subject: "Your email address is being changed".to_string(),
+            body: format!(
+                "A change of your account email to {} was requested. If this was not you, cancel it: {}{}?token={}",
+                result.new_email, app_state.app_base_url, CANCEL_EMAIL_CHANGE_PATH, cancel_token
+            ),
+        })
+        .await?;
+
+    Ok(ResponseData::new(
+        result,
+        "Data has been successfuly created.",
+    ))
+}
+
+pub async fn request_export(
+    pool: &PgPool,
+    id: Uuid,
+    payload: CreateDataExportDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<DataExportDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+
+    let mut tx = Tenant::Unscoped.begin(pool).await?;
+    users_query::find_user(&mut tx, Tenant::Unscoped, id, &Fieldset::all()).await?;
+    let export = users_query::create_user_export(&mut tx, id, payload.format).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::UserExportRequested,
+        id,
+        json!({ "export_id": change("export_id", Value::Null, json!(export.id)) }),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        export.into(),
+        "Data has been successfuly queued.",
+    ))
+}
+
+pub async fn find_export(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    id: Uuid,
+    export_id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<DataExportDTO>, AppError> {
+    validate_user_id_in_token(req, &id)?;
+
+    let export = users_query::find_user_export(pool, export_id).await?;
+    if export.user_id != id {
+        return Err(AppError::NotFound(format!(
+            "Export with ID {} not found",
+            export_id
+        )));
+    }
+
+    let download_url = match (export.status, export.expires_at) {
+        (UserExportStatus::COMPLETED, Some(expires_at)) if expires_at > Utc::now() => {
+            Some(generate_download_link(&export, expires_at, app_state, req)?)
+        }
+        _ => None,
+    };
+
+    Ok(ResponseData::new(
+        DataExportDTO {
+            download_url,
+            ..export.into()
+        },
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+/// Signed link to the archive that works without an access token, so it can be
+/// opened directly by a browser. It never outlives the archive itself.
+fn generate_download_link(
+    export: &UserExport,
+    expires_at: DateTime<Utc>,
+    app_state: &web::Data<AppState>,
+    req: &HttpRequest,
+) -> Result<String, AppError> {
+    let expiration = (Utc::now() + *app_state.download_expiration_time)
+        .min(expires_at)
+        .timestamp() as usize;
+
+    let download_claims = Claims {
+        sub: export.id,
+        exp: expiration,
+        role: UserRole::USER,
+        org: None,
+        membership_role: None,
+    };
+
+    let token = encode(
+        &Header::default(),
+        &download_claims,
+        &EncodingKey::from_secret(app_state.download_key.as_bytes()),
+    )
+    .map_err(|err| AppError::InternalServerError(err.to_string()))?;
+
+    let mut url = req
+        .url_for("user_export_download", [export.id.to_string()])
+        .map_err(|err| AppError::InternalServerError(err.to_string()))?;
+    url.query_pairs_mut().append_pair("token", &token);
+
+    Ok(url.to_string())
+}
+
+pub async fn download_export(
+    pool: &PgPool,
+    app_state: &web::Data<AppState>,
+    export_id: Uuid,
+    token: &str,
+) -> Result<(Vec<u8>, UserExport), AppError> {
+    if verify_download_jwt(token, app_state)? != export_id {
+        return Err(AppError::Unauthorized(
+            "Download token does not match this export.".to_string(),
+        ));
+    }
+
+    let not_found = || AppError::NotFound(format!("Export with ID {} not found", export_id));
+
+    let export = users_query::find_user_export(pool, export_id).await?;
+    if export.status != UserExportStatus::COMPLETED
+        || export
+            .expires_at
+            .is_none_or(|expires_at| expires_at <= Utc::now())
+    {
+        return Err(not_found());
+    }
+
+    let key = export.storage_key.as_deref().ok_or_else(not_found)?;
+    let bytes = app_state.storage.get(key).await?.ok_or_else(not_found)?;
+
+    Ok((bytes, export))
+}
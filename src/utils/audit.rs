@@ -0,0 +1,127 @@
+use crate::{auth::dto::Claims, middlewares::middleware_request_id::RequestId};
+use actix_web::{http::header, HttpMessage, HttpRequest};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeSet, sync::Arc};
+use uuid::Uuid;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Fields whose values never reach the audit log, only the fact that they changed.
+const SECRET_FIELDS: &[&str] = &["password"];
+
+const IP_MAX_LENGTH: usize = 45;
+const USER_AGENT_MAX_LENGTH: usize = 512;
+
+/// Who caused a mutation, through which request and in which organization.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub actor_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl AuditContext {
+    pub fn from_request(req: &HttpRequest) -> Self {
+        // `connection_info` borrows the extensions mutably, so read them first.
+        let (actor_id, organization_id, request_id) = {
+            let extensions = req.extensions();
+            let claims = extensions.get::<Arc<Claims>>();
+            (
+                claims.map(|claims| claims.sub),
+                claims.and_then(|claims| claims.org),
+                extensions.get::<RequestId>().map(|id| id.0.clone()),
+            )
+        };
+
+        AuditContext {
+            actor_id,
+            organization_id,
+            ip: req
+                .connection_info()
+                .realip_remote_addr()
+                .map(|ip| truncate(ip, IP_MAX_LENGTH)),
+            user_agent: req
+                .headers()
+                .get(header::USER_AGENT)
+                .and_then(|value| value.to_str().ok())
+                .map(|user_agent| truncate(user_agent, USER_AGENT_MAX_LENGTH)),
+            request_id,
+        }
+    }
+
+    /// Context of background jobs, there is no request or user behind them.
+    pub fn system() -> Self {
+        AuditContext::default()
+    }
+
+    /// Sets the actor for requests that are not authenticated with an access token,
+    /// e.g. registration or links sent by email.
+    pub fn with_actor(mut self, actor_id: Uuid) -> Self {
+        self.actor_id = Some(actor_id);
+        self
+    }
+
+    /// Sets the organization for changes made in another one than the active, e.g.
+    /// through the members endpoints of an organization or an accepted invitation.
+    pub fn with_organization(mut self, organization_id: Uuid) -> Self {
+        self.organization_id = Some(organization_id);
+        self
+    }
+}
+
+fn truncate(value: &str, max_length: usize) -> String {
+    value.chars().take(max_length).collect()
+}
+
+/// Field level diff of two snapshots as `{field: {"before": .., "after": ..}}`, only
+/// changed fields are listed. A missing snapshot (creation, deletion) reads as `null`.
+pub fn diff<T: Serialize>(before: Option<&T>, after: Option<&T>) -> Value {
+    let object = |value: Option<&T>| match value.map(serde_json::to_value) {
+        Some(Ok(Value::Object(object))) => object,
+        _ => Map::new(),
+    };
+
+    let before = object(before);
+    let after = object(after);
+
+    let fields: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let changes: Map<String, Value> = fields
+        .into_iter()
+        .filter_map(|field| {
+            let old = before.get(field).unwrap_or(&Value::Null);
+            let new = after.get(field).unwrap_or(&Value::Null);
+
+            if old == new {
+                return None;
+            }
+
+            Some((field.clone(), change(field, old.clone(), new.clone())))
+        })
+        .collect();
+
+    Value::Object(changes)
+}
+
+/// Single field change, for mutations without before and after snapshots.
+pub fn change(field: &str, before: Value, after: Value) -> Value {
+    if SECRET_FIELDS.contains(&field) {
+        return json!({ "before": REDACTED, "after": REDACTED });
+    }
+
+    json!({ "before": before, "after": after })
+}
+
+/// Replaces the `before` side of every change, used when the old values are the
+/// personal data being erased.
+pub fn redact_before(mut changes: Value) -> Value {
+    if let Value::Object(fields) = &mut changes {
+        for value in fields.values_mut() {
+            value["before"] = json!(REDACTED);
+        }
+    }
+
+    changes
+}
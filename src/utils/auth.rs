@@ -1,4 +1,4 @@
-use crate::{auth::dto::Claims, utils::errors::AppError};
+use crate::{auth::dto::Claims, users::entity::UserRole, utils::errors::AppError};
 use actix_web::{HttpMessage, HttpRequest};
 use std::sync::Arc;
 use uuid::Uuid;
@@ -20,3 +20,80 @@ pub fn validate_user_id_in_token(req: &HttpRequest, user_id: &Uuid) -> Result<()
 
     Ok(())
 }
+
+pub fn validate_admin_in_token(req: &HttpRequest) -> Result<(), AppError> {
+    let extensions = req.extensions();
+
+    let claims = extensions
+        .get::<Arc<Claims>>()
+        .ok_or(AppError::Unauthorized("Invalid JWT claims".to_string()))?;
+
+    if claims.role != UserRole::ADMIN {
+        return Err(AppError::Forbidden(
+            "Admin role is required to access this resource".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Owners and admins of the active organization, read from the membership by the auth
+/// middleware, and platform admins.
+pub fn validate_org_admin_in_token(req: &HttpRequest) -> Result<(), AppError> {
+    let extensions = req.extensions();
+
+    let claims = extensions
+        .get::<Arc<Claims>>()
+        .ok_or(AppError::Unauthorized("Invalid JWT claims".to_string()))?;
+
+    let is_org_admin = claims
+        .membership_role
+        .is_some_and(|role| role.can_manage_members());
+
+    if claims.role != UserRole::ADMIN && !is_org_admin {
+        return Err(AppError::Forbidden(
+            "Organization admin role is required to access this resource".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn validate_owner_or_admin_in_token(req: &HttpRequest, user_id: &Uuid) -> Result<(), AppError> {
+    let extensions = req.extensions();
+
+    let claims = extensions
+        .get::<Arc<Claims>>()
+        .ok_or(AppError::Unauthorized("Invalid JWT claims".to_string()))?;
+
+    if claims.sub != *user_id && claims.role != UserRole::ADMIN {
+        return Err(AppError::Forbidden(
+            "Not authorized to access this resource".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn user_id_in_token(req: &HttpRequest) -> Result<Uuid, AppError> {
+    let extensions = req.extensions();
+
+    let claims = extensions
+        .get::<Arc<Claims>>()
+        .ok_or(AppError::Unauthorized("Invalid JWT claims".to_string()))?;
+
+    Ok(claims.sub)
+}
+
+pub fn organization_id_in_token(req: &HttpRequest) -> Result<Uuid, AppError> {
+    let extensions = req.extensions();
+
+    let claims = extensions
+        .get::<Arc<Claims>>()
+        .ok_or(AppError::Unauthorized("Invalid JWT claims".to_string()))?;
+
+    // The caller is authenticated, it just has no organization to act in.
+    claims.org.ok_or(AppError::Forbidden(
+        "No active organization, join an organization and log in to it first".to_string(),
+    ))
+}
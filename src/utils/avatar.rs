@@ -0,0 +1,90 @@
+use crate::utils::errors::AppError;
+use image::{imageops::FilterType, ImageFormat, ImageReader, Limits};
+use serde::Deserialize;
+use std::io::Cursor;
+use uuid::Uuid;
+
+/// Formats accepted for uploads, detected from the file's magic bytes rather than
+/// the client supplied content type.
+const ACCEPTED_FORMATS: &[ImageFormat] = &[
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::WebP,
+    ImageFormat::Gif,
+];
+
+// Guards against decompression bombs, a 4096x4096 RGBA image is 64MB decoded.
+const MAX_DIMENSION: u32 = 4096;
+const MAX_DECODED_BYTES: u64 = 64 * 1024 * 1024;
+
+pub const AVATAR_CONTENT_TYPE: &str = "image/png";
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AvatarSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+}
+
+impl AvatarSize {
+    pub const ALL: [AvatarSize; 3] = [AvatarSize::Small, AvatarSize::Medium, AvatarSize::Large];
+
+    pub fn pixels(&self) -> u32 {
+        match self {
+            AvatarSize::Small => 64,
+            AvatarSize::Medium => 256,
+            AvatarSize::Large => 512,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AvatarSize::Small => "small",
+            AvatarSize::Medium => "medium",
+            AvatarSize::Large => "large",
+        }
+    }
+}
+
+pub fn avatar_key(user_id: Uuid, size: AvatarSize) -> String {
+    format!("avatars/{}/{}.png", user_id, size.as_str())
+}
+
+/// Decodes an uploaded image and renders a square PNG for every `AvatarSize`.
+/// Re-encoding also strips metadata (EXIF, GPS) and anything appended to the file.
+pub fn render_avatars(bytes: &[u8]) -> Result<Vec<(AvatarSize, Vec<u8>)>, AppError> {
+    let format = image::guess_format(bytes)
+        .ok()
+        .filter(|format| ACCEPTED_FORMATS.contains(format))
+        .ok_or(AppError::UnsupportedMediaType(
+            "Avatar must be a PNG, JPEG, WebP or GIF image.".to_string(),
+        ))?;
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODED_BYTES);
+
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+
+    let image = reader
+        .decode()
+        .map_err(|e| AppError::BadRequest(format!("Invalid image: {}", e)))?;
+
+    AvatarSize::ALL
+        .iter()
+        .map(|size| {
+            let mut encoded: Vec<u8> = Vec::new();
+
+            image
+                .resize_to_fill(size.pixels(), size.pixels(), FilterType::Lanczos3)
+                .write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+            Ok((*size, encoded))
+        })
+        .collect()
+}
@@ -0,0 +1,34 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Duration;
+
+/// Exponential backoff of the background jobs: `base_seconds` after the first failure,
+/// doubled after every further one, at most `max_seconds`.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub base_seconds: i64,
+    pub max_seconds: i64,
+}
+
+impl Backoff {
+    pub const fn new(base_seconds: i64, max_seconds: i64) -> Self {
+        Backoff {
+            base_seconds,
+            max_seconds,
+        }
+    }
+
+    /// Delay before the next attempt after `attempts` failures.
+    pub fn delay(&self, attempts: i32) -> Duration {
+        let exponent = attempts.clamp(1, 20) as u32 - 1;
+        Duration::seconds((self.base_seconds << exponent).min(self.max_seconds))
+    }
+
+    /// `delay` with up to half of it added at random, so receivers recovering from an
+    /// outage are not hit by every retry at once.
+    pub fn delay_with_jitter(&self, attempts: i32) -> Duration {
+        let delay = self.delay(attempts).num_seconds();
+        let jitter = (OsRng.next_u64() % (delay as u64 / 2 + 1)) as i64;
+
+        Duration::seconds(delay + jitter)
+    }
+}
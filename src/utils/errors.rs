@@ -17,6 +17,9 @@ pub enum AppError {
     #[error("Unauthorized access")]
     Unauthorized(String),
 
+    #[error("Forbidden")]
+    Forbidden(String),
+
     #[error("Bad request")]
     BadRequest(String),
 
@@ -43,25 +46,61 @@ pub enum AppError {
 
     #[error("Password verify error")]
     InvalidCredentials(String),
+
+    #[error("Account pending")]
+    AccountPending(String),
+
+    #[error("Account suspended")]
+    AccountSuspended(String),
+
+    #[error("Account locked")]
+    AccountLocked(String),
+
+    #[error("Precondition failed")]
+    PreconditionFailed(String),
+
+    #[error("Precondition required")]
+    PreconditionRequired(String),
+
+    #[error("Unsupported media type")]
+    UnsupportedMediaType(String),
+
+    #[error("Payload too large")]
+    PayloadTooLarge(String),
+}
+
+impl AppError {
+    /// Detail shown to clients next to the generic error kind.
+    pub fn message(&self) -> String {
+        match self {
+            AppError::NotFound(err) => err.to_string(),
+            AppError::Unauthorized(err) => err.to_string(),
+            AppError::Forbidden(err) => err.to_string(),
+            AppError::BadRequest(err) => err.to_string(),
+            AppError::InternalServerError(err) => err.to_string(),
+            AppError::ValidationError(errors) => format_validation_errors(errors),
+            AppError::DatabaseError(err) => err.to_string(),
+            AppError::TimeoutError(err) => err.to_string(),
+            AppError::RateLimitExceeded(err) => err.to_string(),
+            AppError::PasswordHashingError(err) => err.to_string(),
+            AppError::Conflict(err) => err.to_string(),
+            AppError::InvalidCredentials(err) => err.to_string(),
+            AppError::AccountPending(err) => err.to_string(),
+            AppError::AccountSuspended(err) => err.to_string(),
+            AppError::AccountLocked(err) => err.to_string(),
+            AppError::PreconditionFailed(err) => err.to_string(),
+            AppError::PreconditionRequired(err) => err.to_string(),
+            AppError::UnsupportedMediaType(err) => err.to_string(),
+            AppError::PayloadTooLarge(err) => err.to_string(),
+        }
+    }
 }
 
 impl ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
         let error_response = ErrorResponse {
             error: self.to_string(),
-            message: match self {
-                AppError::NotFound(err) => err.to_string(),
-                AppError::Unauthorized(err) => err.to_string(),
-                AppError::BadRequest(err) => err.to_string(),
-                AppError::InternalServerError(err) => err.to_string(),
-                AppError::ValidationError(errors) => format_validation_errors(errors),
-                AppError::DatabaseError(err) => err.to_string(),
-                AppError::TimeoutError(err) => err.to_string(),
-                AppError::RateLimitExceeded(err) => err.to_string(),
-                AppError::PasswordHashingError(err) => err.to_string(),
-                AppError::Conflict(err) => err.to_string(),
-                AppError::InvalidCredentials(err) => err.to_string(),
-            },
+            message: self.message(),
             code: self.status_code().as_u16(),
             timestamp: custom_timezone_with_fromat(),
         };
@@ -75,6 +114,7 @@ impl ResponseError for AppError {
         match *self {
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
             AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AppError::ValidationError(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::InternalServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -83,7 +123,14 @@ impl ResponseError for AppError {
             AppError::RateLimitExceeded(_) => StatusCode::TOO_MANY_REQUESTS,
             AppError::PasswordHashingError(_) => StatusCode::INTERNAL_SERVER_ERROR,
             AppError::InvalidCredentials(_) => StatusCode::UNAUTHORIZED,
+            AppError::AccountPending(_) => StatusCode::UNAUTHORIZED,
+            AppError::AccountSuspended(_) => StatusCode::FORBIDDEN,
+            AppError::AccountLocked(_) => StatusCode::LOCKED,
             AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
         }
     }
 }
@@ -96,7 +143,7 @@ struct ErrorResponse {
     timestamp: String,
 }
 
-fn format_validation_errors(errors: &ValidationErrors) -> String {
+pub fn format_validation_errors(errors: &ValidationErrors) -> String {
     errors
         .field_errors()
         .iter()
@@ -0,0 +1,64 @@
+use crate::utils::errors::AppError;
+use actix_web::{
+    http::header::{EntityTag, Header, IfMatch, IfNoneMatch},
+    HttpRequest,
+};
+use chrono::{DateTime, Utc};
+
+/// Strong ETag for a row version, `variant` distinguishes partial representations
+/// (e.g. sparse fieldsets) of the same version.
+pub fn etag_for(updated_at: DateTime<Utc>, variant: Option<&str>) -> EntityTag {
+    let version = format!("{:x}", updated_at.timestamp_micros());
+
+    match variant {
+        Some(variant) => EntityTag::new_strong(format!("{}-{}", version, variant)),
+        None => EntityTag::new_strong(version),
+    }
+}
+
+fn version_of(tag: &EntityTag) -> Option<DateTime<Utc>> {
+    if tag.weak {
+        return None;
+    }
+
+    let version = tag.tag().split('-').next()?;
+    let micros = i64::from_str_radix(version, 16).ok()?;
+    DateTime::from_timestamp_micros(micros)
+}
+
+/// True when the client already holds `etag` and a `304 Not Modified` can be sent.
+pub fn is_not_modified(req: &HttpRequest, etag: &EntityTag) -> bool {
+    match IfNoneMatch::parse(req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+        Err(_) => false,
+    }
+}
+
+/// Row versions the client expects to modify, taken from `If-Match`.
+/// `None` means `If-Match: *`, any existing version is accepted.
+pub fn required_versions(req: &HttpRequest) -> Result<Option<Vec<DateTime<Utc>>>, AppError> {
+    if !req.headers().contains_key(IfMatch::name()) {
+        return Err(AppError::PreconditionRequired(
+            "If-Match header is required to modify this resource.".to_string(),
+        ));
+    }
+
+    match IfMatch::parse(req) {
+        Ok(IfMatch::Any) => Ok(None),
+        Ok(IfMatch::Items(tags)) => {
+            let versions: Vec<DateTime<Utc>> = tags.iter().filter_map(version_of).collect();
+
+            if versions.is_empty() {
+                return Err(AppError::PreconditionFailed(
+                    "If-Match does not match the current version of this resource.".to_string(),
+                ));
+            }
+
+            Ok(Some(versions))
+        }
+        Err(_) => Err(AppError::BadRequest(
+            "If-Match header is malformed.".to_string(),
+        )),
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{auth::dto::Claims, server::AppState};
+use crate::{
+    auth::dto::{Claims, InviteClaims},
+    server::AppState,
+};
 use actix_web::{web, HttpRequest};
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
 use uuid::Uuid;
@@ -34,12 +37,44 @@ pub fn verify_jwt(req: &HttpRequest, state: &web::Data<AppState>) -> Result<Clai
 pub fn verify_refresh_jwt(
     refresh_token: String,
     state: &web::Data<AppState>,
-) -> Result<Uuid, AppError> {
+) -> Result<Claims, AppError> {
     let decoding_key = DecodingKey::from_secret(state.refresh_key.as_ref().as_bytes());
     let validation = Validation::new(Algorithm::HS256);
 
     match decode::<Claims>(&refresh_token, &decoding_key, &validation) {
-        Ok(decoded_token) => Ok(decoded_token.claims.sub),
+        Ok(decoded_token) => Ok(decoded_token.claims),
         Err(e) => Err(AppError::Unauthorized(e.to_string())),
     }
 }
+
+pub fn verify_invite_jwt(
+    invite_token: &str,
+    state: &web::Data<AppState>,
+) -> Result<InviteClaims, AppError> {
+    let decoding_key = DecodingKey::from_secret(state.invite_key.as_ref().as_bytes());
+    let validation = Validation::new(Algorithm::HS256);
+
+    match decode::<InviteClaims>(invite_token, &decoding_key, &validation) {
+        Ok(decoded_token) => Ok(decoded_token.claims),
+        Err(e) => Err(AppError::Unauthorized(format!(
+            "Invalid invite token: {}",
+            e
+        ))),
+    }
+}
+
+pub fn verify_download_jwt(
+    download_token: &str,
+    state: &web::Data<AppState>,
+) -> Result<Uuid, AppError> {
+    let decoding_key = DecodingKey::from_secret(state.download_key.as_ref().as_bytes());
+    let validation = Validation::new(Algorithm::HS256);
+
+    match decode::<Claims>(download_token, &decoding_key, &validation) {
+        Ok(decoded_token) => Ok(decoded_token.claims.sub),
+        Err(e) => Err(AppError::Unauthorized(format!(
+            "Invalid download token: {}",
+            e
+        ))),
+    }
+}
@@ -0,0 +1,29 @@
+use crate::utils::errors::AppError;
+use async_trait::async_trait;
+use std::fmt::Debug;
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync + Debug {
+    async fn send(&self, mail: Mail) -> Result<(), AppError>;
+}
+
+/// Logs the recipient and subject of mails instead of delivering them, for development.
+/// Bodies are left out, they carry live confirmation and invite tokens.
+#[derive(Debug, Default)]
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, mail: Mail) -> Result<(), AppError> {
+        log::info!("mail to={} subject={:?}", mail.to, mail.subject);
+
+        Ok(())
+    }
+}
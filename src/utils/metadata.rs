@@ -0,0 +1,68 @@
+use crate::utils::errors::AppError;
+use jsonschema::Validator;
+use regex::Regex;
+use serde_json::Value;
+use std::{fmt, sync::LazyLock};
+use validator::{ValidationError, ValidationErrors};
+
+/// JSON Schema every user's metadata document must satisfy after a change.
+pub struct MetadataSchema(Validator);
+
+impl fmt::Debug for MetadataSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MetadataSchema")
+    }
+}
+
+impl MetadataSchema {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let schema = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let schema: Value =
+            serde_json::from_str(&schema).map_err(|e| format!("{}: {}", path, e))?;
+        let validator =
+            jsonschema::validator_for(&schema).map_err(|e| format!("{}: {}", path, e))?;
+
+        Ok(MetadataSchema(validator))
+    }
+
+    pub fn validate(&self, metadata: &Value) -> Result<(), AppError> {
+        let messages: Vec<String> = self
+            .0
+            .iter_errors(metadata)
+            .map(|error| match error.instance_path.to_string().as_str() {
+                "" => error.to_string(),
+                path => format!("{} {}", path, error),
+            })
+            .collect();
+
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let mut errors = ValidationErrors::new();
+        let mut error = ValidationError::new("metadata_schema");
+        error.message = Some(messages.join(", ").into());
+        errors.add("metadata", error);
+
+        Err(AppError::ValidationError(errors))
+    }
+}
+
+static METADATA_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z0-9_.-]{1,64}$").unwrap());
+
+pub fn validate_metadata_key(key: &str) -> Result<(), AppError> {
+    if !METADATA_KEY.is_match(key) {
+        return Err(AppError::BadRequest(
+            "Metadata key must be 1-64 characters of letters, digits, '_', '.' or '-'.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Value of a `filter[metadata][key]=value` query parameter: valid JSON (`42`, `true`,
+/// `"42"`) is matched as-is, anything else as a string.
+pub fn metadata_filter_value(value: &str) -> Value {
+    serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+}
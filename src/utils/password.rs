@@ -4,7 +4,18 @@ use argon2::{
     Argon2, Params, PasswordHash, PasswordVerifier,
 };
 use regex::Regex;
-use validator::ValidationError;
+use std::sync::LazyLock;
+use validator::{ValidationError, ValidationErrors};
+
+/// Stored instead of a hash for accounts that have not set a password yet (invited users),
+/// it never verifies.
+pub const UNUSABLE_PASSWORD: &str = "!";
+
+static LOWERCASE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[a-z]").unwrap());
+static UPPERCASE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"[A-Z]").unwrap());
+static DIGIT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d").unwrap());
+static SPECIAL_CHAR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"[!@#$%^&*()_+\-=\[\]{};':\"\\|,.<>\/?]"#).unwrap());
 
 pub fn hash_password(password: &str) -> Result<String, AppError> {
     let salt = SaltString::generate(&mut OsRng);
@@ -32,36 +43,31 @@ pub fn verify_password(password: &str, hashed_password: &str) -> Result<(), AppE
 }
 
 pub fn validate_password(password: &str) -> Result<(), ValidationError> {
-    let lowercase = Regex::new(r"[a-z]").unwrap();
-    let uppercase = Regex::new(r"[A-Z]").unwrap();
-    let digit = Regex::new(r"\d").unwrap();
-    let special_char = Regex::new(r#"[!@#$%^&*()_+\-=\[\]{};':\"\\|,.<>\/?]"#).unwrap();
-
     if password.len() < 8 {
         let mut error = ValidationError::new("password_length");
         error.message = Some("Password must be at least 8 characters long.".into());
         return Err(error);
     }
 
-    if !lowercase.is_match(password) {
+    if !LOWERCASE.is_match(password) {
         let mut error = ValidationError::new("password_lowercase");
         error.message = Some("Password must contain at least one lowercase letter (a-z).".into());
         return Err(error);
     }
 
-    if !uppercase.is_match(password) {
+    if !UPPERCASE.is_match(password) {
         let mut error = ValidationError::new("password_uppercase");
         error.message = Some("Password must contain at least one uppercase letter (A-Z).".into());
         return Err(error);
     }
 
-    if !digit.is_match(password) {
+    if !DIGIT.is_match(password) {
         let mut error = ValidationError::new("password_digit");
         error.message = Some("Password must contain at least one digit (0-9).".into());
         return Err(error);
     }
 
-    if !special_char.is_match(password) {
+    if !SPECIAL_CHAR.is_match(password) {
         let mut error = ValidationError::new("password_special_char");
         error.message =
             Some("Password must contain at least one special character (e.g., !@#$%^&*).".into());
@@ -70,3 +76,13 @@ pub fn validate_password(password: &str) -> Result<(), ValidationError> {
 
     Ok(())
 }
+
+/// `validate_password` for a password checked outside of a DTO, e.g. one that is only
+/// new on some paths.
+pub fn validate_new_password(password: &str) -> Result<(), AppError> {
+    validate_password(password).map_err(|error| {
+        let mut errors = ValidationErrors::new();
+        errors.add("password", error);
+        AppError::ValidationError(errors)
+    })
+}
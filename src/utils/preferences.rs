@@ -0,0 +1,61 @@
+use crate::{users::entity::Preferences, utils::errors::AppError};
+use serde_json::{Map, Value};
+use validator::Validate;
+
+/// Version of the preferences document written by this build.
+pub const PREFERENCES_SCHEMA_VERSION: i32 = 1;
+
+/// Upgrade steps, `UPGRADES[n]` turns a version `n + 1` document into version `n + 2`.
+/// Append a step whenever a key is renamed or reshaped, new keys only need a default.
+const UPGRADES: &[fn(&mut Map<String, Value>)] = &[];
+
+/// Brings a stored or submitted document up to the current schema version.
+pub fn upgrade(
+    mut document: Map<String, Value>,
+    schema_version: i32,
+) -> Result<Map<String, Value>, AppError> {
+    if schema_version < 1 || schema_version > PREFERENCES_SCHEMA_VERSION {
+        return Err(AppError::BadRequest(format!(
+            "Unsupported preferences schema version {}, expected 1 to {}.",
+            schema_version, PREFERENCES_SCHEMA_VERSION
+        )));
+    }
+
+    for step in &UPGRADES[(schema_version - 1) as usize..] {
+        step(&mut document);
+    }
+
+    Ok(document)
+}
+
+/// Effective preferences: the sparse document of the user merged over the defaults,
+/// keys the user never set follow the defaults.
+pub fn resolve(
+    defaults: &Preferences,
+    document: &Map<String, Value>,
+) -> Result<Preferences, AppError> {
+    let mut merged =
+        serde_json::to_value(defaults).map_err(|e| AppError::InternalServerError(e.to_string()))?;
+    merge(&mut merged, document);
+
+    let preferences: Preferences = serde_json::from_value(merged)
+        .map_err(|e| AppError::BadRequest(format!("Invalid preferences: {}", e)))?;
+    preferences.validate().map_err(AppError::ValidationError)?;
+
+    Ok(preferences)
+}
+
+fn merge(target: &mut Value, document: &Map<String, Value>) {
+    let Value::Object(target) = target else {
+        return;
+    };
+
+    for (key, value) in document {
+        match (target.get_mut(key), value) {
+            (Some(existing @ Value::Object(_)), Value::Object(nested)) => merge(existing, nested),
+            _ => {
+                target.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use chrono_tz::Tz;
+use regex::Regex;
+use std::sync::LazyLock;
+use validator::ValidationError;
+
+static E164: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\+[1-9]\d{6,14}$").unwrap());
+static LANGUAGE_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[a-zA-Z]{2,3}(-[a-zA-Z0-9]{2,8})*$").unwrap());
+
+/// E.164 phone number, e.g. `+6281234567890`.
+pub fn validate_phone(phone: &str) -> Result<(), ValidationError> {
+    if !E164.is_match(phone) {
+        let mut error = ValidationError::new("phone_format");
+        error.message = Some("Phone must be in E.164 format (e.g., +6281234567890).".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// BCP 47 language tag, e.g. `en`, `en-US` or `zh-Hant-TW`.
+pub fn validate_locale(locale: &str) -> Result<(), ValidationError> {
+    if !LANGUAGE_TAG.is_match(locale) {
+        let mut error = ValidationError::new("locale_format");
+        error.message = Some("Locale must be a BCP 47 language tag (e.g., en-US).".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+/// IANA time zone name, e.g. `Asia/Jakarta`.
+pub fn validate_timezone(timezone: &str) -> Result<(), ValidationError> {
+    if timezone.parse::<Tz>().is_err() {
+        let mut error = ValidationError::new("timezone_unknown");
+        error.message = Some("Timezone must be an IANA time zone (e.g., Asia/Jakarta).".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
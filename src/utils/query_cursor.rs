@@ -0,0 +1,172 @@
+use crate::utils::{
+    errors::AppError,
+    query_sort::{SortDirection, SortKey, Sortable},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum CursorValue {
+    Uuid(Uuid),
+    Text(String),
+    Timestamp(DateTime<Utc>),
+}
+
+/// Resources whose listings can be paged with `after`/`before` cursors.
+pub trait Cursorable: Sortable {
+    /// Value of a sortable column for the row, used to build the next cursor.
+    fn cursor_value(&self, column: &str) -> Option<CursorValue>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CursorDirection {
+    After,
+    Before,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CursorPayload {
+    sort: String,
+    values: Vec<CursorValue>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PageCursor {
+    pub direction: CursorDirection,
+    values: Vec<CursorValue>,
+}
+
+fn sort_signature(keys: &[SortKey]) -> String {
+    keys.iter()
+        .map(|key| format!("{}:{}", key.column, key.direction.as_sql()))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+pub fn encode_cursor<T: Cursorable>(row: &T, keys: &[SortKey]) -> Option<String> {
+    let values = keys
+        .iter()
+        .map(|key| row.cursor_value(key.column))
+        .collect::<Option<Vec<CursorValue>>>()?;
+
+    let payload = serde_json::to_vec(&CursorPayload {
+        sort: sort_signature(keys),
+        values,
+    })
+    .ok()?;
+
+    Some(URL_SAFE_NO_PAD.encode(payload))
+}
+
+pub fn decode_cursor(
+    cursor: &str,
+    direction: CursorDirection,
+    keys: &[SortKey],
+) -> Result<PageCursor, AppError> {
+    let invalid = || AppError::BadRequest("Invalid pagination cursor.".to_string());
+
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let payload: CursorPayload = serde_json::from_slice(&bytes).map_err(|_| invalid())?;
+
+    if payload.sort != sort_signature(keys) || payload.values.len() != keys.len() {
+        return Err(AppError::BadRequest(
+            "Pagination cursor does not match the requested sort.".to_string(),
+        ));
+    }
+
+    Ok(PageCursor {
+        direction,
+        values: payload.values,
+    })
+}
+
+impl PageCursor {
+    /// Appends the keyset condition selecting rows strictly after (or before) the cursor row.
+    pub fn push_condition(&self, query_builder: &mut QueryBuilder<'_, Postgres>, keys: &[SortKey]) {
+        query_builder.push(" AND (");
+
+        for (i, key) in keys.iter().enumerate() {
+            if i > 0 {
+                query_builder.push(" OR ");
+            }
+            query_builder.push("(");
+
+            for (previous, value) in keys.iter().zip(&self.values).take(i) {
+                query_builder.push(format!("{} = ", previous.column));
+                push_cursor_value(query_builder, value);
+                query_builder.push(" AND ");
+            }
+
+            let ascending = key.direction == SortDirection::Asc;
+            let forward = self.direction == CursorDirection::After;
+            let operator = if ascending == forward { ">" } else { "<" };
+
+            query_builder.push(format!("{} {} ", key.column, operator));
+            push_cursor_value(query_builder, &self.values[i]);
+            query_builder.push(")");
+        }
+
+        query_builder.push(")");
+    }
+
+    /// Sort keys to query with, `before` cursors walk the listing backwards.
+    pub fn query_keys(&self, keys: &[SortKey]) -> Vec<SortKey> {
+        match self.direction {
+            CursorDirection::After => keys.to_vec(),
+            CursorDirection::Before => keys
+                .iter()
+                .map(|key| SortKey {
+                    column: key.column,
+                    direction: match key.direction {
+                        SortDirection::Asc => SortDirection::Desc,
+                        SortDirection::Desc => SortDirection::Asc,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+fn push_cursor_value(query_builder: &mut QueryBuilder<'_, Postgres>, value: &CursorValue) {
+    match value {
+        CursorValue::Uuid(value) => query_builder.push_bind(*value),
+        CursorValue::Text(value) => query_builder.push_bind(value.clone()),
+        CursorValue::Timestamp(value) => query_builder.push_bind(*value),
+    };
+}
+
+/// Trims the extra row fetched to detect another page and builds the surrounding cursors.
+/// Rows must have been queried with `limit + 1` using the cursor's `query_keys`.
+pub fn split_page<T: Cursorable>(
+    mut rows: Vec<T>,
+    limit: i64,
+    keys: &[SortKey],
+    cursor: Option<&PageCursor>,
+    has_previous: bool,
+) -> (Vec<T>, Option<String>, Option<String>) {
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit as usize);
+
+    let (has_next, has_previous) = match cursor.map(|cursor| cursor.direction) {
+        Some(CursorDirection::Before) => {
+            rows.reverse();
+            (true, has_more)
+        }
+        Some(CursorDirection::After) => (has_more, true),
+        None => (has_more, has_previous),
+    };
+
+    let next_cursor = rows
+        .last()
+        .filter(|_| has_next)
+        .and_then(|row| encode_cursor(row, keys));
+    let prev_cursor = rows
+        .first()
+        .filter(|_| has_previous)
+        .and_then(|row| encode_cursor(row, keys));
+
+    (rows, next_cursor, prev_cursor)
+}
@@ -0,0 +1,121 @@
+use crate::utils::errors::AppError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Resources that support `fields=` sparse fieldsets and `include=` embedded relations.
+pub trait Projectable {
+    /// Fields clients may request, each one maps to a column of the same name.
+    const FIELDS: &'static [&'static str];
+
+    /// Related resources clients may embed.
+    const INCLUDES: &'static [&'static str];
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct QueryFields {
+    pub fields: Option<String>,
+    pub include: Option<String>,
+}
+
+/// Requested fields, `None` selects every field of the resource.
+#[derive(Debug, Clone)]
+pub struct Fieldset(Option<Vec<&'static str>>);
+
+impl QueryFields {
+    pub fn fieldset<T: Projectable>(&self) -> Result<Fieldset, AppError> {
+        match self
+            .fields
+            .as_deref()
+            .filter(|fields| !fields.trim().is_empty())
+        {
+            Some(fields) => Ok(Fieldset(Some(resolve(fields, T::FIELDS, "field")?))),
+            None => Ok(Fieldset(None)),
+        }
+    }
+
+    pub fn includes<T: Projectable>(&self) -> Result<Vec<&'static str>, AppError> {
+        match self
+            .include
+            .as_deref()
+            .filter(|include| !include.trim().is_empty())
+        {
+            Some(include) => resolve(include, T::INCLUDES, "include"),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+fn resolve(
+    list: &str,
+    allowed: &'static [&'static str],
+    kind: &str,
+) -> Result<Vec<&'static str>, AppError> {
+    let mut resolved: Vec<&'static str> = Vec::new();
+
+    for name in list.split(',').map(str::trim) {
+        let item = allowed
+            .iter()
+            .find(|item| **item == name)
+            .ok_or(AppError::BadRequest(format!(
+                "Invalid {} '{}'. Allowed values: {}.",
+                kind,
+                name,
+                if allowed.is_empty() {
+                    "none".to_string()
+                } else {
+                    allowed.join(", ")
+                }
+            )))?;
+
+        if !resolved.contains(item) {
+            resolved.push(item);
+        }
+    }
+
+    Ok(resolved)
+}
+
+impl Fieldset {
+    pub fn all() -> Self {
+        Fieldset(None)
+    }
+
+    /// Comma separated column list for a `SELECT`, always containing the `required`
+    /// columns (ids, sort keys) even when the client did not ask for them.
+    pub fn columns<T: Projectable>(&self, required: &[&'static str]) -> String {
+        let mut columns: Vec<&'static str> = match &self.0 {
+            Some(fields) => fields.clone(),
+            None => T::FIELDS.to_vec(),
+        };
+
+        for column in required {
+            if !columns.contains(column) {
+                columns.push(column);
+            }
+        }
+
+        columns.join(", ")
+    }
+
+    /// Whether `field` was requested, every field is when the selection is not partial.
+    pub fn contains(&self, field: &str) -> bool {
+        self.0.as_ref().is_none_or(|fields| fields.contains(&field))
+    }
+
+    /// Stable name of a partial selection, `None` when every field is selected.
+    pub fn signature(&self) -> Option<String> {
+        self.0.as_ref().map(|fields| fields.join("."))
+    }
+
+    /// Serializes `value` keeping only the requested fields.
+    pub fn project<T: Serialize>(&self, value: &T) -> Result<Value, AppError> {
+        let mut value = serde_json::to_value(value)
+            .map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+        if let (Some(fields), Value::Object(map)) = (&self.0, &mut value) {
+            map.retain(|key, _| fields.contains(&key.as_str()));
+        }
+
+        Ok(value)
+    }
+}
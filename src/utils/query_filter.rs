@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::{Postgres, QueryBuilder};
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct TextFilter {
+    pub eq: Option<String>,
+    pub prefix: Option<String>,
+    pub ilike: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct DateRangeFilter {
+    pub gte: Option<DateTime<Utc>>,
+    pub lte: Option<DateTime<Utc>>,
+}
+
+impl TextFilter {
+    /// Appends `AND` conditions for `column`, every value is bound as a parameter.
+    pub fn push_conditions(&self, query_builder: &mut QueryBuilder<'_, Postgres>, column: &str) {
+        if let Some(eq) = &self.eq {
+            query_builder
+                .push(format!(" AND {} = ", column))
+                .push_bind(eq.clone());
+        }
+
+        if let Some(prefix) = &self.prefix {
+            query_builder
+                .push(format!(" AND {} LIKE ", column))
+                .push_bind(format!("{}%", escape_like(prefix)));
+        }
+
+        if let Some(ilike) = &self.ilike {
+            query_builder
+                .push(format!(" AND {} ILIKE ", column))
+                .push_bind(format!("%{}%", escape_like(ilike)));
+        }
+    }
+}
+
+impl DateRangeFilter {
+    /// Appends inclusive range conditions for `column`.
+    pub fn push_conditions(&self, query_builder: &mut QueryBuilder<'_, Postgres>, column: &str) {
+        if let Some(gte) = self.gte {
+            query_builder
+                .push(format!(" AND {} >= ", column))
+                .push_bind(gte);
+        }
+
+        if let Some(lte) = self.lte {
+            query_builder
+                .push(format!(" AND {} <= ", column))
+                .push_bind(lte);
+        }
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
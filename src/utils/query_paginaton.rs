@@ -1,63 +1,69 @@
+use crate::utils::{
+    errors::AppError,
+    query_cursor::{decode_cursor, CursorDirection, PageCursor},
+    query_sort::{parse_sort, SortKey, Sortable},
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use validator::{Validate, ValidationError};
+use validator::Validate;
 
-fn default_order() -> HashMap<String, String> {
-    let mut default = HashMap::new();
-    default.insert("created_at".to_string(), "DESC".to_string());
-    default
-}
-
-fn validate_order(order: &HashMap<String, String>) -> Result<(), ValidationError> {
-    for (key, value) in order {
-        if !value.eq_ignore_ascii_case("ASC") && !value.eq_ignore_ascii_case("DESC") {
-            let mut error = ValidationError::new("invalid_order");
-            error.message = Some(
-                format!(
-                    "Invalid order value for key '{}'. Must be 'ASC' or 'DESC'.",
-                    key
-                )
-                .into(),
-            );
-            return Err(error);
-        }
-    }
-    Ok(())
-}
+pub const DEFAULT_PAGE_LIMIT: i64 = 10;
+pub const MAX_PAGE_LIMIT: i64 = 100;
 
 #[derive(Deserialize, Serialize, Validate, Debug)]
 pub struct QueryPagination {
     pub limit: Option<i64>,
     pub page: Option<i64>,
-
-    #[validate(custom(function = "validate_order"))]
-    #[serde(default = "default_order")]
-    pub order: HashMap<String, String>,
+    pub sort: Option<String>,
+    pub after: Option<String>,
+    pub before: Option<String>,
 }
 
 impl QueryPagination {
-    pub fn paginate(&self) -> (i64, i64, i64, HashMap<String, String>) {
-        let mut limit = self.limit.unwrap_or(10);
-        let mut page = self.page.unwrap_or(1);
+    /// Returns `(limit, offset, page)`, `limit` is capped at `MAX_PAGE_LIMIT`.
+    /// The offset is always 0 when paging with a cursor.
+    pub fn paginate(&self) -> Result<(i64, i64, i64), AppError> {
+        let limit = self.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+        let page = self.page.unwrap_or(1);
+
+        if limit < 1 {
+            return Err(AppError::BadRequest(format!(
+                "limit must be between 1 and {}.",
+                MAX_PAGE_LIMIT
+            )));
+        }
 
-        if limit == -1 {
-            page = 1;
-            limit = i64::MAX;
+        if page < 1 {
+            return Err(AppError::BadRequest(
+                "page must be greater than 0.".to_string(),
+            ));
         }
 
-        let offset = if limit == i64::MAX {
+        let limit = limit.min(MAX_PAGE_LIMIT);
+
+        let offset = if self.after.is_some() || self.before.is_some() {
             0
         } else {
-            (page - 1) * limit
+            (page - 1)
+                .checked_mul(limit)
+                .ok_or(AppError::BadRequest("page is too large.".to_string()))?
         };
 
-        let order = self
-            .order
-            .iter()
-            .map(|(key, value)| (key.clone(), value.to_uppercase()))
-            .collect();
+        Ok((limit, offset, page))
+    }
 
-        (limit, offset, page, order)
+    pub fn sort_keys<T: Sortable>(&self) -> Result<Vec<SortKey>, AppError> {
+        parse_sort::<T>(self.sort.as_deref())
+    }
+
+    pub fn cursor(&self, keys: &[SortKey]) -> Result<Option<PageCursor>, AppError> {
+        match (&self.after, &self.before) {
+            (Some(_), Some(_)) => Err(AppError::BadRequest(
+                "Only one of 'after' or 'before' can be used.".to_string(),
+            )),
+            (Some(after), None) => decode_cursor(after, CursorDirection::After, keys).map(Some),
+            (None, Some(before)) => decode_cursor(before, CursorDirection::Before, keys).map(Some),
+            (None, None) => Ok(None),
+        }
     }
 }
 
@@ -66,6 +72,8 @@ pub struct ResultWithPagination<T> {
     pub page: i64,
     pub count: i64,
     pub current_count: usize,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
     pub data: T,
 }
 
@@ -76,7 +84,19 @@ impl<T> ResultWithPagination<T> {
             page,
             count,
             current_count,
+            next_cursor: None,
+            prev_cursor: None,
             data,
         }
     }
+
+    pub fn with_cursors(
+        mut self,
+        next_cursor: Option<String>,
+        prev_cursor: Option<String>,
+    ) -> Self {
+        self.next_cursor = next_cursor;
+        self.prev_cursor = prev_cursor;
+        self
+    }
 }
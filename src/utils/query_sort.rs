@@ -0,0 +1,90 @@
+use crate::utils::errors::AppError;
+use sqlx::{Postgres, QueryBuilder};
+
+/// Column every listing falls back to so rows with equal sort keys keep a stable order.
+pub const TIEBREAKER_COLUMN: &str = "id";
+
+/// Resources that can be listed with `sort=-created_at,name` style parameters.
+pub trait Sortable {
+    /// Columns clients are allowed to sort on.
+    const SORTABLE_COLUMNS: &'static [&'static str];
+
+    /// Sort applied when the client does not send one.
+    const DEFAULT_SORT: &'static str;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl SortDirection {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub column: &'static str,
+    pub direction: SortDirection,
+}
+
+/// Parses a comma separated sort list, a leading `-` sorts descending.
+/// Columns are resolved against the resource whitelist so only known names reach SQL.
+pub fn parse_sort<T: Sortable>(sort: Option<&str>) -> Result<Vec<SortKey>, AppError> {
+    let sort = sort
+        .filter(|sort| !sort.trim().is_empty())
+        .unwrap_or(T::DEFAULT_SORT);
+
+    let mut keys: Vec<SortKey> = Vec::new();
+
+    for field in sort.split(',').map(str::trim) {
+        let (direction, name) = match field.strip_prefix('-') {
+            Some(name) => (SortDirection::Desc, name),
+            None => (SortDirection::Asc, field.strip_prefix('+').unwrap_or(field)),
+        };
+
+        let column = T::SORTABLE_COLUMNS
+            .iter()
+            .find(|column| **column == name)
+            .ok_or(AppError::BadRequest(format!(
+                "Invalid sort column '{}'. Allowed columns: {}.",
+                name,
+                T::SORTABLE_COLUMNS.join(", ")
+            )))?;
+
+        if keys.iter().any(|key| key.column == *column) {
+            return Err(AppError::BadRequest(format!(
+                "Duplicate sort column '{}'.",
+                name
+            )));
+        }
+
+        keys.push(SortKey { column, direction });
+    }
+
+    if !keys.iter().any(|key| key.column == TIEBREAKER_COLUMN) {
+        keys.push(SortKey {
+            column: TIEBREAKER_COLUMN,
+            direction: SortDirection::Asc,
+        });
+    }
+
+    Ok(keys)
+}
+
+pub fn push_order_by(query_builder: &mut QueryBuilder<'_, Postgres>, keys: &[SortKey]) {
+    query_builder.push(" ORDER BY ");
+
+    for (i, key) in keys.iter().enumerate() {
+        if i > 0 {
+            query_builder.push(", ");
+        }
+        query_builder.push(format!("{} {}", key.column, key.direction.as_sql()));
+    }
+}
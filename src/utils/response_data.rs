@@ -29,6 +29,8 @@ pub struct ResponseDatas<T> {
     pub page_count: u64,
     pub count: u64,
     pub current_count: u64,
+    pub next_cursor: Option<String>,
+    pub prev_cursor: Option<String>,
     pub data: T,
 }
 
@@ -49,7 +51,19 @@ where
             count: count as u64,
             page_count,
             current_count: current_count as u64,
+            next_cursor: None,
+            prev_cursor: None,
             data,
         }
     }
+
+    pub fn with_cursors(
+        mut self,
+        next_cursor: Option<String>,
+        prev_cursor: Option<String>,
+    ) -> Self {
+        self.next_cursor = next_cursor;
+        self.prev_cursor = prev_cursor;
+        self
+    }
 }
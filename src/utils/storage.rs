@@ -0,0 +1,83 @@
+use crate::utils::errors::AppError;
+use async_trait::async_trait;
+use std::{
+    fmt::Debug,
+    io::ErrorKind,
+    path::{Component, Path, PathBuf},
+};
+
+/// Blob storage for user uploaded files, keys are `/` separated relative paths
+/// such as `avatars/<user id>/small.png`.
+#[async_trait]
+pub trait Storage: Send + Sync + Debug {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), AppError>;
+
+    /// `None` when nothing is stored under `key`.
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, AppError>;
+
+    /// Deleting a missing key is not an error.
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+#[derive(Debug)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        LocalStorage { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, AppError> {
+        let relative = Path::new(key);
+
+        if key.is_empty()
+            || !relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+        {
+            return Err(AppError::InternalServerError(format!(
+                "Invalid storage key '{}'",
+                key
+            )));
+        }
+
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, bytes: Vec<u8>) -> Result<(), AppError> {
+        let path = self.path(key)?;
+        let io_error = |e: std::io::Error| AppError::InternalServerError(e.to_string());
+
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await.map_err(io_error)?;
+        }
+
+        // Write next to the target and rename so readers never see a partial file.
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, bytes).await.map_err(io_error)?;
+        tokio::fs::rename(&partial, &path).await.map_err(io_error)?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, AppError> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(AppError::InternalServerError(e.to_string())),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::InternalServerError(e.to_string())),
+        }
+    }
+}
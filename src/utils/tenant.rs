@@ -0,0 +1,84 @@
+use crate::{
+    auth::dto::Claims,
+    utils::{auth::organization_id_in_token, errors::AppError},
+};
+use actix_web::{HttpMessage, HttpRequest};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, Transaction};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Organization a query is restricted to. Every user query that can return someone
+/// other than the caller takes one, so an organization never sees another's members.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tenant {
+    Organization(Uuid),
+    /// The caller's own record, background jobs and account flows.
+    Unscoped,
+}
+
+impl Tenant {
+    /// Active organization from the `org` claim of the access token, the auth
+    /// middleware has checked that the caller is still a member.
+    pub fn from_request(req: &HttpRequest) -> Result<Self, AppError> {
+        organization_id_in_token(req).map(Tenant::Organization)
+    }
+
+    /// Like `from_request`, but reads of the caller's own record are not scoped.
+    pub fn for_target(req: &HttpRequest, user_id: &Uuid) -> Result<Self, AppError> {
+        let is_self = req
+            .extensions()
+            .get::<Arc<Claims>>()
+            .is_some_and(|claims| claims.sub == *user_id);
+
+        match is_self {
+            true => Ok(Tenant::Unscoped),
+            false => Tenant::from_request(req),
+        }
+    }
+
+    pub fn organization_id(&self) -> Option<Uuid> {
+        match self {
+            Tenant::Organization(id) => Some(*id),
+            Tenant::Unscoped => None,
+        }
+    }
+
+    /// Starts a transaction for the row level security policies. In an organization it
+    /// sets `app.current_tenant`, so the policies enforce the tenant on top of the
+    /// conditions in the queries. `Unscoped` opts out explicitly with `app.rls_bypass`,
+    /// a transaction with neither setting sees no users. The settings are transaction
+    /// local and gone once the connection is back in the pool.
+    pub async fn begin(&self, pool: &PgPool) -> Result<Transaction<'static, Postgres>, AppError> {
+        let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+        self.set_local(&mut tx).await?;
+
+        Ok(tx)
+    }
+
+    /// Applies the settings of `begin` to a transaction started elsewhere, such as on
+    /// a connection that holds a session lock.
+    pub async fn set_local(&self, conn: &mut PgConnection) -> Result<(), AppError> {
+        let (setting, value) = match self {
+            Tenant::Organization(id) => ("app.current_tenant", id.to_string()),
+            Tenant::Unscoped => ("app.rls_bypass", "on".to_string()),
+        };
+        sqlx::query("SELECT set_config($1, $2, true)")
+            .bind(setting)
+            .bind(value)
+            .execute(conn)
+            .await
+            .map_err(AppError::DatabaseError)?;
+
+        Ok(())
+    }
+
+    /// Appends ` AND in_tenant(<column>, ...)`, the query must already have a `WHERE`.
+    pub fn push_condition(&self, query_builder: &mut QueryBuilder<'_, Postgres>, column: &str) {
+        query_builder
+            .push(" AND in_tenant(")
+            .push(column)
+            .push(", ")
+            .push_bind(self.organization_id())
+            .push(")");
+    }
+}
@@ -0,0 +1,70 @@
+use crate::{events::entity::EventType, webhooks::entity::WebhookEndpoint};
+use serde::{Deserialize, Serialize};
+use validator::{Validate, ValidationError};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateWebhookDTO {
+    #[validate(url, length(max = 2048), custom(function = "validate_webhook_url"))]
+    pub url: String,
+
+    #[validate(length(max = 255))]
+    pub description: Option<String>,
+
+    #[validate(length(min = 1), custom(function = "validate_event_types"))]
+    pub event_types: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateWebhookDTO {
+    #[validate(url, length(max = 2048), custom(function = "validate_webhook_url"))]
+    pub url: String,
+
+    #[validate(length(max = 255))]
+    pub description: Option<String>,
+
+    #[validate(length(min = 1), custom(function = "validate_event_types"))]
+    pub event_types: Vec<String>,
+
+    /// Enabling an endpoint that was disabled after failures resets its failure count.
+    pub enabled: bool,
+}
+
+/// Response of the creation, the only time the signing secret is shown.
+#[derive(Debug, Serialize)]
+pub struct CreatedWebhookDTO {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpoint,
+    pub secret: String,
+}
+
+fn validate_webhook_url(url: &str) -> Result<(), ValidationError> {
+    if !url.starts_with("https://") && !url.starts_with("http://") {
+        let mut error = ValidationError::new("webhook_url_scheme");
+        error.message = Some("Webhook URL must use http or https.".into());
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+fn validate_event_types(event_types: &[String]) -> Result<(), ValidationError> {
+    if let Some(unknown) = event_types
+        .iter()
+        .find(|event_type| EventType::parse(event_type).is_none())
+    {
+        let mut error = ValidationError::new("event_type_unknown");
+        error.message = Some(
+            format!(
+                "Unknown event type '{}', expected one of: {}.",
+                unknown,
+                EventType::ALL
+                    .map(|event_type| event_type.as_str())
+                    .join(", ")
+            )
+            .into(),
+        );
+        return Err(error);
+    }
+
+    Ok(())
+}
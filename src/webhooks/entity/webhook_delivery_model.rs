@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{prelude::FromRow, Type};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Type, PartialEq)]
+#[sqlx(type_name = "webhook_delivery_status")]
+#[serde(rename_all = "UPPERCASE")]
+pub enum WebhookDeliveryStatus {
+    PENDING,
+    SUCCEEDED,
+    FAILED,
+}
+
+/// One outbox event sent to one endpoint. `payload` is the exact body that is signed
+/// and posted.
+#[derive(Debug, FromRow, Serialize, Clone)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub endpoint_id: Uuid,
+    pub outbox_id: i64,
+    pub redelivery_of: Option<i64>,
+    pub event_type: String,
+    pub payload: Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+/// Pending delivery locked by the delivery job, with what is needed to send it.
+#[derive(Debug, FromRow)]
+pub struct ClaimedDelivery {
+    #[sqlx(flatten)]
+    pub delivery: WebhookDelivery,
+    pub url: String,
+    pub secret: String,
+}
@@ -0,0 +1,140 @@
+use crate::{
+    middlewares::middleware_auth::JwtAuthMiddleware,
+    server::AppState,
+    utils::{errors::AppError, query_paginaton::QueryPagination},
+    webhooks::{
+        dto::{CreateWebhookDTO, UpdateWebhookDTO},
+        webhooks_service,
+    },
+};
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde_qs::actix::QsQuery;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub fn configure(cfg: &mut web::ServiceConfig, app_state: web::Data<AppState>) {
+    cfg.service(
+        web::scope("/admin/webhooks")
+            .wrap(JwtAuthMiddleware::new(app_state))
+            .service(
+                web::resource("/{id}/deliveries/{delivery_id}/redeliver")
+                    .route(web::post().to(redeliver)),
+            )
+            .service(
+                web::resource("/{id}/deliveries/{delivery_id}").route(web::get().to(find_delivery)),
+            )
+            .service(web::resource("/{id}/deliveries").route(web::get().to(find_deliveries)))
+            .service(
+                web::resource("/{id}")
+                    .route(web::get().to(find))
+                    .route(web::put().to(update))
+                    .route(web::delete().to(delete)),
+            )
+            .service(
+                web::resource("")
+                    .route(web::get().to(find_all))
+                    .route(web::post().to(create)),
+            ),
+    );
+}
+
+async fn create(
+    pool: web::Data<PgPool>,
+    payload: web::Json<CreateWebhookDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match webhooks_service::create(&pool, payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_all(
+    pool: web::Data<PgPool>,
+    query_pagination: QsQuery<QueryPagination>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match webhooks_service::find_all(&pool, query_pagination.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match webhooks_service::find(&pool, id.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn update(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    payload: web::Json<UpdateWebhookDTO>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match webhooks_service::update(&pool, id.into_inner(), payload.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn delete(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match webhooks_service::delete(&pool, id.into_inner(), &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_deliveries(
+    pool: web::Data<PgPool>,
+    id: web::Path<Uuid>,
+    query_pagination: QsQuery<QueryPagination>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    match webhooks_service::find_deliveries(
+        &pool,
+        id.into_inner(),
+        query_pagination.into_inner(),
+        &req,
+    )
+    .await
+    {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn find_delivery(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, i64)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, delivery_id) = path.into_inner();
+
+    match webhooks_service::find_delivery(&pool, id, delivery_id, &req).await {
+        Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(err) => Err(err),
+    }
+}
+
+async fn redeliver(
+    pool: web::Data<PgPool>,
+    path: web::Path<(Uuid, i64)>,
+    req: HttpRequest,
+) -> Result<HttpResponse, AppError> {
+    let (id, delivery_id) = path.into_inner();
+
+    match webhooks_service::redeliver(&pool, id, delivery_id, &req).await {
+        Ok(response) => Ok(HttpResponse::Created().json(response)),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,491 @@
+use crate::{
+    events::entity::OutboxEvent,
+    utils::{errors::AppError, query_paginaton::ResultWithPagination},
+    webhooks::{
+        dto::{CreateWebhookDTO, UpdateWebhookDTO},
+        entity::{ClaimedDelivery, WebhookDelivery, WebhookDeliveryStatus, WebhookEndpoint},
+    },
+};
+use chrono::{DateTime, Utc};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+pub async fn create_endpoint(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    payload: CreateWebhookDTO,
+    secret: &str,
+    created_by: Option<Uuid>,
+) -> Result<WebhookEndpoint, AppError> {
+    let result = sqlx::query_as::<_, WebhookEndpoint>(
+        r#"--sql
+        INSERT INTO
+            webhook_endpoints (
+                organization_id,
+                url,
+                description,
+                secret,
+                event_types,
+                created_by
+            )
+        VALUES
+            ($1, $2, $3, $4, $5, $6)
+        RETURNING
+            *
+        "#,
+    )
+    .bind(organization_id)
+    .bind(payload.url)
+    .bind(payload.description)
+    .bind(secret)
+    .bind(payload.event_types)
+    .bind(created_by)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+pub async fn find_endpoints(
+    pool: &PgPool,
+    organization_id: Uuid,
+    limit: i64,
+    offset: i64,
+    page: i64,
+) -> Result<ResultWithPagination<Vec<WebhookEndpoint>>, AppError> {
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(*)
+        FROM
+            webhook_endpoints
+        WHERE
+            organization_id = $1
+        "#,
+    )
+    .bind(organization_id)
+    .fetch_one(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    let result = sqlx::query_as::<_, WebhookEndpoint>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            webhook_endpoints
+        WHERE
+            organization_id = $1
+        ORDER BY
+            created_at DESC, id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(organization_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(ResultWithPagination::new(
+        limit,
+        page,
+        count,
+        result.len(),
+        result,
+    ))
+}
+
+pub async fn find_endpoint(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    id: Uuid,
+) -> Result<WebhookEndpoint, AppError> {
+    let result = sqlx::query_as::<_, WebhookEndpoint>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            webhook_endpoints
+        WHERE
+            id = $1
+            AND organization_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(organization_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Webhook with ID {} not found",
+        id
+    )))?;
+
+    Ok(result)
+}
+
+/// Enabling clears the failures that disabled the endpoint, disabling keeps the time
+/// it was first disabled.
+pub async fn update_endpoint(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    id: Uuid,
+    payload: UpdateWebhookDTO,
+) -> Result<WebhookEndpoint, AppError> {
+    let result = sqlx::query_as::<_, WebhookEndpoint>(
+        r#"--sql
+        UPDATE
+            webhook_endpoints
+        SET
+            url = $1,
+            description = $2,
+            event_types = $3,
+            enabled = $4,
+            consecutive_failures = CASE WHEN $4 THEN 0 ELSE consecutive_failures END,
+            disabled_at = CASE WHEN $4 THEN NULL ELSE COALESCE(disabled_at, $5) END,
+            updated_at = $5
+        WHERE
+            id = $6
+            AND organization_id = $7
+        RETURNING
+            *
+        "#,
+    )
+    .bind(payload.url)
+    .bind(payload.description)
+    .bind(payload.event_types)
+    .bind(payload.enabled)
+    .bind(Utc::now())
+    .bind(id)
+    .bind(organization_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Webhook with ID {} not found",
+        id
+    )))?;
+
+    Ok(result)
+}
+
+pub async fn delete_endpoint(
+    conn: &mut PgConnection,
+    organization_id: Uuid,
+    id: Uuid,
+) -> Result<WebhookEndpoint, AppError> {
+    let result = sqlx::query_as::<_, WebhookEndpoint>(
+        r#"--sql
+        DELETE FROM webhook_endpoints
+        WHERE
+            id = $1
+            AND organization_id = $2
+        RETURNING
+            *
+        "#,
+    )
+    .bind(id)
+    .bind(organization_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Webhook with ID {} not found",
+        id
+    )))?;
+
+    Ok(result)
+}
+
+/// Queues the event for every enabled endpoint subscribed to its type in the
+/// organizations of the event, returns how many deliveries were queued. Publishing the
+/// same event again queues nothing new.
+pub async fn enqueue_deliveries(pool: &PgPool, event: &OutboxEvent) -> Result<u64, AppError> {
+    let payload =
+        serde_json::to_value(event).map_err(|e| AppError::InternalServerError(e.to_string()))?;
+
+    let result = sqlx::query(
+        r#"--sql
+        INSERT INTO
+            webhook_deliveries (endpoint_id, outbox_id, event_type, payload)
+        SELECT
+            id, $1, $2, $3
+        FROM
+            webhook_endpoints
+        WHERE
+            enabled
+            AND $2 = ANY (event_types)
+            AND organization_id = ANY ($4)
+        ON CONFLICT (endpoint_id, outbox_id) WHERE redelivery_of IS NULL DO NOTHING
+        "#,
+    )
+    .bind(event.id)
+    .bind(&event.event_type)
+    .bind(payload)
+    .bind(&event.organization_ids)
+    .execute(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result.rows_affected())
+}
+
+pub async fn find_deliveries(
+    pool: &PgPool,
+    endpoint_id: Uuid,
+    limit: i64,
+    offset: i64,
+    page: i64,
+) -> Result<ResultWithPagination<Vec<WebhookDelivery>>, AppError> {
+    let count: i64 = sqlx::query_scalar::<_, i64>(
+        r#"--sql
+        SELECT
+            COUNT(*)
+        FROM
+            webhook_deliveries
+        WHERE
+            endpoint_id = $1
+        "#,
+    )
+    .bind(endpoint_id)
+    .fetch_one(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    let result = sqlx::query_as::<_, WebhookDelivery>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            webhook_deliveries
+        WHERE
+            endpoint_id = $1
+        ORDER BY
+            created_at DESC, id DESC
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(endpoint_id)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(ResultWithPagination::new(
+        limit,
+        page,
+        count,
+        result.len(),
+        result,
+    ))
+}
+
+pub async fn find_delivery(
+    conn: &mut PgConnection,
+    endpoint_id: Uuid,
+    id: i64,
+) -> Result<WebhookDelivery, AppError> {
+    let result = sqlx::query_as::<_, WebhookDelivery>(
+        r#"--sql
+        SELECT
+            *
+        FROM
+            webhook_deliveries
+        WHERE
+            id = $1 AND endpoint_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(endpoint_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?
+    .ok_or(AppError::NotFound(format!(
+        "Delivery with ID {} not found",
+        id
+    )))?;
+
+    Ok(result)
+}
+
+/// Queues the payload of a past delivery again, the original stays in the log.
+pub async fn create_redelivery(
+    conn: &mut PgConnection,
+    delivery: &WebhookDelivery,
+) -> Result<WebhookDelivery, AppError> {
+    let result = sqlx::query_as::<_, WebhookDelivery>(
+        r#"--sql
+        INSERT INTO
+            webhook_deliveries (endpoint_id, outbox_id, redelivery_of, event_type, payload)
+        VALUES
+            ($1, $2, $3, $4, $5)
+        RETURNING
+            *
+        "#,
+    )
+    .bind(delivery.endpoint_id)
+    .bind(delivery.outbox_id)
+    .bind(delivery.id)
+    .bind(&delivery.event_type)
+    .bind(&delivery.payload)
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Locks up to `limit` due deliveries of enabled endpoints. Locked rows are skipped, so
+/// replicas share the work.
+pub async fn claim_deliveries(
+    conn: &mut PgConnection,
+    limit: i64,
+) -> Result<Vec<ClaimedDelivery>, AppError> {
+    let result = sqlx::query_as::<_, ClaimedDelivery>(
+        r#"--sql
+        SELECT
+            webhook_deliveries.*,
+            webhook_endpoints.url,
+            webhook_endpoints.secret
+        FROM
+            webhook_deliveries
+            JOIN webhook_endpoints ON webhook_endpoints.id = webhook_deliveries.endpoint_id
+        WHERE
+            webhook_deliveries.status = $1
+            AND webhook_deliveries.next_attempt_at <= $2
+            AND webhook_endpoints.enabled
+        ORDER BY
+            webhook_deliveries.next_attempt_at, webhook_deliveries.id
+        LIMIT
+            $3
+        FOR UPDATE OF
+            webhook_deliveries SKIP LOCKED
+        "#,
+    )
+    .bind(WebhookDeliveryStatus::PENDING)
+    .bind(Utc::now())
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result)
+}
+
+/// Records one attempt. `status` stays `PENDING` while `next_attempt_at` is set.
+pub async fn record_delivery_attempt(
+    conn: &mut PgConnection,
+    id: i64,
+    status: WebhookDeliveryStatus,
+    response_status: Option<i32>,
+    error: Option<String>,
+    next_attempt_at: Option<DateTime<Utc>>,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            webhook_deliveries
+        SET
+            status = $1,
+            attempts = attempts + 1,
+            last_attempt_at = $2,
+            next_attempt_at = COALESCE($3, next_attempt_at),
+            response_status = $4,
+            last_error = $5,
+            delivered_at = CASE WHEN $1 = 'SUCCEEDED'::webhook_delivery_status THEN $2 END
+        WHERE
+            id = $6
+        "#,
+    )
+    .bind(status)
+    .bind(now)
+    .bind(next_attempt_at)
+    .bind(response_status)
+    .bind(error)
+    .bind(id)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+pub async fn reset_endpoint_failures(conn: &mut PgConnection, id: Uuid) -> Result<(), AppError> {
+    sqlx::query(
+        r#"--sql
+        UPDATE
+            webhook_endpoints
+        SET
+            consecutive_failures = 0
+        WHERE
+            id = $1 AND consecutive_failures > 0
+        "#,
+    )
+    .bind(id)
+    .execute(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(())
+}
+
+/// Counts a failed attempt against the endpoint and disables it once `disable_after`
+/// attempts in a row failed, returns whether this failure disabled it.
+pub async fn record_endpoint_failure(
+    conn: &mut PgConnection,
+    id: Uuid,
+    disable_after: i32,
+) -> Result<bool, AppError> {
+    let result = sqlx::query_scalar::<_, bool>(
+        r#"--sql
+        UPDATE
+            webhook_endpoints
+        SET
+            consecutive_failures = consecutive_failures + 1,
+            enabled = enabled AND consecutive_failures + 1 < $1,
+            disabled_at = CASE
+                WHEN enabled AND consecutive_failures + 1 >= $1 THEN $2
+                ELSE disabled_at
+            END
+        WHERE
+            id = $3
+        RETURNING
+            disabled_at IS NOT DISTINCT FROM $2
+        "#,
+    )
+    .bind(disable_after)
+    .bind(Utc::now())
+    .bind(id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result.unwrap_or(false))
+}
+
+/// Removes finished deliveries created before `cutoff`, returns how many were removed.
+pub async fn delete_finished_deliveries(
+    pool: &PgPool,
+    cutoff: DateTime<Utc>,
+) -> Result<u64, AppError> {
+    let result = sqlx::query(
+        r#"--sql
+        DELETE FROM webhook_deliveries
+        WHERE
+            status != $1 AND created_at < $2
+        "#,
+    )
+    .bind(WebhookDeliveryStatus::PENDING)
+    .bind(cutoff)
+    .execute(pool)
+    .await
+    .map_err(AppError::DatabaseError)?;
+
+    Ok(result.rows_affected())
+}
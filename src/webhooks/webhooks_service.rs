@@ -0,0 +1,241 @@
+use crate::{
+    audit::{audit_query, entity::AuditAction},
+    utils::{
+        audit::{diff, AuditContext},
+        auth::{organization_id_in_token, user_id_in_token, validate_org_admin_in_token},
+        errors::AppError,
+        query_paginaton::QueryPagination,
+        response_data::{ResponseData, ResponseDatas},
+        webhook::generate_webhook_secret,
+    },
+    webhooks::{
+        dto::{CreateWebhookDTO, CreatedWebhookDTO, UpdateWebhookDTO},
+        entity::{WebhookDelivery, WebhookDeliveryStatus, WebhookEndpoint},
+        webhooks_query,
+    },
+};
+use actix_web::HttpRequest;
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+pub async fn create(
+    pool: &PgPool,
+    payload: CreateWebhookDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<CreatedWebhookDTO>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let secret = generate_webhook_secret();
+
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+    let endpoint = webhooks_query::create_endpoint(
+        &mut tx,
+        organization_id,
+        payload,
+        &secret,
+        Some(user_id_in_token(req)?),
+    )
+    .await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::WebhookCreated,
+        endpoint.id,
+        diff(None, Some(&endpoint)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        CreatedWebhookDTO { endpoint, secret },
+        "Data has been successfuly created.",
+    ))
+}
+
+pub async fn find_all(
+    pool: &PgPool,
+    query_pagination: QueryPagination,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<WebhookEndpoint>>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+
+    if query_pagination.after.is_some() || query_pagination.before.is_some() {
+        return Err(AppError::BadRequest(
+            "Cursor pagination is not supported for webhooks.".to_string(),
+        ));
+    }
+
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let result = webhooks_query::find_endpoints(pool, organization_id, limit, offset, page).await?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        result.data,
+    ))
+}
+
+pub async fn find(
+    pool: &PgPool,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<WebhookEndpoint>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+
+    let mut conn = pool.acquire().await.map_err(AppError::DatabaseError)?;
+    let endpoint = webhooks_query::find_endpoint(&mut conn, organization_id, id).await?;
+
+    Ok(ResponseData::new(
+        endpoint,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+pub async fn update(
+    pool: &PgPool,
+    id: Uuid,
+    payload: UpdateWebhookDTO,
+    req: &HttpRequest,
+) -> Result<ResponseData<WebhookEndpoint>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+    payload.validate().map_err(AppError::ValidationError)?;
+
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+    let before = webhooks_query::find_endpoint(&mut tx, organization_id, id).await?;
+    let endpoint = webhooks_query::update_endpoint(&mut tx, organization_id, id, payload).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::WebhookUpdated,
+        id,
+        diff(Some(&before), Some(&endpoint)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        endpoint,
+        "Data has been successfuly updated.",
+    ))
+}
+
+pub async fn delete(
+    pool: &PgPool,
+    id: Uuid,
+    req: &HttpRequest,
+) -> Result<ResponseData<WebhookEndpoint>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+    let endpoint = webhooks_query::delete_endpoint(&mut tx, organization_id, id).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::WebhookDeleted,
+        id,
+        diff(Some(&endpoint), None),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        endpoint,
+        "Data has been successfuly deleted.",
+    ))
+}
+
+pub async fn find_deliveries(
+    pool: &PgPool,
+    id: Uuid,
+    query_pagination: QueryPagination,
+    req: &HttpRequest,
+) -> Result<ResponseDatas<Vec<WebhookDelivery>>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+
+    if query_pagination.after.is_some() || query_pagination.before.is_some() {
+        return Err(AppError::BadRequest(
+            "Cursor pagination is not supported for webhook deliveries.".to_string(),
+        ));
+    }
+
+    let mut conn = pool.acquire().await.map_err(AppError::DatabaseError)?;
+    webhooks_query::find_endpoint(&mut conn, organization_id, id).await?;
+
+    let (limit, offset, page) = query_pagination.paginate()?;
+    let result = webhooks_query::find_deliveries(pool, id, limit, offset, page).await?;
+
+    Ok(ResponseDatas::new(
+        result.limit,
+        result.page,
+        result.count,
+        result.current_count,
+        result.data,
+    ))
+}
+
+pub async fn find_delivery(
+    pool: &PgPool,
+    id: Uuid,
+    delivery_id: i64,
+    req: &HttpRequest,
+) -> Result<ResponseData<WebhookDelivery>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+
+    let mut conn = pool.acquire().await.map_err(AppError::DatabaseError)?;
+    webhooks_query::find_endpoint(&mut conn, organization_id, id).await?;
+    let delivery = webhooks_query::find_delivery(&mut conn, id, delivery_id).await?;
+
+    Ok(ResponseData::new(
+        delivery,
+        "Data has been successfuly retrieved.",
+    ))
+}
+
+/// Queues a finished delivery again with its original payload and event id, receivers
+/// deduplicating on the id treat it as the same event.
+pub async fn redeliver(
+    pool: &PgPool,
+    id: Uuid,
+    delivery_id: i64,
+    req: &HttpRequest,
+) -> Result<ResponseData<WebhookDelivery>, AppError> {
+    validate_org_admin_in_token(req)?;
+    let organization_id = organization_id_in_token(req)?;
+
+    let mut tx = pool.begin().await.map_err(AppError::DatabaseError)?;
+    webhooks_query::find_endpoint(&mut tx, organization_id, id).await?;
+    let delivery = webhooks_query::find_delivery(&mut tx, id, delivery_id).await?;
+
+    if delivery.status == WebhookDeliveryStatus::PENDING {
+        return Err(AppError::Conflict(
+            "Delivery is still pending, it will be retried automatically.".to_string(),
+        ));
+    }
+
+    let redelivery = webhooks_query::create_redelivery(&mut tx, &delivery).await?;
+    audit_query::insert_audit_event(
+        &mut tx,
+        &AuditContext::from_request(req),
+        AuditAction::WebhookRedelivered,
+        id,
+        diff(None, Some(&redelivery)),
+    )
+    .await?;
+    tx.commit().await.map_err(AppError::DatabaseError)?;
+
+    Ok(ResponseData::new(
+        redelivery,
+        "Data has been successfuly created.",
+    ))
+}
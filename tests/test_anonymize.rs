@@ -0,0 +1,219 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+        refresh_token,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        organizations::entity::MembershipRole,
+        router::configure_v1,
+        users::entity::UserRole,
+        utils::{
+            avatar::{avatar_key, AvatarSize},
+            password::UNUSABLE_PASSWORD,
+        },
+    };
+
+    async fn fill_profile(pool: &PgPool, id: Uuid) {
+        sqlx::query(
+            r#"--sql
+            UPDATE
+                users
+            SET
+                display_name = 'Anon Display',
+                phone = '+15551234567',
+                bio = 'Personal bio',
+                metadata = '{"plan": "pro"}',
+                avatar_updated_at = now()
+            WHERE
+                id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    /// Asserts every personal field is scrubbed and the account can no longer sign in.
+    async fn assert_scrubbed(pool: &PgPool, id: Uuid) {
+        let user: Value = sqlx::query_scalar("SELECT to_jsonb(users) FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+        assert_eq!(user["name"], "Deleted user");
+        assert_eq!(user["email"], format!("deleted-{}@anonymized.invalid", id));
+        assert_eq!(user["password"], UNUSABLE_PASSWORD);
+        for field in ["display_name", "phone", "bio", "avatar_updated_at"] {
+            assert_eq!(user[field], Value::Null, "{}", field);
+        }
+        assert_eq!(user["metadata"], json!({}));
+        assert_eq!(user["status"], "DELETED");
+        assert!(user["anonymized_at"].is_string());
+    }
+
+    /// Asserts the erasure was audited as done by `actor` and announced as a permanent deletion.
+    async fn assert_recorded(pool: &PgPool, id: Uuid, actor: Uuid) {
+        let actors: Vec<Option<Uuid>> = sqlx::query_scalar(
+            "SELECT actor_id FROM audit_events WHERE target_id = $1 AND action = 'user.anonymized'",
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+        assert_eq!(actors, vec![Some(actor)]);
+
+        let payloads: Vec<Value> = sqlx::query_scalar(
+            "SELECT payload FROM outbox WHERE aggregate_id = $1 AND event_type = 'user.deleted'",
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .unwrap();
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0]["permanent"], true);
+    }
+
+    #[actix_web::test]
+    async fn test_user_anonymizes_itself() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Anon Self", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        fill_profile(&pool, user).await;
+        let key = avatar_key(user, AvatarSize::Small);
+        state.storage.put(&key, b"avatar".to_vec()).await.unwrap();
+        let token = access_token(&state, user, UserRole::USER, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::delete()
+                .uri(&format!("/api/V1/users/{}?mode=anonymize", user))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .insert_header((header::IF_MATCH, "*"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(body["data"]["name"], "Deleted user");
+
+        assert_scrubbed(&pool, user).await;
+        assert_recorded(&pool, user, user).await;
+        assert!(state.storage.get(&key).await.unwrap().is_none());
+
+        // Tokens issued before the erasure no longer work.
+        let err = test::try_call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/users/{}", user))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request(),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::post()
+                .uri("/api/V1/auth/refresh")
+                .set_json(json!({ "refresh_token": refresh_token(&state, user, Some(org)) }))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_only_organization_admins_anonymize_other_users() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let admin = insert_user(&pool, "Anon Admin", UserRole::USER).await;
+        let member = insert_user(&pool, "Anon Member", UserRole::USER).await;
+        let target = insert_user(&pool, "Anon Target", UserRole::USER).await;
+        let outsider = insert_user(&pool, "Anon Outsider", UserRole::USER).await;
+        let org = insert_organization(
+            &pool,
+            &[
+                (admin, MembershipRole::ADMIN),
+                (member, MembershipRole::MEMBER),
+                (target, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        insert_organization(&pool, &[(outsider, MembershipRole::MEMBER)]).await;
+        fill_profile(&pool, target).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let member_token = access_token(&state, member, UserRole::USER, Some(org));
+        let admin_token = access_token(&state, admin, UserRole::USER, Some(org));
+        let anonymize = |token: &str, id: Uuid| {
+            test::TestRequest::post()
+                .uri(&format!("/api/V1/admin/users/{}/anonymize", id))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, anonymize(&member_token, target)).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::delete()
+                .uri(&format!("/api/V1/users/{}?mode=anonymize", target))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", member_token)))
+                .insert_header((header::IF_MATCH, "*"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let untouched: Option<String> =
+            sqlx::query_scalar("SELECT display_name FROM users WHERE id = $1")
+                .bind(target)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(untouched.as_deref(), Some("Anon Display"));
+
+        let res = test::call_service(&app, anonymize(&admin_token, outsider)).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res = test::call_service(&app, anonymize(&admin_token, target)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_scrubbed(&pool, target).await;
+        assert_recorded(&pool, target, admin).await;
+
+        let res = test::call_service(&app, anonymize(&admin_token, target)).await;
+        assert_eq!(res.status(), StatusCode::CONFLICT);
+    }
+}
@@ -0,0 +1,138 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        app_state, connect, connect_as_app, insert_organization, insert_user, request_as,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        audit::{audit_query, entity::AuditAction},
+        organizations::entity::MembershipRole,
+        router::configure_v1,
+        users::{entity::UserRole, users_service},
+        utils::{
+            audit::{change, AuditContext, REDACTED},
+            password::hash_password,
+        },
+    };
+
+    async fn changes_of(pool: &PgPool, target_id: Uuid) -> Vec<Value> {
+        sqlx::query_scalar(
+            "SELECT changes FROM audit_events WHERE target_id = $1 ORDER BY occurred_at",
+        )
+        .bind(target_id)
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_hard_delete_keeps_field_names_only() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let state = app_state(Arc::default());
+        let admin = insert_user(&pool, "Audit Admin", UserRole::ADMIN).await;
+        let user = insert_user(&pool, "Audit Target", UserRole::USER).await;
+
+        let mut conn = pool.acquire().await.unwrap();
+        audit_query::insert_audit_event(
+            &mut conn,
+            &AuditContext::system().with_actor(admin),
+            AuditAction::UserUpdated,
+            user,
+            json!({ "name": change("name", json!("Old Name"), json!("Audit Target")) }),
+        )
+        .await
+        .unwrap();
+        drop(conn);
+
+        // The admin's export shows which fields they changed on others, not the values.
+        let exported = audit_query::find_user_audit_events(&app_pool, admin)
+            .await
+            .unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(
+            exported[0].changes,
+            json!({ "name": { "before": REDACTED, "after": REDACTED } })
+        );
+
+        let req = request_as(user, UserRole::USER, None);
+        users_service::soft_delete(&app_pool, user, &req)
+            .await
+            .unwrap();
+        users_service::delete(&app_pool, &state, user, &req)
+            .await
+            .unwrap();
+
+        let changes = changes_of(&pool, user).await;
+        assert_eq!(changes.len(), 3);
+        assert!(changes[2].as_object().unwrap().contains_key("email"));
+        for changes in changes {
+            for value in changes.as_object().unwrap().values() {
+                assert_eq!(value["before"], json!(REDACTED));
+                assert!(value["after"] == json!(REDACTED) || value["after"].is_null());
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_login_is_audited_in_the_organization_logged_in_to() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Audit Login", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        sqlx::query("UPDATE users SET password = $2 WHERE id = $1")
+            .bind(user)
+            .bind(hash_password("Login-Passw0rd!").unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let login = |password: &str| {
+            test::TestRequest::post()
+                .uri("/api/V1/auth/login")
+                .insert_header((header::USER_AGENT, "audit-test"))
+                .set_json(json!({ "email": format!("{}@example.com", user), "password": password }))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, login("Wrong-Passw0rd!")).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = test::call_service(&app, login("Login-Passw0rd!")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let events: Vec<(Option<Uuid>, Option<Uuid>, Option<String>)> = sqlx::query_as(
+            r#"--sql
+            SELECT
+                actor_id, organization_id, user_agent
+            FROM
+                audit_events
+            WHERE
+                target_id = $1 AND action = 'user.logged_in'
+            "#,
+        )
+        .bind(user)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            events,
+            vec![(Some(user), Some(org), Some("audit-test".to_string()))]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{access_token, app_state, connect, connect_as_app, insert_user};
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use image::{ImageFormat, Rgb, RgbImage};
+    use std::{env, io::Cursor, sync::Arc};
+    use uuid::Uuid;
+    use web_server::{router::configure_v1, users::entity::UserRole};
+
+    const BOUNDARY: &str = "avatar-test-boundary";
+
+    fn encoded_image(color: [u8; 3], format: ImageFormat) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        RgbImage::from_pixel(300, 300, Rgb(color))
+            .write_to(&mut Cursor::new(&mut bytes), format)
+            .unwrap();
+        bytes
+    }
+
+    fn multipart_body(content_type: &str, file: &[u8]) -> Vec<u8> {
+        let mut body = format!(
+            "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"avatar\"; filename=\"avatar\"\r\nContent-Type: {content_type}\r\n\r\n"
+        )
+        .into_bytes();
+        body.extend_from_slice(file);
+        body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+        body
+    }
+
+    fn upload(user: Uuid, token: &str, content_type: &str, file: &[u8]) -> test::TestRequest {
+        test::TestRequest::put()
+            .uri(&format!("/api/V1/users/{}/avatar", user))
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+            .insert_header((
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            ))
+            .set_payload(multipart_body(content_type, file))
+    }
+
+    fn download(user: Uuid, token: &str, size: &str) -> test::TestRequest {
+        test::TestRequest::get()
+            .uri(&format!("/api/V1/users/{}/avatar?size={}", user, size))
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+    }
+
+    fn stored_files(user: Uuid) -> usize {
+        std::fs::read_dir(
+            env::temp_dir()
+                .join("web_server_test")
+                .join("avatars")
+                .join(user.to_string()),
+        )
+        .map(|entries| entries.count())
+        .unwrap_or(0)
+    }
+
+    #[actix_web::test]
+    async fn test_uploaded_avatar_is_served_with_cache_headers() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Avatar User", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        for (content_type, format) in [
+            ("image/png", ImageFormat::Png),
+            ("image/jpeg", ImageFormat::Jpeg),
+        ] {
+            let file = encoded_image([200, 30, 30], format);
+            let res =
+                test::call_service(&app, upload(user, &token, content_type, &file).to_request())
+                    .await;
+            assert_eq!(res.status(), StatusCode::OK, "{}", content_type);
+            assert!(res.headers().contains_key(header::ETAG));
+
+            let res = test::call_service(&app, download(user, &token, "small").to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let headers = res.headers().clone();
+            assert_eq!(headers.get(header::CONTENT_TYPE).unwrap(), "image/png");
+            assert_eq!(
+                headers.get(header::CACHE_CONTROL).unwrap(),
+                "private, max-age=86400"
+            );
+            assert!(headers.contains_key(header::LAST_MODIFIED));
+            let etag = headers.get(header::ETAG).unwrap().clone();
+
+            let served = image::load_from_memory(&test::read_body(res).await).unwrap();
+            assert_eq!((served.width(), served.height()), (64, 64));
+
+            let res = test::call_service(
+                &app,
+                download(user, &token, "small")
+                    .insert_header((header::IF_NONE_MATCH, etag))
+                    .to_request(),
+            )
+            .await;
+            assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        }
+        assert_eq!(stored_files(user), 3);
+    }
+
+    #[actix_web::test]
+    async fn test_avatar_upload_rejects_non_images_and_oversized_files() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Avatar Rejected User", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        // The declared content type is not trusted, only the file's magic bytes.
+        let res = test::call_service(
+            &app,
+            upload(user, &token, "image/png", b"<html>not an image</html>").to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let res = test::call_service(
+            &app,
+            upload(user, &token, "text/plain", b"plain text").to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let mut oversized = encoded_image([0, 0, 0], ImageFormat::Png);
+        oversized.resize(*state.avatar_max_size + 1, 0);
+        let res = test::call_service(
+            &app,
+            upload(user, &token, "image/png", &oversized).to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let res = test::call_service(&app, download(user, &token, "medium").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(stored_files(user), 0);
+    }
+
+    #[actix_web::test]
+    async fn test_replacing_an_avatar_replaces_the_stored_files() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Avatar Replaced User", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let mut served = Vec::new();
+        for color in [[255, 0, 0], [0, 0, 255]] {
+            let file = encoded_image(color, ImageFormat::Png);
+            let res =
+                test::call_service(&app, upload(user, &token, "image/png", &file).to_request())
+                    .await;
+            assert_eq!(res.status(), StatusCode::OK);
+
+            let res = test::call_service(&app, download(user, &token, "medium").to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let etag = res.headers().get(header::ETAG).unwrap().clone();
+            let image = image::load_from_memory(&test::read_body(res).await)
+                .unwrap()
+                .to_rgb8();
+            served.push((etag, *image.get_pixel(0, 0)));
+        }
+
+        assert_ne!(served[0].0, served[1].0);
+        assert_eq!(served[1].1, Rgb([0, 0, 255]));
+        assert_eq!(stored_files(user), 3);
+    }
+}
@@ -0,0 +1,28 @@
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+    use web_server::utils::backoff::Backoff;
+
+    #[test]
+    fn test_delay_doubles_up_to_the_maximum() {
+        let backoff = Backoff::new(5, 60);
+
+        assert_eq!(backoff.delay(0), Duration::seconds(5));
+        assert_eq!(backoff.delay(1), Duration::seconds(5));
+        assert_eq!(backoff.delay(2), Duration::seconds(10));
+        assert_eq!(backoff.delay(4), Duration::seconds(40));
+        assert_eq!(backoff.delay(5), Duration::seconds(60));
+        assert_eq!(backoff.delay(1000), Duration::seconds(60));
+    }
+
+    #[test]
+    fn test_jitter_adds_at_most_half_the_delay() {
+        let backoff = Backoff::new(10, 21600);
+
+        for attempts in 1..30 {
+            let delay = backoff.delay(attempts);
+            let jittered = backoff.delay_with_jitter(attempts);
+            assert!(jittered >= delay && jittered <= delay + delay / 2);
+        }
+    }
+}
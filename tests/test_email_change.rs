@@ -0,0 +1,237 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+        RecordingMailer,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test::{self, TestRequest},
+        web, App,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        organizations::entity::MembershipRole, router::configure_v1, users::entity::UserRole,
+    };
+
+    /// Token of the last mail sent to `to`.
+    fn token_for(mailer: &RecordingMailer, to: &str) -> String {
+        let sent = mailer.sent.lock().unwrap();
+        let body = &sent.iter().rev().find(|mail| mail.to == to).unwrap().body;
+        let start = body.find("token=").unwrap() + "token=".len();
+        body[start..].split_whitespace().next().unwrap().to_string()
+    }
+
+    /// Link of the last mail sent to `to`, relative to the base URL of the app.
+    fn link_for(mailer: &RecordingMailer, to: &str) -> String {
+        let sent = mailer.sent.lock().unwrap();
+        let body = &sent.iter().rev().find(|mail| mail.to == to).unwrap().body;
+        let start = body.find("http://localhost:8080/").unwrap() + "http://localhost:8080".len();
+        body[start..].split_whitespace().next().unwrap().to_string()
+    }
+
+    fn post(uri: &str, token: Option<&str>, body: Value) -> TestRequest {
+        let req = TestRequest::post().uri(uri).set_json(body);
+        match token {
+            Some(token) => req.insert_header((header::AUTHORIZATION, format!("Bearer {}", token))),
+            None => req,
+        }
+    }
+
+    async fn email_of(pool: &sqlx::PgPool, id: Uuid) -> String {
+        sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_update_points_email_changes_to_their_flow() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Updating User", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let req = TestRequest::put()
+            .uri(&format!("/api/V1/users/{}", user))
+            .insert_header((
+                header::AUTHORIZATION,
+                format!(
+                    "Bearer {}",
+                    access_token(&state, user, UserRole::USER, Some(org))
+                ),
+            ))
+            .set_json(json!({ "name": "Updating User", "email": "moved@example.com" }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+        assert!(
+            body.contains(&format!("POST /api/V1/users/{}/email-change", user)),
+            "{}",
+            body
+        );
+        assert_eq!(email_of(&pool, user).await, format!("{}@example.com", user));
+    }
+
+    #[actix_web::test]
+    async fn test_email_change_applies_once_the_new_address_confirms() {
+        let pool = connect().await;
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = app_state(mailer.clone());
+        let user = insert_user(&pool, "Moving User", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let token = access_token(&state, user, UserRole::USER, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let old_email = format!("{}@example.com", user);
+        let new_email = format!("{}@moved.example.com", user);
+        let req = post(
+            &format!("/api/V1/users/{}/email-change", user),
+            Some(&token),
+            json!({ "email": new_email }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert!(status.is_success(), "{}", status);
+        assert_eq!(email_of(&pool, user).await, old_email);
+
+        // The current address only gets a link to cancel the change.
+        let cancel = token_for(&mailer, &old_email);
+        let confirm = token_for(&mailer, &new_email);
+        assert_ne!(cancel, confirm);
+
+        let req = post(
+            "/api/V1/auth/confirm-email-change",
+            None,
+            json!({ "token": confirm }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(email_of(&pool, user).await, new_email);
+
+        let req = post(
+            "/api/V1/auth/confirm-email-change",
+            None,
+            json!({ "token": confirm }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert!(status.is_client_error(), "{}", status);
+    }
+
+    #[actix_web::test]
+    async fn test_cancelled_email_change_cannot_be_confirmed() {
+        let pool = connect().await;
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = app_state(mailer.clone());
+        let user = insert_user(&pool, "Staying User", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let token = access_token(&state, user, UserRole::USER, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let old_email = format!("{}@example.com", user);
+        let new_email = format!("{}@moved.example.com", user);
+        let req = post(
+            &format!("/api/V1/users/{}/email-change", user),
+            Some(&token),
+            json!({ "email": new_email }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert!(status.is_success(), "{}", status);
+
+        let req = post(
+            "/api/V1/auth/cancel-email-change",
+            None,
+            json!({ "token": token_for(&mailer, &old_email) }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert_eq!(status, StatusCode::OK);
+
+        let req = post(
+            "/api/V1/auth/confirm-email-change",
+            None,
+            json!({ "token": token_for(&mailer, &new_email) }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert!(status.is_client_error(), "{}", status);
+        assert_eq!(email_of(&pool, user).await, old_email);
+    }
+
+    #[actix_web::test]
+    async fn test_email_change_links_resolve_to_previews() {
+        let pool = connect().await;
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = app_state(mailer.clone());
+        let user = insert_user(&pool, "Linked User", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let token = access_token(&state, user, UserRole::USER, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let old_email = format!("{}@example.com", user);
+        let new_email = format!("{}@moved.example.com", user);
+        let req = post(
+            &format!("/api/V1/users/{}/email-change", user),
+            Some(&token),
+            json!({ "email": new_email }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert!(status.is_success(), "{}", status);
+
+        let confirm = link_for(&mailer, &new_email);
+        let cancel = link_for(&mailer, &old_email);
+        assert!(confirm.starts_with("/api/V1/auth/confirm-email-change?token="));
+        assert!(cancel.starts_with("/api/V1/auth/cancel-email-change?token="));
+
+        // Opening either link only shows the pending change.
+        for link in [&confirm, &cancel] {
+            let req = TestRequest::get().uri(link).to_request();
+            let res = test::call_service(&app, req).await;
+            assert_eq!(res.status(), StatusCode::OK);
+            let body: Value = test::read_body_json(res).await;
+            assert_eq!(body["data"]["new_email"], json!(new_email));
+        }
+        assert_eq!(email_of(&pool, user).await, old_email);
+
+        let req = post(
+            "/api/V1/auth/confirm-email-change",
+            None,
+            json!({ "token": token_for(&mailer, &new_email) }),
+        );
+        let status = test::call_service(&app, req.to_request()).await.status();
+        assert_eq!(status, StatusCode::OK);
+
+        let req = TestRequest::get().uri(&confirm).to_request();
+        let status = test::call_service(&app, req).await.status();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test {
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+    use web_server::{
+        users::{dto::GetUserDTO, entity::User},
+        utils::{errors::AppError, query_fields::QueryFields},
+    };
+
+    #[test]
+    fn test_full_rows_require_every_column() {
+        let user = User {
+            id: Some(Uuid::new_v4()),
+            name: Some("Row User".to_string()),
+            ..Default::default()
+        };
+
+        match GetUserDTO::try_from(user) {
+            Err(AppError::InternalServerError(message)) => {
+                assert_eq!(message, "User row is missing the 'email' column")
+            }
+            other => panic!("expected a missing column error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_partial_rows_only_require_the_requested_fields() {
+        let query_fields = QueryFields {
+            fields: Some("id,name".to_string()),
+            include: None,
+        };
+        let fieldset = query_fields.fieldset::<GetUserDTO>().unwrap();
+        let id = Uuid::new_v4();
+
+        let user = User {
+            id: Some(id),
+            name: Some("Row User".to_string()),
+            updated_at: Some(Utc::now()),
+            ..Default::default()
+        };
+        let dto = GetUserDTO::from_row(user, &fieldset).unwrap();
+        assert_eq!(
+            fieldset.project(&dto).unwrap(),
+            json!({ "id": id, "name": "Row User" })
+        );
+
+        let user = User {
+            id: Some(id),
+            ..Default::default()
+        };
+        assert!(matches!(
+            GetUserDTO::from_row(user, &fieldset),
+            Err(AppError::InternalServerError(_))
+        ));
+    }
+}
@@ -0,0 +1,301 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        app_state, connect, connect_as_app, insert_organization, insert_user, request_as,
+        RecordingMailer,
+    };
+    use actix_web::{
+        http::StatusCode,
+        test::{self, TestRequest},
+        web, App,
+    };
+    use chrono::{Duration, Utc};
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::{json, Value};
+    use sqlx::PgPool;
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        auth::{
+            auth_service,
+            dto::{AcceptInviteDto, InviteClaims, InvitePurpose},
+        },
+        invitations::{
+            dto::CreateInvitationDTO,
+            entity::{Invitation, InvitationStatus},
+            invitations_service,
+        },
+        organizations::entity::MembershipRole,
+        router::configure_v1,
+        server::AppState,
+        users::entity::UserRole,
+        utils::{errors::AppError, password::hash_password},
+    };
+
+    /// Organization owned by a new user, returns `(owner, organization)`.
+    async fn organization(pool: &PgPool) -> (Uuid, Uuid) {
+        let owner = insert_user(pool, "Inviting Owner", UserRole::USER).await;
+        let org = insert_organization(pool, &[(owner, MembershipRole::OWNER)]).await;
+        (owner, org)
+    }
+
+    async fn invite(
+        pool: &PgPool,
+        state: &web::Data<AppState>,
+        (owner, org): (Uuid, Uuid),
+        email: String,
+        role: MembershipRole,
+    ) -> Result<Invitation, AppError> {
+        invitations_service::create(
+            pool,
+            state,
+            CreateInvitationDTO {
+                email,
+                role,
+                expires_at: None,
+            },
+            &request_as(owner, UserRole::USER, Some(org)),
+        )
+        .await
+        .map(|response| response.data)
+    }
+
+    async fn accept(
+        pool: &PgPool,
+        state: &web::Data<AppState>,
+        token: String,
+        password: &str,
+    ) -> Result<(), AppError> {
+        auth_service::accept_invite(
+            pool,
+            state,
+            AcceptInviteDto {
+                token,
+                password: password.to_string(),
+                name: Some("Invited User".to_string()),
+            },
+            &TestRequest::default().to_http_request(),
+        )
+        .await
+        .map(|_| ())
+    }
+
+    fn token_in(mailer: &RecordingMailer) -> String {
+        let sent = mailer.sent.lock().unwrap();
+        let body = &sent.last().unwrap().body;
+        let start = body.find("token=").unwrap() + "token=".len();
+        body[start..].split_whitespace().next().unwrap().to_string()
+    }
+
+    fn invite_token(state: &AppState, sub: Uuid, purpose: InvitePurpose) -> String {
+        let claims = InviteClaims {
+            sub,
+            exp: (Utc::now() + Duration::hours(1)).timestamp() as usize,
+            purpose,
+        };
+
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(state.invite_key.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_existing_account_accepts_with_its_current_password() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = app_state(mailer.clone());
+        let inviter = organization(&pool).await;
+
+        // Set before the password policy, it has to keep working.
+        let user = insert_user(&pool, "Existing Account", UserRole::USER).await;
+        sqlx::query("UPDATE users SET password = $1 WHERE id = $2")
+            .bind(hash_password("legacy").unwrap())
+            .bind(user)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let invitation = invite(
+            &app_pool,
+            &state,
+            inviter,
+            format!("{}@example.com", user),
+            MembershipRole::ADMIN,
+        )
+        .await
+        .unwrap();
+        assert!(mailer.sent.lock().unwrap()[0].body.contains(" as admin. "));
+
+        let token = token_in(&mailer);
+        let wrong = accept(&app_pool, &state, token.clone(), "Wr0ng-password").await;
+        assert!(matches!(wrong, Err(AppError::InvalidCredentials(_))));
+
+        accept(&app_pool, &state, token, "legacy").await.unwrap();
+        let role: MembershipRole = sqlx::query_scalar(
+            "SELECT role FROM memberships WHERE organization_id = $1 AND user_id = $2",
+        )
+        .bind(invitation.organization_id)
+        .bind(user)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(role, MembershipRole::ADMIN);
+    }
+
+    #[actix_web::test]
+    async fn test_new_account_is_held_to_the_password_policy() {
+        let app_pool = connect_as_app().await;
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = app_state(mailer.clone());
+        let inviter = organization(&connect().await).await;
+
+        invite(
+            &app_pool,
+            &state,
+            inviter,
+            format!("{}@example.com", Uuid::new_v4()),
+            MembershipRole::MEMBER,
+        )
+        .await
+        .unwrap();
+        let token = token_in(&mailer);
+
+        let weak = accept(&app_pool, &state, token.clone(), "legacy").await;
+        assert!(matches!(weak, Err(AppError::ValidationError(_))));
+
+        accept(&app_pool, &state, token, "Str0ng-password")
+            .await
+            .unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_invite_tokens_are_bound_to_their_purpose() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let state = app_state(Arc::default());
+        let inviter = organization(&pool).await;
+        let invitation = invite(
+            &app_pool,
+            &state,
+            inviter,
+            format!("{}@example.com", Uuid::new_v4()),
+            MembershipRole::MEMBER,
+        )
+        .await
+        .unwrap();
+        let user = insert_user(&pool, "Pending Account", UserRole::USER).await;
+
+        // An account setup token never accepts an invitation and the other way around.
+        let setup = invite_token(&state, invitation.id, InvitePurpose::AccountSetup);
+        let result = accept(&app_pool, &state, setup, "Str0ng-password").await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        let status: InvitationStatus =
+            sqlx::query_scalar("SELECT status FROM invitations WHERE id = $1")
+                .bind(invitation.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(status, InvitationStatus::PENDING);
+
+        let joining = invite_token(&state, user, InvitePurpose::Invitation);
+        let result = accept(&app_pool, &state, joining, "Str0ng-password").await;
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+
+        // Tokens without a purpose are rejected outright.
+        let untyped = encode(
+            &Header::default(),
+            &json!({
+                "sub": invitation.id,
+                "exp": (Utc::now() + Duration::hours(1)).timestamp(),
+            }),
+            &EncodingKey::from_secret(state.invite_key.as_bytes()),
+        )
+        .unwrap();
+        let result = accept(&app_pool, &state, untyped, "Str0ng-password").await;
+        assert!(matches!(result, Err(AppError::Unauthorized(_))));
+    }
+
+    #[actix_web::test]
+    async fn test_members_are_matched_case_insensitively() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let state = app_state(Arc::default());
+        let (owner, org) = organization(&pool).await;
+        let member = insert_user(&pool, "Existing Member", UserRole::USER).await;
+        sqlx::query(
+            "INSERT INTO memberships (organization_id, user_id, role) VALUES ($1, $2, 'MEMBER')",
+        )
+        .bind(org)
+        .bind(member)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let result = invite(
+            &app_pool,
+            &state,
+            (owner, org),
+            format!("{}@EXAMPLE.COM", member.to_string().to_uppercase()),
+            MembershipRole::MEMBER,
+        )
+        .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+    }
+
+    #[actix_web::test]
+    async fn test_invite_link_opens_the_accept_route() {
+        let app_pool = connect_as_app().await;
+        let mailer = Arc::new(RecordingMailer::default());
+        let state = app_state(mailer.clone());
+        let inviter = organization(&connect().await).await;
+        let email = format!("{}@example.com", Uuid::new_v4());
+        invite(
+            &app_pool,
+            &state,
+            inviter,
+            email.clone(),
+            MembershipRole::MEMBER,
+        )
+        .await
+        .unwrap();
+
+        let link = mailer.sent.lock().unwrap()[0]
+            .body
+            .split_whitespace()
+            .find(|word| word.contains("token="))
+            .unwrap()
+            .to_string();
+        let path = link.strip_prefix(state.app_base_url.as_str()).unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(app_pool.clone()))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri(path).to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body["data"]["purpose"], json!("invitation"));
+        assert_eq!(body["data"]["email"], json!(email));
+        assert_eq!(body["data"]["organization_id"], json!(inviter.1));
+
+        let req = test::TestRequest::post()
+            .uri(path)
+            .set_json(json!({
+                "token": token_in(&mailer),
+                "password": "Str0ng-password",
+                "name": "Invited User",
+            }))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
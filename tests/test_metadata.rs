@@ -0,0 +1,243 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use serde_json::{json, Value};
+    use std::{env, sync::Arc};
+    use uuid::Uuid;
+    use web_server::{
+        organizations::entity::MembershipRole, router::configure_v1, server::AppState,
+        users::entity::UserRole, utils::metadata::MetadataSchema,
+    };
+
+    /// Test state validating metadata against a schema that requires `plan`.
+    fn state_with_schema() -> web::Data<AppState> {
+        let path = env::temp_dir().join(format!("metadata-schema-{}.json", Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            json!({
+                "type": "object",
+                "properties": {
+                    "plan": { "enum": ["free", "pro"] },
+                    "seats": { "type": "integer", "minimum": 1 },
+                },
+                "required": ["plan"],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let schema = MetadataSchema::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut state = Arc::try_unwrap(app_state(Arc::default()).into_inner()).unwrap();
+        state.metadata_schema = Some(Arc::new(schema));
+        web::Data::new(state)
+    }
+
+    fn set_metadata(user: Uuid, token: &str, key: &str, value: Value) -> test::TestRequest {
+        test::TestRequest::put()
+            .uri(&format!("/api/V1/users/{}/metadata/{}", user, key))
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+            .set_json(value)
+    }
+
+    fn delete_metadata(user: Uuid, token: &str, key: &str) -> test::TestRequest {
+        test::TestRequest::delete()
+            .uri(&format!("/api/V1/users/{}/metadata/{}", user, key))
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+    }
+
+    fn find_metadata(user: Uuid, token: &str) -> test::TestRequest {
+        test::TestRequest::get()
+            .uri(&format!("/api/V1/users/{}/metadata", user))
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+    }
+
+    #[actix_web::test]
+    async fn test_metadata_writes_are_validated_against_the_schema() {
+        let pool = connect().await;
+        let state = state_with_schema();
+        let user = insert_user(&pool, "Metadata User", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        // The schema requires `plan`, so nothing else can be stored before it.
+        let res = test::call_service(
+            &app,
+            set_metadata(user, &token, "seats", json!(3)).to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let res = test::call_service(
+            &app,
+            set_metadata(user, &token, "plan", json!("enterprise")).to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let res = test::call_service(
+            &app,
+            set_metadata(user, &token, "plan", json!("pro")).to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let entry: Value = test::read_body_json(res).await;
+        assert_eq!(entry["data"], json!({ "key": "plan", "value": "pro" }));
+
+        let res = test::call_service(
+            &app,
+            set_metadata(user, &token, "seats", json!(0)).to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let res = test::call_service(
+            &app,
+            set_metadata(user, &token, "seats", json!(3)).to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        let res = test::call_service(
+            &app,
+            set_metadata(user, &token, "not%20a%20key", json!(1)).to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+        // Rejected writes are rolled back.
+        let metadata: Value =
+            test::call_and_read_body_json(&app, find_metadata(user, &token).to_request()).await;
+        assert_eq!(metadata["data"], json!({ "plan": "pro", "seats": 3 }));
+    }
+
+    #[actix_web::test]
+    async fn test_metadata_keys_are_deleted_unless_the_schema_requires_them() {
+        let pool = connect().await;
+        let state = state_with_schema();
+        let user = insert_user(&pool, "Metadata Delete User", UserRole::USER).await;
+        let other = insert_user(&pool, "Metadata Delete Other", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let other_token = access_token(&state, other, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        for (key, value) in [("plan", json!("free")), ("seats", json!(2))] {
+            let res =
+                test::call_service(&app, set_metadata(user, &token, key, value).to_request()).await;
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+
+        let res = test::call_service(
+            &app,
+            delete_metadata(user, &other_token, "seats").to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res =
+            test::call_service(&app, delete_metadata(user, &token, "seats").to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let deleted: Value = test::read_body_json(res).await;
+        assert_eq!(deleted["data"], json!({ "plan": "free" }));
+
+        let res =
+            test::call_service(&app, delete_metadata(user, &token, "seats").to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let res =
+            test::call_service(&app, delete_metadata(user, &token, "plan").to_request()).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let metadata: Value =
+            test::call_and_read_body_json(&app, find_metadata(user, &token).to_request()).await;
+        assert_eq!(metadata["data"], json!({ "plan": "free" }));
+    }
+
+    #[actix_web::test]
+    async fn test_users_are_filtered_by_metadata() {
+        let pool = connect().await;
+        let state = state_with_schema();
+        let pro = insert_user(&pool, "Metadata Pro", UserRole::USER).await;
+        let free = insert_user(&pool, "Metadata Free", UserRole::USER).await;
+        let org = insert_organization(
+            &pool,
+            &[
+                (pro, MembershipRole::MEMBER),
+                (free, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        for (user, plan, seats) in [(pro, "pro", 5), (free, "free", 1)] {
+            let token = access_token(&state, user, UserRole::USER, Some(org));
+            for (key, value) in [("plan", json!(plan)), ("seats", json!(seats))] {
+                let res =
+                    test::call_service(&app, set_metadata(user, &token, key, value).to_request())
+                        .await;
+                assert_eq!(res.status(), StatusCode::OK);
+            }
+        }
+
+        let token = access_token(&state, free, UserRole::USER, Some(org));
+        let list = |filter: &str| {
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/users?{}", filter))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+        let ids = |page: Value| -> Vec<Value> {
+            page["data"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|user| user["id"].clone())
+                .collect()
+        };
+
+        let page: Value =
+            test::call_and_read_body_json(&app, list("filter[metadata][plan]=pro")).await;
+        assert_eq!(ids(page), vec![json!(pro)]);
+
+        // Numbers are matched as JSON, so `5` does not match the string "5".
+        let page: Value =
+            test::call_and_read_body_json(&app, list("filter[metadata][seats]=5")).await;
+        assert_eq!(ids(page), vec![json!(pro)]);
+        let page: Value =
+            test::call_and_read_body_json(&app, list("filter[metadata][seats]=%225%22")).await;
+        assert!(ids(page).is_empty());
+
+        let page: Value = test::call_and_read_body_json(
+            &app,
+            list("filter[metadata][plan]=free&filter[metadata][seats]=1"),
+        )
+        .await;
+        assert_eq!(ids(page), vec![json!(free)]);
+    }
+}
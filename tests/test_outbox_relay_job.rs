@@ -0,0 +1,104 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::connect;
+    use async_trait::async_trait;
+    use chrono::Duration;
+    use uuid::Uuid;
+    use web_server::{
+        events::{
+            entity::{DomainEvent, OutboxEvent},
+            event_sink::{ChannelSink, EventSink},
+            events_query,
+        },
+        jobs::outbox_relay_job::{self, OutboxRelayConfig},
+        utils::errors::AppError,
+    };
+
+    fn config() -> OutboxRelayConfig {
+        OutboxRelayConfig {
+            interval: Duration::seconds(1),
+            batch_size: 2,
+            retention: Duration::days(7),
+        }
+    }
+
+    /// Forwards to a channel but rejects every event of one aggregate.
+    #[derive(Debug)]
+    struct RejectingSink {
+        rejected: Uuid,
+        inner: ChannelSink,
+    }
+
+    #[async_trait]
+    impl EventSink for RejectingSink {
+        async fn publish(&self, event: &OutboxEvent) -> Result<(), AppError> {
+            if event.aggregate_id == self.rejected {
+                return Err(AppError::InternalServerError("rejected".to_string()));
+            }
+
+            self.inner.publish(event).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_outbox_relay_keeps_order_per_aggregate() {
+        let pool = connect().await;
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut conn = pool.acquire().await.unwrap();
+        events_query::insert_events(
+            &mut conn,
+            vec![
+                DomainEvent::UserRegistered { user_id: first },
+                DomainEvent::UserRegistered { user_id: second },
+                DomainEvent::user_updated(first, &serde_json::json!({ "name": {} })),
+                DomainEvent::UserDeleted {
+                    user_id: second,
+                    permanent: false,
+                },
+                DomainEvent::PasswordChanged { user_id: first },
+            ],
+        )
+        .await
+        .unwrap();
+        drop(conn);
+
+        let (inner, mut receiver) = ChannelSink::new();
+        let sink = RejectingSink {
+            rejected: second,
+            inner,
+        };
+        outbox_relay_job::run(&pool, &sink, &config())
+            .await
+            .unwrap();
+
+        let mut published = Vec::new();
+        while let Ok(event) = receiver.try_recv() {
+            if event.aggregate_id == first || event.aggregate_id == second {
+                published.push(event.event_type);
+            }
+        }
+        assert_eq!(
+            published,
+            vec!["user.registered", "user.updated", "user.password_changed"]
+        );
+
+        // The rejected head is retried later and holds back the rest of its aggregate.
+        let pending: Vec<(String, i32)> = sqlx::query_as(
+            "SELECT event_type, attempts FROM outbox WHERE aggregate_id = $1 AND published_at IS NULL ORDER BY id",
+        )
+        .bind(second)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            pending,
+            vec![
+                ("user.registered".to_string(), 1),
+                ("user.deleted".to_string(), 0)
+            ]
+        );
+    }
+}
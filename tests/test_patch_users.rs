@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod test {
+    use web_server::{
+        users::dto::{
+            patch_users_dto::{JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE},
+            PatchUserDocument, UserPatch,
+        },
+        utils::errors::AppError,
+    };
+
+    fn document() -> PatchUserDocument {
+        PatchUserDocument {
+            name: "Patched User".to_string(),
+            display_name: None,
+            phone: None,
+            locale: None,
+            timezone: None,
+            bio: None,
+        }
+    }
+
+    fn apply(content_type: &str, body: &str) -> Result<PatchUserDocument, AppError> {
+        UserPatch::parse(content_type, body.as_bytes())?.apply(document())
+    }
+
+    #[test]
+    fn test_status_cannot_be_patched() {
+        let patches = [
+            (MERGE_PATCH_CONTENT_TYPE, r#"{ "status": "ACTIVE" }"#),
+            (
+                JSON_PATCH_CONTENT_TYPE,
+                r#"[{ "op": "add", "path": "/status", "value": "ACTIVE" }]"#,
+            ),
+            (
+                JSON_PATCH_CONTENT_TYPE,
+                r#"[{ "op": "replace", "path": "/status", "value": "ACTIVE" }]"#,
+            ),
+        ];
+
+        for (content_type, body) in patches {
+            let result = apply(content_type, body);
+            assert!(
+                matches!(result, Err(AppError::BadRequest(_))),
+                "{} was accepted",
+                body
+            );
+        }
+    }
+
+    #[test]
+    fn test_email_cannot_be_patched() {
+        let result = apply(
+            MERGE_PATCH_CONTENT_TYPE,
+            r#"{ "email": "new@example.com" }"#,
+        );
+        assert!(matches!(result, Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_profile_fields_can_be_patched() {
+        let result = apply(MERGE_PATCH_CONTENT_TYPE, r#"{ "bio": "Hello" }"#).unwrap();
+        assert_eq!(result.bio.as_deref(), Some("Hello"));
+    }
+}
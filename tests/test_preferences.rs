@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod test {
+    use serde_json::{json, Value};
+    use web_server::{
+        users::entity::{DateFormat, NotificationChannel, NotificationTopic, Preferences},
+        utils::preferences::{resolve, upgrade, PREFERENCES_SCHEMA_VERSION},
+    };
+
+    fn defaults() -> Preferences {
+        serde_json::from_value(json!({
+            "language": "en",
+            "timezone": "UTC",
+            "date_format": "YYYY-MM-DD",
+            "notifications": { "channels": ["email"], "opt_ins": ["security_alerts"] },
+        }))
+        .unwrap()
+    }
+
+    fn document(value: Value) -> serde_json::Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn test_resolve_merges_sparse_document_over_defaults() {
+        let preferences = resolve(
+            &defaults(),
+            &document(json!({
+                "timezone": "Asia/Jakarta",
+                "notifications": { "channels": ["push", "email"] },
+            })),
+        )
+        .unwrap();
+
+        assert_eq!(preferences.language, "en");
+        assert_eq!(preferences.timezone, "Asia/Jakarta");
+        assert_eq!(preferences.date_format, DateFormat::YearMonthDay);
+        assert_eq!(
+            preferences
+                .notifications
+                .channels
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![NotificationChannel::Email, NotificationChannel::Push]
+        );
+        assert_eq!(
+            preferences
+                .notifications
+                .opt_ins
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![NotificationTopic::SecurityAlerts]
+        );
+    }
+
+    #[test]
+    fn test_resolve_rejects_unknown_and_invalid_values() {
+        assert!(resolve(&defaults(), &document(json!({ "theme": "dark" }))).is_err());
+        assert!(resolve(&defaults(), &document(json!({ "timezone": "Mars/Base" }))).is_err());
+        assert!(resolve(&defaults(), &document(json!({ "date_format": "D/M/Y" }))).is_err());
+    }
+
+    #[test]
+    fn test_upgrade_accepts_known_versions_only() {
+        assert!(upgrade(document(json!({})), PREFERENCES_SCHEMA_VERSION).is_ok());
+        assert!(upgrade(document(json!({})), 0).is_err());
+        assert!(upgrade(document(json!({})), PREFERENCES_SCHEMA_VERSION + 1).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{connect, connect_as_app};
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+    use web_server::{
+        jobs::purge_users_job::{self, PurgeMode, PurgeUsersConfig},
+        utils::{
+            password::UNUSABLE_PASSWORD,
+            storage::{LocalStorage, Storage},
+        },
+    };
+
+    async fn insert_deleted_user(pool: &PgPool, deleted_days_ago: i64) -> Uuid {
+        let id = Uuid::new_v4();
+        let deleted_at = Utc::now() - Duration::days(deleted_days_ago);
+
+        sqlx::query(
+            r#"--sql
+            INSERT INTO
+                users (id, name, email, password, status, deleted_at)
+            VALUES
+                ($1, 'Purge Test', $2, 'hash', 'DELETED', $3)
+            "#,
+        )
+        .bind(id)
+        .bind(format!("purge-{}@example.com", id))
+        .bind(deleted_at)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        id
+    }
+
+    async fn user_email(pool: &PgPool, id: Uuid) -> Option<String> {
+        sqlx::query_scalar("SELECT email FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn user_password(pool: &PgPool, id: Uuid) -> String {
+        sqlx::query_scalar("SELECT password FROM users WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn insert_export(pool: &PgPool, storage: &LocalStorage, user_id: Uuid) -> String {
+        let key = format!("exports/{}/archive.zip", user_id);
+        storage.put(&key, b"archive".to_vec()).await.unwrap();
+
+        sqlx::query(
+            r#"--sql
+            INSERT INTO
+                user_exports (user_id, status, format, storage_key)
+            VALUES
+                ($1, 'COMPLETED', 'zip', $2)
+            "#,
+        )
+        .bind(user_id)
+        .bind(&key)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        key
+    }
+
+    fn config(mode: PurgeMode, dry_run: bool) -> PurgeUsersConfig {
+        PurgeUsersConfig {
+            interval: Duration::hours(1),
+            retention: Duration::days(30),
+            batch_size: 2,
+            dry_run,
+            mode,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_purge_users_job() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let storage = LocalStorage::new(std::env::temp_dir().join("purge_users_job_test"));
+
+        let expired = insert_deleted_user(&pool, 45).await;
+        let retained = insert_deleted_user(&pool, 5).await;
+
+        purge_users_job::run(&app_pool, &storage, &config(PurgeMode::Delete, true))
+            .await
+            .unwrap();
+        assert!(user_email(&pool, expired).await.is_some());
+
+        purge_users_job::run(&app_pool, &storage, &config(PurgeMode::Delete, false))
+            .await
+            .unwrap();
+        assert!(user_email(&pool, expired).await.is_none());
+        assert!(user_email(&pool, retained).await.is_some());
+
+        let anonymized = insert_deleted_user(&pool, 45).await;
+        let export = insert_export(&pool, &storage, anonymized).await;
+
+        purge_users_job::run(&app_pool, &storage, &config(PurgeMode::Anonymize, false))
+            .await
+            .unwrap();
+        assert_eq!(
+            user_email(&pool, anonymized).await,
+            Some(format!("deleted-{}@anonymized.invalid", anonymized))
+        );
+        assert_eq!(user_password(&pool, anonymized).await, UNUSABLE_PASSWORD);
+        // Same cleanup as the erasure: the export row and its archive are gone.
+        let exports: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM user_exports WHERE user_id = $1")
+                .bind(anonymized)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(exports, 0);
+        let history: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users_history WHERE id = $1")
+            .bind(anonymized)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(history, 0);
+        assert!(storage.get(&export).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod test {
+    use web_server::utils::{
+        errors::AppError,
+        query_paginaton::{QueryPagination, DEFAULT_PAGE_LIMIT, MAX_PAGE_LIMIT},
+        query_sort::{SortDirection, SortKey},
+    };
+
+    fn pagination(limit: Option<i64>, page: Option<i64>) -> QueryPagination {
+        QueryPagination {
+            limit,
+            page,
+            sort: None,
+            after: None,
+            before: None,
+        }
+    }
+
+    #[test]
+    fn test_paginate_defaults_and_caps_the_limit() {
+        assert_eq!(
+            pagination(None, None).paginate().unwrap(),
+            (DEFAULT_PAGE_LIMIT, 0, 1)
+        );
+        assert_eq!(
+            pagination(Some(25), Some(3)).paginate().unwrap(),
+            (25, 50, 3)
+        );
+        assert_eq!(
+            pagination(Some(MAX_PAGE_LIMIT + 1), Some(2))
+                .paginate()
+                .unwrap(),
+            (MAX_PAGE_LIMIT, MAX_PAGE_LIMIT, 2)
+        );
+    }
+
+    #[test]
+    fn test_paginate_rejects_out_of_range_values() {
+        for (limit, page) in [
+            (Some(0), None),
+            (Some(-1), None),
+            (None, Some(0)),
+            (None, Some(-5)),
+            (Some(MAX_PAGE_LIMIT), Some(i64::MAX)),
+            (None, Some(i64::MAX / 2)),
+        ] {
+            let result = pagination(limit, page).paginate();
+            assert!(
+                matches!(result, Err(AppError::BadRequest(_))),
+                "limit={:?} page={:?}",
+                limit,
+                page
+            );
+        }
+    }
+
+    #[test]
+    fn test_cursor_pages_start_at_offset_zero() {
+        let mut query = pagination(Some(5), Some(4));
+        query.after = Some("cursor".to_string());
+
+        assert_eq!(query.paginate().unwrap(), (5, 0, 4));
+    }
+
+    #[test]
+    fn test_cursor_must_be_valid_and_match_the_sort() {
+        let keys = [SortKey {
+            column: "id",
+            direction: SortDirection::Asc,
+        }];
+
+        let mut both = pagination(None, None);
+        both.after = Some("a".to_string());
+        both.before = Some("b".to_string());
+        assert!(matches!(both.cursor(&keys), Err(AppError::BadRequest(_))));
+
+        let mut garbage = pagination(None, None);
+        garbage.after = Some("not a cursor".to_string());
+        assert!(matches!(
+            garbage.cursor(&keys),
+            Err(AppError::BadRequest(_))
+        ));
+
+        assert!(pagination(None, None).cursor(&keys).unwrap().is_none());
+    }
+}
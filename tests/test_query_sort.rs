@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod test {
+    use web_server::{
+        users::dto::GetUserDTO,
+        utils::query_sort::{parse_sort, SortDirection, SortKey},
+    };
+
+    fn key(column: &'static str, direction: SortDirection) -> SortKey {
+        SortKey { column, direction }
+    }
+
+    #[test]
+    fn test_parse_sort_keeps_order_and_adds_tiebreaker() {
+        let keys = parse_sort::<GetUserDTO>(Some("-created_at,name")).unwrap();
+
+        assert_eq!(
+            keys,
+            vec![
+                key("created_at", SortDirection::Desc),
+                key("name", SortDirection::Asc),
+                key("id", SortDirection::Asc),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_sort_defaults() {
+        let keys = parse_sort::<GetUserDTO>(None).unwrap();
+
+        assert_eq!(
+            keys,
+            vec![
+                key("created_at", SortDirection::Desc),
+                key("id", SortDirection::Asc),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_sort_rejects_unknown_and_duplicate_columns() {
+        assert!(parse_sort::<GetUserDTO>(Some("password")).is_err());
+        assert!(parse_sort::<GetUserDTO>(Some("name; DROP TABLE users")).is_err());
+        assert!(parse_sort::<GetUserDTO>(Some("name,-name")).is_err());
+    }
+}
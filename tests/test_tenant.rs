@@ -0,0 +1,200 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use serde_json::{json, Value};
+    use sqlx::PgConnection;
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        audit::{audit_query, entity::AuditAction},
+        organizations::entity::MembershipRole,
+        router::configure_v1,
+        users::entity::UserRole,
+        utils::{audit::AuditContext, tenant::Tenant},
+    };
+
+    async fn visible(conn: &mut PgConnection, ids: &[Uuid]) -> Vec<Uuid> {
+        sqlx::query_scalar("SELECT id FROM users WHERE id = ANY($1) ORDER BY name DESC")
+            .bind(ids)
+            .fetch_all(conn)
+            .await
+            .unwrap()
+    }
+
+    #[actix_web::test]
+    async fn test_removed_member_loses_access_to_the_organization() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let member = insert_user(&pool, "Tenant Member", UserRole::USER).await;
+        let other = insert_user(&pool, "Tenant Other", UserRole::USER).await;
+        let org = insert_organization(
+            &pool,
+            &[
+                (member, MembershipRole::MEMBER),
+                (other, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let token = access_token(&state, member, UserRole::USER, Some(org));
+        let request = || {
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/users/{}", other))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        sqlx::query("DELETE FROM memberships WHERE organization_id = $1 AND user_id = $2")
+            .bind(org)
+            .bind(member)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let err = test::try_call_service(&app, request()).await.unwrap_err();
+        assert_eq!(
+            err.as_response_error().status_code(),
+            StatusCode::UNAUTHORIZED
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_listing_without_an_organization_is_forbidden() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Tenant Without Organization", UserRole::USER).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let get = |uri: String| {
+            test::TestRequest::get()
+                .uri(&uri)
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, get("/api/V1/users".to_string())).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let body: Value = test::read_body_json(res).await;
+        assert!(body.to_string().contains("No active organization"));
+
+        // The caller's own record needs no organization.
+        let res = test::call_service(&app, get(format!("/api/V1/users/{}", user))).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_audit_events_are_scoped_to_the_organization() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let admin = insert_user(&pool, "Tenant Admin", UserRole::ADMIN).await;
+        let inside = insert_user(&pool, "Tenant Inside", UserRole::USER).await;
+        let outside = insert_user(&pool, "Tenant Outside", UserRole::USER).await;
+        let org = insert_organization(
+            &pool,
+            &[
+                (admin, MembershipRole::OWNER),
+                (inside, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        // Inside is a member of both, what happens to it in the other one stays there.
+        let other_org = insert_organization(
+            &pool,
+            &[
+                (outside, MembershipRole::OWNER),
+                (inside, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+
+        let actor = Uuid::new_v4();
+        let mut conn = pool.acquire().await.unwrap();
+        for (organization_id, target) in [(org, inside), (other_org, outside), (other_org, inside)]
+        {
+            audit_query::insert_audit_event(
+                &mut conn,
+                &AuditContext::system()
+                    .with_actor(actor)
+                    .with_organization(organization_id),
+                AuditAction::UserUpdated,
+                target,
+                json!({}),
+            )
+            .await
+            .unwrap();
+        }
+        drop(conn);
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+        let token = access_token(&state, admin, UserRole::ADMIN, Some(org));
+        let req = test::TestRequest::get()
+            .uri(&format!(
+                "/api/V1/admin/audit-events?filter[actor_id]={}",
+                actor
+            ))
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(body["count"], json!(1));
+        assert_eq!(body["data"][0]["target_id"], json!(inside));
+    }
+
+    #[actix_web::test]
+    async fn test_row_level_security_fails_closed() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let member = insert_user(&pool, "Tenant Visible", UserRole::USER).await;
+        let stranger = insert_user(&pool, "Tenant Hidden", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(member, MembershipRole::OWNER)]).await;
+        insert_organization(&pool, &[(stranger, MembershipRole::OWNER)]).await;
+
+        // Neither a tenant nor the opt-out, as for a query that misses both.
+        let mut conn = app_pool.acquire().await.unwrap();
+        assert_eq!(
+            visible(&mut conn, &[member, stranger]).await,
+            Vec::<Uuid>::new()
+        );
+        drop(conn);
+
+        let mut tx = Tenant::Organization(org).begin(&app_pool).await.unwrap();
+        assert_eq!(visible(&mut tx, &[member, stranger]).await, vec![member]);
+        tx.rollback().await.unwrap();
+
+        let mut tx = Tenant::Unscoped.begin(&app_pool).await.unwrap();
+        assert_eq!(
+            visible(&mut tx, &[member, stranger]).await,
+            vec![member, stranger]
+        );
+        tx.rollback().await.unwrap();
+    }
+}
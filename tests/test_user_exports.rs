@@ -0,0 +1,208 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{access_token, app_state, connect, connect_as_app, insert_user};
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use chrono::Duration;
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        jobs::user_export_job::{self, UserExportConfig},
+        router::configure_v1,
+        users::entity::UserRole,
+    };
+
+    fn config() -> UserExportConfig {
+        UserExportConfig {
+            interval: Duration::seconds(5),
+            retention: Duration::days(1),
+        }
+    }
+
+    /// Path and query of an absolute download link, the test service ignores the host.
+    fn download_path(download_url: &str) -> String {
+        let start = download_url.find("/api/V1/").unwrap();
+        download_url[start..].to_string()
+    }
+
+    #[actix_web::test]
+    async fn test_export_is_built_and_downloaded_by_its_owner_only() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Export User", UserRole::USER).await;
+        let other = insert_user(&pool, "Export Other", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let other_token = access_token(&state, other, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let request_export = |token: &str| {
+            test::TestRequest::post()
+                .uri(&format!("/api/V1/users/{}/export", user))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .set_json(json!({ "format": "json" }))
+                .to_request()
+        };
+        let find_export = |caller, token: &str, export_id: &str| {
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/users/{}/exports/{}", caller, export_id))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, request_export(&other_token)).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let queued: Value = test::call_and_read_body_json(&app, request_export(&token)).await;
+        let export_id = queued["data"]["id"].as_str().unwrap().to_string();
+        assert_eq!(queued["data"]["status"], "PENDING");
+        assert_eq!(queued["data"]["download_url"], Value::Null);
+
+        // A second request while the first is queued returns the same export.
+        let again: Value = test::call_and_read_body_json(&app, request_export(&token)).await;
+        assert_eq!(again["data"]["id"], queued["data"]["id"]);
+
+        let pending: Value =
+            test::call_and_read_body_json(&app, find_export(user, &token, &export_id)).await;
+        assert_eq!(pending["data"]["status"], "PENDING");
+        assert_eq!(pending["data"]["download_url"], Value::Null);
+
+        user_export_job::run(&pool, state.storage.as_ref(), &config())
+            .await
+            .unwrap();
+
+        let completed: Value =
+            test::call_and_read_body_json(&app, find_export(user, &token, &export_id)).await;
+        assert_eq!(completed["data"]["status"], "COMPLETED");
+        assert!(completed["data"]["expires_at"].is_string());
+        let download_url = completed["data"]["download_url"].as_str().unwrap();
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&download_path(download_url))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+        let document: Value = serde_json::from_slice(&test::read_body(res).await).unwrap();
+        assert_eq!(document["profile"]["id"], json!(user));
+
+        // Another user can neither read the export status nor reuse the link for theirs.
+        let res = test::call_service(&app, find_export(user, &other_token, &export_id)).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = test::call_service(&app, find_export(other, &other_token, &export_id)).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        let other_export: Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::post()
+                .uri(&format!("/api/V1/users/{}/export", other))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", other_token)))
+                .set_json(json!({}))
+                .to_request(),
+        )
+        .await;
+        let other_id = other_export["data"]["id"].as_str().unwrap();
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&download_path(download_url).replace(&export_id, other_id))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/exports/{}/download", export_id))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn test_expired_export_link_stops_working_and_archive_is_removed() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Export Expired User", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let find_export = |export_id: &str| {
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/users/{}/exports/{}", user, export_id))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let queued: Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::post()
+                .uri(&format!("/api/V1/users/{}/export", user))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .set_json(json!({ "format": "zip" }))
+                .to_request(),
+        )
+        .await;
+        let export_id = queued["data"]["id"].as_str().unwrap().to_string();
+        let export_uuid: Uuid = export_id.parse().unwrap();
+
+        user_export_job::run(&pool, state.storage.as_ref(), &config())
+            .await
+            .unwrap();
+
+        let completed: Value = test::call_and_read_body_json(&app, find_export(&export_id)).await;
+        assert_eq!(completed["data"]["status"], "COMPLETED");
+        let link = download_path(completed["data"]["download_url"].as_str().unwrap());
+        let key: String = sqlx::query_scalar("SELECT storage_key FROM user_exports WHERE id = $1")
+            .bind(export_uuid)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(state.storage.get(&key).await.unwrap().is_some());
+
+        sqlx::query(
+            "UPDATE user_exports SET expires_at = now() - interval '1 minute' WHERE id = $1",
+        )
+        .bind(export_uuid)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let expiring: Value = test::call_and_read_body_json(&app, find_export(&export_id)).await;
+        assert_eq!(expiring["data"]["download_url"], Value::Null);
+        let res = test::call_service(&app, test::TestRequest::get().uri(&link).to_request()).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+        user_export_job::run(&pool, state.storage.as_ref(), &config())
+            .await
+            .unwrap();
+
+        let expired: Value = test::call_and_read_body_json(&app, find_export(&export_id)).await;
+        assert_eq!(expired["data"]["status"], "EXPIRED");
+        assert!(state.storage.get(&key).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,163 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use chrono::{DateTime, SecondsFormat, Utc};
+    use serde_json::Value;
+    use sqlx::PgPool;
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        organizations::entity::MembershipRole, router::configure_v1, users::entity::UserRole,
+    };
+
+    /// Renames the user in its own transaction and returns a time before the rename.
+    async fn rename(pool: &PgPool, id: Uuid, name: &str) -> DateTime<Utc> {
+        let before = Utc::now();
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+
+        sqlx::query("UPDATE users SET name = $2 WHERE id = $1")
+            .bind(id)
+            .bind(name)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        before
+    }
+
+    fn get(uri: String, token: &str) -> test::TestRequest {
+        test::TestRequest::get()
+            .uri(&uri)
+            .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+    }
+
+    fn as_of(time: DateTime<Utc>) -> String {
+        time.to_rfc3339_opts(SecondsFormat::Micros, true)
+    }
+
+    #[actix_web::test]
+    async fn test_history_lists_versions_and_reads_them_back() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "History V1", UserRole::USER).await;
+        let admin = insert_user(&pool, "History Admin", UserRole::ADMIN).await;
+        let org = insert_organization(
+            &pool,
+            &[
+                (user, MembershipRole::MEMBER),
+                (admin, MembershipRole::OWNER),
+            ],
+        )
+        .await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let first = rename(&pool, user, "History V2").await;
+        let second = rename(&pool, user, "History V3").await;
+
+        for token in [
+            access_token(&state, user, UserRole::USER, Some(org)),
+            access_token(&state, admin, UserRole::ADMIN, Some(org)),
+        ] {
+            let history: Value = test::call_and_read_body_json(
+                &app,
+                get(format!("/api/V1/users/{}/history", user), &token).to_request(),
+            )
+            .await;
+            assert_eq!(history["count"], 2);
+            let versions = history["data"].as_array().unwrap();
+            let names: Vec<&str> = versions
+                .iter()
+                .map(|version| version["record"]["name"].as_str().unwrap())
+                .collect();
+            assert_eq!(names, ["History V2", "History V1"]);
+            assert!(versions[0]["version"].as_i64() > versions[1]["version"].as_i64());
+            assert_eq!(versions[0]["valid_from"], versions[1]["valid_to"]);
+
+            for (time, name) in [(first, "History V1"), (second, "History V2")] {
+                let found: Value = test::call_and_read_body_json(
+                    &app,
+                    get(
+                        format!("/api/V1/users/{}?as_of={}", user, as_of(time)),
+                        &token,
+                    )
+                    .to_request(),
+                )
+                .await;
+                assert_eq!(found["data"]["name"], name);
+            }
+
+            let found: Value = test::call_and_read_body_json(
+                &app,
+                get(format!("/api/V1/users/{}", user), &token).to_request(),
+            )
+            .await;
+            assert_eq!(found["data"]["name"], "History V3");
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_history_is_isolated_per_organization() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "History Tenant", UserRole::USER).await;
+        let admin = insert_user(&pool, "History Other Admin", UserRole::ADMIN).await;
+        insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let other_org = insert_organization(&pool, &[(admin, MembershipRole::OWNER)]).await;
+        let token = access_token(&state, admin, UserRole::ADMIN, Some(other_org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let before = rename(&pool, user, "History Tenant Renamed").await;
+
+        for uri in [
+            format!("/api/V1/users/{}/history", user),
+            format!("/api/V1/users/{}?as_of={}", user, as_of(before)),
+            format!("/api/V1/users/{}/history", Uuid::new_v4()),
+        ] {
+            let res = test::call_service(&app, get(uri.clone(), &token).to_request()).await;
+            assert_eq!(res.status(), StatusCode::NOT_FOUND, "{}", uri);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_unchanged_user_has_an_empty_history() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "History Unchanged", UserRole::USER).await;
+        let token = access_token(&state, user, UserRole::USER, None);
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let history: Value = test::call_and_read_body_json(
+            &app,
+            get(format!("/api/V1/users/{}/history", user), &token).to_request(),
+        )
+        .await;
+        assert_eq!(history["count"], 0);
+        assert!(history["data"].as_array().unwrap().is_empty());
+    }
+}
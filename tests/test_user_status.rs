@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod test {
+    use web_server::users::entity::UserStatus;
+
+    const ALL: [UserStatus; 5] = [
+        UserStatus::ACTIVE,
+        UserStatus::DELETED,
+        UserStatus::SUSPENDED,
+        UserStatus::LOCKED,
+        UserStatus::PENDING,
+    ];
+
+    #[test]
+    fn test_pending_is_only_entered_on_creation() {
+        for status in ALL {
+            assert!(!status.can_transition_to(&UserStatus::PENDING));
+        }
+    }
+
+    #[test]
+    fn test_every_status_can_be_left_and_deleted_accounts_only_restored() {
+        for status in ALL {
+            assert!(!status.transitions().is_empty());
+            assert!(!status.can_transition_to(&status));
+        }
+
+        assert_eq!(UserStatus::DELETED.transitions(), &[UserStatus::ACTIVE]);
+        assert!(!UserStatus::SUSPENDED.can_transition_to(&UserStatus::LOCKED));
+        assert!(UserStatus::LOCKED.can_transition_to(&UserStatus::ACTIVE));
+    }
+}
@@ -0,0 +1,312 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use web_server::{
+        organizations::entity::MembershipRole, router::configure_v1, users::entity::UserRole,
+        utils::password::hash_password,
+    };
+
+    #[actix_web::test]
+    async fn test_inactive_status_filters_are_admin_only() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Listing User", UserRole::USER).await;
+        let admin = insert_user(&pool, "Listing Admin", UserRole::ADMIN).await;
+        let org = insert_organization(
+            &pool,
+            &[
+                (user, MembershipRole::MEMBER),
+                (admin, MembershipRole::OWNER),
+            ],
+        )
+        .await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let list = |caller, role, status: &str| {
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/users?filter[status]={}", status))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    format!("Bearer {}", access_token(&state, caller, role, Some(org))),
+                ))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, list(user, UserRole::USER, "ACTIVE")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+
+        for status in ["SUSPENDED", "LOCKED", "PENDING", "DELETED"] {
+            let res = test::call_service(&app, list(user, UserRole::USER, status)).await;
+            assert_eq!(res.status(), StatusCode::FORBIDDEN, "{}", status);
+
+            let res = test::call_service(&app, list(admin, UserRole::ADMIN, status)).await;
+            assert_eq!(res.status(), StatusCode::OK, "{}", status);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_cursor_pages_through_every_member_once() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let mut members = Vec::new();
+        for name in ["Cursor A", "Cursor B", "Cursor C", "Cursor D", "Cursor E"] {
+            members.push(insert_user(&pool, name, UserRole::USER).await);
+        }
+        let org = insert_organization(
+            &pool,
+            &members
+                .iter()
+                .map(|id| (*id, MembershipRole::MEMBER))
+                .collect::<Vec<_>>(),
+        )
+        .await;
+        let token = access_token(&state, members[0], UserRole::USER, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let mut names = Vec::new();
+        let mut uri = "/api/V1/users?limit=2&sort=-name".to_string();
+        loop {
+            let req = test::TestRequest::get()
+                .uri(&uri)
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request();
+            let body: Value = test::call_and_read_body_json(&app, req).await;
+            assert_eq!(body["count"], json!(5));
+            names.extend(
+                body["data"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|user| user["name"].as_str().unwrap().to_string()),
+            );
+
+            match body["next_cursor"].as_str() {
+                Some(cursor) => uri = format!("/api/V1/users?limit=2&sort=-name&after={}", cursor),
+                None => break,
+            }
+        }
+
+        assert_eq!(
+            names,
+            vec!["Cursor E", "Cursor D", "Cursor C", "Cursor B", "Cursor A"]
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_search_escapes_highlights_and_validates_trimmed_query() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "<b>Quillon</b> & Co", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let token = access_token(&state, user, UserRole::USER, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let search = |q: &str| {
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/users/search?q={}", q))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let body: Value = test::call_and_read_body_json(&app, search("quillon")).await;
+        assert_eq!(body["count"], json!(1));
+        assert_eq!(
+            body["data"][0]["highlight"],
+            json!(format!(
+                "&lt;b&gt;<mark>Quillon</mark>&lt;/b&gt; &amp; Co {}@example.com",
+                user
+            ))
+        );
+
+        let res = test::call_service(&app, search("%20%20q%20%20")).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[actix_web::test]
+    async fn test_conditional_requests_prevent_lost_updates() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Versioned User", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let token = access_token(&state, user, UserRole::USER, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let uri = format!("/api/V1/users/{}", user);
+        let authorized = |req: test::TestRequest| {
+            req.uri(&uri)
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+        };
+        let etag_of = |res: &actix_web::dev::ServiceResponse| {
+            res.headers()
+                .get(header::ETAG)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string()
+        };
+        let rename = |name: &str| json!({ "name": name });
+
+        let res = test::call_service(&app, authorized(test::TestRequest::get()).to_request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let etag = etag_of(&res);
+
+        let req = authorized(test::TestRequest::get())
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::NOT_MODIFIED
+        );
+
+        let req = authorized(test::TestRequest::put())
+            .set_json(rename("Unconditional User"))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::PRECONDITION_REQUIRED
+        );
+
+        let req = authorized(test::TestRequest::put())
+            .insert_header((header::IF_MATCH, etag.clone()))
+            .set_json(rename("First Editor"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_ne!(etag_of(&res), etag);
+
+        // A second editor still holding the old version is turned away.
+        let req = authorized(test::TestRequest::put())
+            .insert_header((header::IF_MATCH, etag.clone()))
+            .set_json(rename("Second Editor"))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::PRECONDITION_FAILED
+        );
+
+        let req = authorized(test::TestRequest::delete())
+            .insert_header((header::IF_MATCH, etag))
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, req).await.status(),
+            StatusCode::PRECONDITION_FAILED
+        );
+
+        let name: String = sqlx::query_scalar("SELECT name FROM users WHERE id = $1")
+            .bind(user)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(name, "First Editor");
+    }
+
+    #[actix_web::test]
+    async fn test_password_change_requires_the_current_password() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Password User", UserRole::USER).await;
+        let other = insert_user(&pool, "Password Other", UserRole::USER).await;
+        sqlx::query("UPDATE users SET password = $2 WHERE id = $1")
+            .bind(user)
+            .bind(hash_password("Old-Passw0rd!").unwrap())
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let change = |caller, current: &str, password: &str| {
+            test::TestRequest::put()
+                .uri(&format!("/api/V1/users/{}/password", user))
+                .insert_header((
+                    header::AUTHORIZATION,
+                    format!(
+                        "Bearer {}",
+                        access_token(&state, caller, UserRole::USER, None)
+                    ),
+                ))
+                .set_json(json!({ "current_password": current, "password": password }))
+                .to_request()
+        };
+        let login = |password: &str| {
+            test::TestRequest::post()
+                .uri("/api/V1/auth/login")
+                .set_json(json!({ "email": format!("{}@example.com", user), "password": password }))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, change(other, "Old-Passw0rd!", "New-Passw0rd!")).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = test::call_service(&app, change(user, "Wrong-Passw0rd!", "New-Passw0rd!")).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = test::call_service(&app, change(user, "Old-Passw0rd!", "weak")).await;
+        assert_eq!(res.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let events = |event_type: &'static str| {
+            sqlx::query_scalar::<_, i64>(
+                "SELECT COUNT(*) FROM outbox WHERE aggregate_id = $1 AND event_type = $2",
+            )
+            .bind(user)
+            .bind(event_type)
+            .fetch_one(&pool)
+        };
+        assert_eq!(events("user.password_changed").await.unwrap(), 0);
+
+        let res = test::call_service(&app, change(user, "Old-Passw0rd!", "New-Passw0rd!")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(events("user.password_changed").await.unwrap(), 1);
+
+        let changes: Value = sqlx::query_scalar(
+            "SELECT changes FROM audit_events WHERE target_id = $1 AND action = 'user.password_changed'",
+        )
+        .bind(user)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(!changes.to_string().contains("Passw0rd"));
+
+        let res = test::call_service(&app, login("Old-Passw0rd!")).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        let res = test::call_service(&app, login("New-Passw0rd!")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
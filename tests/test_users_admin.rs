@@ -0,0 +1,447 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+        request_as,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+    use uuid::Uuid;
+    use web_server::{
+        organizations::entity::MembershipRole,
+        router::configure_v1,
+        users::{
+            dto::{
+                bulk_users_dto::BulkUsersDTO,
+                import_users_dto::{ImportRowStatus, ImportUsersQuery},
+                UpdateUserStatusDTO,
+            },
+            entity::{UserRole, UserStatus},
+            users_admin_service, users_service,
+        },
+        utils::{
+            avatar::{avatar_key, AvatarSize},
+            errors::AppError,
+            password::{verify_password, UNUSABLE_PASSWORD},
+        },
+    };
+
+    #[actix_web::test]
+    async fn test_admin_role_is_read_from_the_database() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let user = insert_user(&pool, "Former Admin", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        // Still claims ADMIN, the token was issued before the demotion.
+        let token = access_token(&state, user, UserRole::ADMIN, Some(org));
+        let request = || {
+            test::TestRequest::get()
+                .uri(&format!("/api/V1/admin/users/{}/status-transitions", user))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, request()).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        sqlx::query("UPDATE users SET role = 'ADMIN' WHERE id = $1")
+            .bind(user)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let res = test::call_service(&app, request()).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn test_org_admin_assigns_roles_in_its_organization_only() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let org_admin = insert_user(&pool, "Org A Admin", UserRole::USER).await;
+        let shared = insert_user(&pool, "Shared Member", UserRole::USER).await;
+        let other_owner = insert_user(&pool, "Org B Owner", UserRole::USER).await;
+        let org_a = insert_organization(
+            &pool,
+            &[
+                (org_admin, MembershipRole::ADMIN),
+                (shared, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        let org_b = insert_organization(
+            &pool,
+            &[
+                (other_owner, MembershipRole::OWNER),
+                (shared, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+        let bulk = |user, org, role: &str| {
+            test::TestRequest::post()
+                .uri("/api/V1/admin/users/bulk")
+                .insert_header((
+                    header::AUTHORIZATION,
+                    format!(
+                        "Bearer {}",
+                        access_token(&state, user, UserRole::USER, Some(org))
+                    ),
+                ))
+                .set_json(json!({
+                    "operations": [{ "action": "assign_role", "role": role, "ids": [shared] }],
+                }))
+                .to_request()
+        };
+
+        // The platform role is not a membership role.
+        let res = test::call_service(&app, bulk(org_admin, org_a, "ADMIN")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(body["data"]["results"][0]["status"], json!("succeeded"));
+
+        let res = test::call_service(&app, bulk(org_admin, org_a, "OWNER")).await;
+        let body: Value = test::read_body_json(res).await;
+        assert_eq!(body["data"]["results"][0]["status"], json!("failed"));
+
+        let res = test::call_service(&app, bulk(shared, org_b, "ADMIN")).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+        let role: UserRole = sqlx::query_scalar("SELECT role FROM users WHERE id = $1")
+            .bind(shared)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(role, UserRole::USER);
+
+        let memberships: Vec<(Uuid, MembershipRole)> = sqlx::query_as(
+            "SELECT organization_id, role FROM memberships WHERE user_id = $1 ORDER BY role",
+        )
+        .bind(shared)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            memberships,
+            vec![
+                (org_a, MembershipRole::ADMIN),
+                (org_b, MembershipRole::MEMBER)
+            ]
+        );
+    }
+
+    #[actix_web::test]
+    async fn test_org_admin_cannot_change_shared_accounts() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let org_admin = insert_user(&pool, "Org A Admin", UserRole::USER).await;
+        let own = insert_user(&pool, "Org A Only", UserRole::USER).await;
+        let shared = insert_user(&pool, "Shared Deleted", UserRole::USER).await;
+        let other_owner = insert_user(&pool, "Org B Owner", UserRole::USER).await;
+        sqlx::query("UPDATE users SET status = 'DELETED' WHERE id = $1")
+            .bind(shared)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let org_a = insert_organization(
+            &pool,
+            &[
+                (org_admin, MembershipRole::ADMIN),
+                (own, MembershipRole::MEMBER),
+                (shared, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        let org_b = insert_organization(
+            &pool,
+            &[
+                (other_owner, MembershipRole::OWNER),
+                (shared, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/V1/admin/users/bulk")
+            .insert_header((
+                header::AUTHORIZATION,
+                format!(
+                    "Bearer {}",
+                    access_token(&state, org_admin, UserRole::USER, Some(org_a))
+                ),
+            ))
+            .set_json(json!({
+                "mode": "best_effort",
+                "operations": [
+                    { "action": "hard_delete", "ids": [shared] },
+                    { "action": "restore", "ids": [shared] },
+                    { "action": "soft_delete", "ids": [own] },
+                    { "action": "remove_member", "ids": [shared] },
+                ],
+            }))
+            .to_request();
+        let body: Value = test::call_and_read_body_json(&app, req).await;
+        let statuses: Vec<Value> = body["data"]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| result["status"].clone())
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                json!("failed"),
+                json!("failed"),
+                json!("succeeded"),
+                json!("succeeded")
+            ]
+        );
+
+        let status: UserStatus = sqlx::query_scalar("SELECT status FROM users WHERE id = $1")
+            .bind(shared)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(status, UserStatus::DELETED);
+
+        let organizations: Vec<Uuid> =
+            sqlx::query_scalar("SELECT organization_id FROM memberships WHERE user_id = $1")
+                .bind(shared)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(organizations, vec![org_b]);
+    }
+
+    #[actix_web::test]
+    async fn test_hard_delete_removes_stored_files() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let state = app_state(Arc::default());
+        let admin = insert_user(&pool, "Files Admin", UserRole::ADMIN).await;
+        let bulk_deleted = insert_user(&pool, "Files Bulk Deleted", UserRole::USER).await;
+        let self_deleted = insert_user(&pool, "Files Self Deleted", UserRole::USER).await;
+        sqlx::query("UPDATE users SET status = 'DELETED' WHERE id = ANY($1)")
+            .bind(vec![bulk_deleted, self_deleted])
+            .execute(&pool)
+            .await
+            .unwrap();
+        let org = insert_organization(
+            &pool,
+            &[
+                (admin, MembershipRole::OWNER),
+                (bulk_deleted, MembershipRole::MEMBER),
+                (self_deleted, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+
+        let mut keys = Vec::new();
+        for user in [bulk_deleted, self_deleted] {
+            let avatar = avatar_key(user, AvatarSize::ALL[0]);
+            let export = format!("exports/{}/archive.zip", user);
+            for key in [&avatar, &export] {
+                state.storage.put(key, b"file".to_vec()).await.unwrap();
+            }
+            sqlx::query(
+                "INSERT INTO user_exports (user_id, status, format, storage_key) VALUES ($1, 'COMPLETED', 'zip', $2)",
+            )
+            .bind(user)
+            .bind(&export)
+            .execute(&pool)
+            .await
+            .unwrap();
+            keys.extend([avatar, export]);
+        }
+
+        let payload: BulkUsersDTO = serde_json::from_value(json!({
+            "operations": [{ "action": "hard_delete", "ids": [bulk_deleted] }],
+        }))
+        .unwrap();
+        let report = users_admin_service::bulk(
+            &app_pool,
+            &state,
+            payload,
+            &request_as(admin, UserRole::ADMIN, Some(org)),
+        )
+        .await
+        .unwrap();
+        assert!(report.data.committed);
+
+        users_service::delete(
+            &app_pool,
+            &state,
+            self_deleted,
+            &request_as(self_deleted, UserRole::USER, None),
+        )
+        .await
+        .unwrap();
+
+        for key in keys {
+            assert!(state.storage.get(&key).await.unwrap().is_none(), "{}", key);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_import_hashes_every_chunk_in_order() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let admin = insert_user(&pool, "Import Admin", UserRole::ADMIN).await;
+        let org = insert_organization(&pool, &[(admin, MembershipRole::OWNER)]).await;
+
+        let batch = Uuid::new_v4();
+        let mut body = "name,email,password\n".to_string();
+        for i in 0..120 {
+            let password = if i % 6 == 0 { "" } else { "Passw0rd!" };
+            body.push_str(&format!(
+                "Imported {},import-{}-{}@example.com,{}\n",
+                i, batch, i, password
+            ));
+        }
+
+        let report = users_admin_service::import(
+            &connect_as_app().await,
+            &state,
+            "text/csv",
+            body.as_bytes(),
+            ImportUsersQuery { dry_run: false },
+            &request_as(admin, UserRole::ADMIN, Some(org)),
+        )
+        .await
+        .unwrap()
+        .data;
+        assert_eq!((report.created, report.invited), (100, 20));
+
+        let password: String = sqlx::query_scalar("SELECT password FROM users WHERE id = $1")
+            .bind(report.rows[1].id.unwrap())
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(verify_password("Passw0rd!", &password).is_ok());
+
+        for row in report.rows {
+            let (email, password, status): (String, String, UserStatus) =
+                sqlx::query_as("SELECT email, password, status FROM users WHERE id = $1")
+                    .bind(row.id.unwrap())
+                    .fetch_one(&pool)
+                    .await
+                    .unwrap();
+            assert_eq!(Some(email), row.email);
+
+            match row.status {
+                ImportRowStatus::Invited => {
+                    assert_eq!(password, UNUSABLE_PASSWORD);
+                    assert_eq!(status, UserStatus::PENDING);
+                    assert!(row.invite_link.is_some());
+                }
+                _ => assert!(password.starts_with("$argon2id$")),
+            }
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_bulk_spares_the_caller_and_live_accounts() {
+        let pool = connect().await;
+        let app_pool = connect_as_app().await;
+        let admin = insert_user(&pool, "Bulk Admin", UserRole::ADMIN).await;
+        let active = insert_user(&pool, "Bulk Active", UserRole::USER).await;
+        let deleted = insert_user(&pool, "Bulk Deleted", UserRole::USER).await;
+        sqlx::query("UPDATE users SET status = 'DELETED' WHERE id = $1")
+            .bind(deleted)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let org = insert_organization(
+            &pool,
+            &[
+                (admin, MembershipRole::OWNER),
+                (active, MembershipRole::MEMBER),
+                (deleted, MembershipRole::MEMBER),
+            ],
+        )
+        .await;
+        let req = request_as(admin, UserRole::ADMIN, Some(org));
+
+        let payload: BulkUsersDTO = serde_json::from_value(json!({
+            "mode": "best_effort",
+            "operations": [
+                { "action": "assign_role", "role": "MEMBER", "ids": [admin] },
+                { "action": "soft_delete", "ids": [admin] },
+                { "action": "hard_delete", "ids": [active] },
+                { "action": "hard_delete", "ids": [deleted] },
+            ],
+        }))
+        .unwrap();
+        let report =
+            users_admin_service::bulk(&app_pool, &app_state(Arc::default()), payload, &req)
+                .await
+                .unwrap();
+        let statuses: Vec<Value> = serde_json::to_value(&report.data).unwrap()["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| result["status"].clone())
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                json!("failed"),
+                json!("failed"),
+                json!("failed"),
+                json!("succeeded")
+            ]
+        );
+
+        let remaining: Vec<(Uuid, UserRole, UserStatus)> =
+            sqlx::query_as("SELECT id, role, status FROM users WHERE id = ANY($1) ORDER BY name")
+                .bind(vec![admin, active, deleted])
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(
+            remaining,
+            vec![
+                (active, UserRole::USER, UserStatus::ACTIVE),
+                (admin, UserRole::ADMIN, UserStatus::ACTIVE),
+            ]
+        );
+
+        let own_status = users_admin_service::set_status(
+            &app_pool,
+            admin,
+            UpdateUserStatusDTO {
+                status: UserStatus::SUSPENDED,
+                reason: None,
+            },
+            &req,
+        )
+        .await;
+        assert!(matches!(own_status, Err(AppError::Forbidden(_))));
+    }
+}
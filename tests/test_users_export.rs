@@ -0,0 +1,138 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{
+        access_token, app_state, connect, connect_as_app, insert_organization, insert_user,
+    };
+    use actix_web::{
+        http::{header, StatusCode},
+        test, web, App,
+    };
+    use serde_json::Value;
+    use std::{collections::HashSet, sync::Arc};
+    use uuid::Uuid;
+    use web_server::{
+        organizations::entity::MembershipRole, router::configure_v1, users::entity::UserRole,
+    };
+
+    #[actix_web::test]
+    async fn test_export_streams_the_filtered_rows_of_the_tenant_only() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let prefix = format!("Stream {}", Uuid::new_v4().simple());
+
+        let admin = insert_user(&pool, "Stream Admin", UserRole::ADMIN).await;
+        let mut matching = Vec::new();
+        for index in 0..3 {
+            let name = format!("{} {}", prefix, index);
+            matching.push(insert_user(&pool, &name, UserRole::USER).await);
+        }
+        let suspended = insert_user(&pool, &format!("{} suspended", prefix), UserRole::USER).await;
+        sqlx::query("UPDATE users SET status = 'SUSPENDED' WHERE id = $1")
+            .bind(suspended)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let unmatched = insert_user(&pool, "Stream Unmatched", UserRole::USER).await;
+
+        let mut members = vec![
+            (admin, MembershipRole::OWNER),
+            (suspended, MembershipRole::MEMBER),
+            (unmatched, MembershipRole::MEMBER),
+        ];
+        members.extend(matching.iter().map(|id| (*id, MembershipRole::MEMBER)));
+        let org = insert_organization(&pool, &members).await;
+
+        let outsider = insert_user(&pool, &format!("{} outsider", prefix), UserRole::USER).await;
+        insert_organization(&pool, &[(outsider, MembershipRole::MEMBER)]).await;
+
+        let token = access_token(&state, admin, UserRole::ADMIN, Some(org));
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let export = |format: &str| {
+            test::TestRequest::get()
+                .uri(&format!(
+                    "/api/V1/admin/users/export?format={}&filter[name][prefix]={}",
+                    format,
+                    prefix.replace(' ', "%20")
+                ))
+                .insert_header((header::AUTHORIZATION, format!("Bearer {}", token)))
+                .to_request()
+        };
+        let expected: HashSet<String> = matching.iter().map(|id| id.to_string()).collect();
+
+        let res = test::call_service(&app, export("csv")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "text/csv; charset=utf-8"
+        );
+        let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+        let mut lines = body.lines();
+        let columns: Vec<&str> = lines.next().unwrap().split(',').collect();
+        let id_column = columns.iter().position(|column| *column == "id").unwrap();
+        let ids: HashSet<String> = lines
+            .map(|line| line.split(',').nth(id_column).unwrap().to_string())
+            .collect();
+        assert_eq!(body.lines().count(), 1 + matching.len());
+        assert_eq!(ids, expected);
+
+        let res = test::call_service(&app, export("ndjson")).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/x-ndjson"
+        );
+        let body = String::from_utf8(test::read_body(res).await.to_vec()).unwrap();
+        let rows: Vec<Value> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), matching.len());
+        let ids: HashSet<String> = rows
+            .iter()
+            .map(|row| row["id"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(ids, expected);
+        assert!(!body.contains(&outsider.to_string()));
+        assert!(!body.contains(&suspended.to_string()));
+    }
+
+    #[actix_web::test]
+    async fn test_export_requires_an_organization_admin() {
+        let pool = connect().await;
+        let state = app_state(Arc::default());
+        let member = insert_user(&pool, "Stream Member", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(member, MembershipRole::MEMBER)]).await;
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(connect_as_app().await))
+                .app_data(state.clone())
+                .configure(|cfg| configure_v1(cfg, state.clone())),
+        )
+        .await;
+
+        let res = test::call_service(
+            &app,
+            test::TestRequest::get()
+                .uri("/api/V1/admin/users/export?format=csv")
+                .insert_header((
+                    header::AUTHORIZATION,
+                    format!(
+                        "Bearer {}",
+                        access_token(&state, member, UserRole::USER, Some(org))
+                    ),
+                ))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,265 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{connect, insert_organization, insert_user};
+    use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+    use sqlx::PgPool;
+    use std::sync::{Arc, Mutex};
+    use uuid::Uuid;
+    use web_server::{
+        events::{
+            entity::{DomainEvent, OutboxEvent},
+            events_query,
+        },
+        jobs::webhook_delivery_job::{self, WebhookDeliveryConfig},
+        organizations::entity::MembershipRole,
+        users::entity::UserRole,
+        utils::webhook::{
+            is_public_address, sign_payload, validate_webhook_host, SIGNATURE_HEADER,
+            TIMESTAMP_HEADER,
+        },
+        webhooks::{dto::CreateWebhookDTO, entity::WebhookEndpoint, webhooks_query},
+    };
+
+    type Received = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    fn config() -> WebhookDeliveryConfig {
+        WebhookDeliveryConfig {
+            interval: Duration::seconds(1),
+            batch_size: 10,
+            timeout: Duration::seconds(5),
+            max_attempts: 5,
+            disable_after: 2,
+            retention: Duration::days(30),
+            // The stand-in receiver listens on 127.0.0.1.
+            allow_private_networks: true,
+        }
+    }
+
+    /// Stand-in receiver, `/flaky` fails its first request and `/down` every request.
+    async fn receive(
+        req: HttpRequest,
+        body: String,
+        received: web::Data<Received>,
+    ) -> HttpResponse {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let mut received = received.lock().unwrap();
+        received.push((header(TIMESTAMP_HEADER), header(SIGNATURE_HEADER), body));
+
+        match (req.path(), received.len()) {
+            ("/flaky", 1) | ("/down", _) => HttpResponse::InternalServerError().finish(),
+            _ => HttpResponse::NoContent().finish(),
+        }
+    }
+
+    async fn endpoint(pool: &PgPool, organization_id: Uuid, url: String) -> WebhookEndpoint {
+        let mut conn = pool.acquire().await.unwrap();
+        let payload = CreateWebhookDTO {
+            url,
+            description: None,
+            event_types: vec!["user.updated".to_string()],
+        };
+
+        webhooks_query::create_endpoint(&mut conn, organization_id, payload, "whsec_test", None)
+            .await
+            .unwrap()
+    }
+
+    /// Outbox row of a new update event, marked published so the relay leaves it alone.
+    async fn outbox_event(pool: &PgPool, user_id: Uuid) -> OutboxEvent {
+        let mut conn = pool.acquire().await.unwrap();
+        events_query::insert_event(
+            &mut conn,
+            DomainEvent::user_updated(user_id, &json!({ "name": {} })),
+        )
+        .await
+        .unwrap();
+
+        let event: OutboxEvent = sqlx::query_as("SELECT * FROM outbox WHERE aggregate_id = $1")
+            .bind(user_id)
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        events_query::mark_event_published(&mut conn, event.id)
+            .await
+            .unwrap();
+
+        event
+    }
+
+    async fn make_due(pool: &PgPool, endpoints: &[Uuid]) {
+        sqlx::query(
+            "UPDATE webhook_deliveries SET next_attempt_at = $1 WHERE status = 'PENDING' AND endpoint_id = ANY($2)",
+        )
+        .bind(Utc::now())
+        .bind(endpoints)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[actix_web::test]
+    async fn test_webhook_delivery_job_signs_retries_and_disables() {
+        let pool = connect().await;
+
+        let flaky_received: Received = Arc::default();
+        let down_received: Received = Arc::default();
+        let (flaky_data, down_data) = (flaky_received.clone(), down_received.clone());
+        let server = HttpServer::new(move || {
+            App::new()
+                .service(
+                    web::resource("/flaky")
+                        .app_data(web::Data::new(flaky_data.clone()))
+                        .to(receive),
+                )
+                .service(
+                    web::resource("/down")
+                        .app_data(web::Data::new(down_data.clone()))
+                        .to(receive),
+                )
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let port = server.addrs()[0].port();
+        actix_web::rt::spawn(server.run());
+
+        let user = insert_user(&pool, "Webhook User", UserRole::USER).await;
+        let org = insert_organization(&pool, &[(user, MembershipRole::MEMBER)]).await;
+        let other_org = insert_organization(&pool, &[]).await;
+        let flaky = endpoint(&pool, org, format!("http://127.0.0.1:{}/flaky", port)).await;
+        let down = endpoint(&pool, org, format!("http://127.0.0.1:{}/down", port)).await;
+        // Subscribed too, but the user is not a member of its organization.
+        let other = endpoint(&pool, other_org, format!("http://127.0.0.1:{}/flaky", port)).await;
+
+        let event = outbox_event(&pool, user).await;
+        assert_eq!(event.organization_ids, vec![org]);
+        assert_eq!(
+            webhooks_query::enqueue_deliveries(&pool, &event)
+                .await
+                .unwrap(),
+            2
+        );
+        // Publishing the same event again queues nothing.
+        assert_eq!(
+            webhooks_query::enqueue_deliveries(&pool, &event)
+                .await
+                .unwrap(),
+            0
+        );
+
+        let client = config().client();
+        assert_eq!(
+            webhook_delivery_job::run(&pool, &client, &config())
+                .await
+                .unwrap(),
+            0
+        );
+        make_due(&pool, &[flaky.id, down.id]).await;
+        assert_eq!(
+            webhook_delivery_job::run(&pool, &client, &config())
+                .await
+                .unwrap(),
+            1
+        );
+
+        let received = flaky_received.lock().unwrap().clone();
+        assert_eq!(received.len(), 2);
+        for (timestamp, signature, body) in received {
+            let timestamp: i64 = timestamp.parse().unwrap();
+            assert_eq!(
+                signature,
+                sign_payload("whsec_test", timestamp, body.as_bytes())
+            );
+            let body: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(body["id"], json!(event.id));
+            assert_eq!(body["event_type"], json!("user.updated"));
+        }
+
+        let mut conn = pool.acquire().await.unwrap();
+        let flaky = webhooks_query::find_endpoint(&mut conn, org, flaky.id)
+            .await
+            .unwrap();
+        let down = webhooks_query::find_endpoint(&mut conn, org, down.id)
+            .await
+            .unwrap();
+        assert!(flaky.enabled && flaky.consecutive_failures == 0);
+        assert!(!down.enabled && down.disabled_at.is_some());
+        assert_eq!(down_received.lock().unwrap().len(), 2);
+
+        // Pending deliveries of a disabled endpoint wait until it is enabled again.
+        make_due(&pool, &[flaky.id, down.id]).await;
+        webhook_delivery_job::run(&pool, &client, &config())
+            .await
+            .unwrap();
+        assert_eq!(down_received.lock().unwrap().len(), 2);
+
+        for (organization_id, id) in [(org, flaky.id), (org, down.id), (other_org, other.id)] {
+            webhooks_query::delete_endpoint(&mut conn, organization_id, id)
+                .await
+                .unwrap();
+        }
+        sqlx::query("DELETE FROM outbox WHERE id = $1")
+            .bind(event.id)
+            .execute(&mut *conn)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn test_private_addresses_are_refused() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in ["93.184.216.34", "1.1.1.1", "2606:4700:4700::1111"] {
+            assert!(is_public_address(ip.parse().unwrap()), "{}", ip);
+        }
+
+        assert!(validate_webhook_host("http://169.254.169.254/latest/meta-data").is_err());
+        assert!(validate_webhook_host("http://[::1]:8080/hook").is_err());
+        assert!(validate_webhook_host("https://1.1.1.1/hook").is_ok());
+        assert!(validate_webhook_host("https://hooks.example.com/hook").is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_delivery_client_does_not_resolve_to_private_networks() {
+        let server =
+            HttpServer::new(|| App::new().default_service(web::to(HttpResponse::NoContent)))
+                .workers(1)
+                .bind(("127.0.0.1", 0))
+                .unwrap();
+        let port = server.addrs()[0].port();
+        actix_web::rt::spawn(server.run());
+        let url = format!("http://localhost:{}/hook", port);
+
+        let guarded = WebhookDeliveryConfig {
+            allow_private_networks: false,
+            ..config()
+        };
+        assert!(guarded.client().post(&url).send().await.is_err());
+
+        let response = config().client().post(&url).send().await.unwrap();
+        assert!(response.status().is_success());
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{connect, insert_organization, insert_user, request_as};
+    use web_server::{
+        organizations::entity::MembershipRole,
+        users::entity::UserRole,
+        utils::{errors::AppError, query_paginaton::QueryPagination},
+        webhooks::{dto::CreateWebhookDTO, webhooks_service},
+    };
+
+    fn pagination() -> QueryPagination {
+        QueryPagination {
+            limit: Some(100),
+            page: None,
+            sort: None,
+            after: None,
+            before: None,
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_webhooks_are_scoped_to_the_organization() {
+        let pool = connect().await;
+        let admin_a = insert_user(&pool, "Webhook Admin A", UserRole::ADMIN).await;
+        let admin_b = insert_user(&pool, "Webhook Admin B", UserRole::ADMIN).await;
+        let org_a = insert_organization(&pool, &[(admin_a, MembershipRole::OWNER)]).await;
+        let org_b = insert_organization(&pool, &[(admin_b, MembershipRole::OWNER)]).await;
+        let req_a = request_as(admin_a, UserRole::ADMIN, Some(org_a));
+        let req_b = request_as(admin_b, UserRole::ADMIN, Some(org_b));
+
+        let created = webhooks_service::create(
+            &pool,
+            CreateWebhookDTO {
+                url: "https://example.com/hooks".to_string(),
+                description: None,
+                event_types: vec!["user.updated".to_string()],
+            },
+            &req_a,
+        )
+        .await
+        .unwrap()
+        .data
+        .endpoint;
+        assert_eq!(created.organization_id, org_a);
+
+        let listed = webhooks_service::find_all(&pool, pagination(), &req_b)
+            .await
+            .unwrap();
+        assert!(listed.data.iter().all(|endpoint| endpoint.id != created.id));
+
+        let found = webhooks_service::find(&pool, created.id, &req_b).await;
+        assert!(matches!(found, Err(AppError::NotFound(_))));
+        let deliveries =
+            webhooks_service::find_deliveries(&pool, created.id, pagination(), &req_b).await;
+        assert!(matches!(deliveries, Err(AppError::NotFound(_))));
+        let deleted = webhooks_service::delete(&pool, created.id, &req_b).await;
+        assert!(matches!(deleted, Err(AppError::NotFound(_))));
+
+        let deleted = webhooks_service::delete(&pool, created.id, &req_a)
+            .await
+            .unwrap();
+        assert_eq!(deleted.data.id, created.id);
+    }
+}